@@ -0,0 +1,51 @@
+//! Thresholds that relative intensities (%FTP, %threshold pace, %threshold heart rate) are
+//! resolved against.
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Athlete {
+    /// Functional threshold power in watts
+    pub ftp_watts: u16,
+    /// Running threshold pace in seconds per kilometer
+    pub threshold_pace_secs_per_km: f64,
+    /// Lactate threshold heart rate in bpm
+    pub threshold_heart_rate: u16,
+    pub max_heart_rate: u16,
+}
+
+impl Default for Athlete {
+    /// 250W, 4:30/km, 170 and 190 bpm
+    fn default() -> Self {
+        Athlete { ftp_watts: 250, threshold_pace_secs_per_km: 270.0, threshold_heart_rate: 170, max_heart_rate: 190 }
+    }
+}
+
+impl Athlete {
+    pub fn watts(&self, fraction_of_ftp: f64) -> u16 {
+        (fraction_of_ftp * self.ftp_watts as f64).round() as u16
+    }
+
+    pub fn fraction_of_ftp(&self, watts: u16) -> f64 {
+        watts as f64 / self.ftp_watts as f64
+    }
+
+    /// A fraction of threshold speed, so 1.1 is faster than threshold
+    pub fn pace(&self, fraction_of_threshold: f64) -> f64 {
+        self.threshold_pace_secs_per_km / fraction_of_threshold
+    }
+
+    pub fn fraction_of_threshold(&self, secs_per_km: f64) -> f64 {
+        self.threshold_pace_secs_per_km / secs_per_km
+    }
+
+    pub fn heart_rate(&self, fraction_of_threshold: f64) -> u16 {
+        (fraction_of_threshold * self.threshold_heart_rate as f64).round() as u16
+    }
+
+    pub fn heart_rate_of_max(&self, fraction_of_max: f64) -> u16 {
+        (fraction_of_max * self.max_heart_rate as f64).round() as u16
+    }
+
+    pub fn fraction_of_threshold_heart_rate(&self, bpm: u16) -> f64 {
+        bpm as f64 / self.threshold_heart_rate as f64
+    }
+}
@@ -0,0 +1,511 @@
+//! Fluent, non-interactive way to write workouts in code.
+//!
+//! ```
+//! use garmin_json_structure::builder::*;
+//! use garmin_json_structure::garmin::timestamp::SystemClock;
+//!
+//! let workout = swim()
+//!     .name("Threshold 100s")
+//!     .warmup(400.m(), Free)
+//!     .repeat(4, |r| r.main(100.m(), Free).rest(20.s()))
+//!     .cooldown(200.m(), AnyStroke)
+//!     .build(&SystemClock)
+//!     .unwrap();
+//! assert_eq!(workout.estimate(Default::default()).total.distance_in_meters, 1000.0);
+//! ```
+//!
+//! Step ids, orders and child step ids are assigned by `build`, which also fills in the
+//! estimates and refuses workouts that fail validation.
+
+use crate::garmin::author::Author;
+use crate::garmin::estimate::SwimPace;
+use crate::garmin::sport_type::Sport;
+use crate::garmin::timestamp::Clock;
+use crate::garmin::unit::Unit;
+use crate::garmin::validation::{Diagnostic, Issue, Severity};
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_segments::WorkoutSegment;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
+use crate::garmin::workout_steps::equipment_type::EquipmentType;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::{PreferredEndConditionUnit, Unit as DistanceUnit};
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::{Step, StepType};
+use crate::garmin::workout_steps::stroke_type::StrokeType;
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+use std::cell::Cell;
+
+pub use crate::garmin::workout_steps::equipment_type::Equipment;
+pub use crate::garmin::workout_steps::stroke_type::Stroke::{self, *};
+
+/// Step id of the first step, Garmin Connect replaces the ids on upload anyway
+pub const FIRST_STEP_ID: u64 = 9615001364;
+
+/// How a step ends, written as `100.m()`, `25.yd()`, `20.s()`, `2.mins()` or `lap_button()`
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Length {
+    Distance(f32, DistanceUnit),
+    Time(f32),
+    LapButton,
+}
+
+pub fn lap_button() -> Length {
+    Length::LapButton
+}
+
+pub trait Units {
+    fn m(self) -> Length;
+    fn km(self) -> Length;
+    fn yd(self) -> Length;
+    fn s(self) -> Length;
+    fn mins(self) -> Length;
+}
+
+impl Units for f32 {
+    fn m(self) -> Length {
+        Length::Distance(self, DistanceUnit::Meter)
+    }
+
+    fn km(self) -> Length {
+        Length::Distance(self, DistanceUnit::Kilometer)
+    }
+
+    fn yd(self) -> Length {
+        Length::Distance(self, DistanceUnit::Yard)
+    }
+
+    fn s(self) -> Length {
+        Length::Time(self)
+    }
+
+    fn mins(self) -> Length {
+        Length::Time(self * 60.0)
+    }
+}
+
+impl Units for u32 {
+    fn m(self) -> Length {
+        (self as f32).m()
+    }
+
+    fn km(self) -> Length {
+        (self as f32).km()
+    }
+
+    fn yd(self) -> Length {
+        (self as f32).yd()
+    }
+
+    fn s(self) -> Length {
+        (self as f32).s()
+    }
+
+    fn mins(self) -> Length {
+        (self as f32).mins()
+    }
+}
+
+/// Starts a pool swim workout, 25m pool unless `pool` says otherwise
+pub fn swim() -> WorkoutBuilder {
+    WorkoutBuilder::new(Sport::Swimming)
+}
+
+/// Starts a workout for any sport, the pool length only applies to swimming
+pub fn workout(sport: Sport) -> WorkoutBuilder {
+    WorkoutBuilder::new(sport)
+}
+
+/// Step methods shared by the workout and its repeat groups.
+///
+/// Modifiers like `note` and `equipment` apply to the step added last, `build` refuses the
+/// workout if that step is a repeat group or there is none.
+pub trait StepsBuilder: Sized {
+    #[doc(hidden)]
+    fn steps(&mut self) -> &mut Vec<WorkoutStep>;
+
+    /// Misuses found while building, paths relative to these steps
+    #[doc(hidden)]
+    fn errors(&mut self) -> &mut Vec<Diagnostic>;
+
+    fn warmup(self, length: Length, stroke: Stroke) -> Self {
+        self.active(Step::Warmup, length, stroke)
+    }
+
+    fn main(self, length: Length, stroke: Stroke) -> Self {
+        self.active(Step::Main, length, stroke)
+    }
+
+    fn cooldown(self, length: Length, stroke: Stroke) -> Self {
+        self.active(Step::Cooldown, length, stroke)
+    }
+
+    /// The stroke is left out when the workout's sport isn't swimming
+    fn active(mut self, step: Step, length: Length, stroke: Stroke) -> Self {
+        let (end_condition, value) = end_condition(length, Condition::Time);
+        let mut single = ExecutableStepDTO::active_step(
+            0,
+            0,
+            StepType::new(step),
+            Cell::new(None),
+            None,
+            end_condition,
+            value,
+            None,
+            StrokeType::new(Some(stroke)),
+            EquipmentType::default(),
+        );
+        if let Length::Distance(distance, unit) = length {
+            single.set_distance(distance, unit);
+        }
+        self.steps().push(WorkoutStep::Single(single));
+        self
+    }
+
+    /// Rest for a fixed time, or until the lap button is pressed
+    fn rest(mut self, length: Length) -> Self {
+        let (end_condition, value) = end_condition(length, Condition::FixedRest);
+        let single = ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, end_condition, value);
+        self.steps().push(WorkoutStep::Single(single));
+        self
+    }
+
+    /// Rest with a `time` end condition, the way older workouts were written
+    fn timed_rest(mut self, length: Length) -> Self {
+        let (end_condition, value) = end_condition(length, Condition::Time);
+        let single = ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, end_condition, value);
+        self.steps().push(WorkoutStep::Single(single));
+        self
+    }
+
+    /// Rest until `interval` has passed since the start of the previous step, e.g. `@1:45`
+    fn send_off(mut self, interval: Length) -> Self {
+        let (end_condition, value) = end_condition(interval, Condition::FixedRepetition);
+        let single = ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, end_condition, value);
+        self.steps().push(WorkoutStep::Single(single));
+        self
+    }
+
+    fn repeat(mut self, iterations: u8, steps: impl FnOnce(RepeatBuilder) -> RepeatBuilder) -> Self {
+        let repeat = steps(RepeatBuilder { steps: Vec::new(), errors: Vec::new() });
+        let path = format!("workoutSteps[{}]", self.steps().len());
+        for mut error in repeat.errors {
+            error.path = join(&path, &error.path);
+            self.errors().push(error);
+        }
+        let group = RepeatGroupDTO::new(0, 0, 0, iterations, repeat.steps);
+        self.steps().push(WorkoutStep::Repeat(group));
+        self
+    }
+
+    fn note(self, description: &str) -> Self {
+        let description = description.to_string();
+        modify_last(self, "note", |single| single.set_description(Some(description)))
+    }
+
+    fn equipment(self, equipment: Equipment) -> Self {
+        modify_last(self, "equipment", |single| single.set_equipment_type(EquipmentType::new(Some(equipment))))
+    }
+
+    fn target(self, target: StepTarget) -> Self {
+        modify_last(self, "target", |single| single.set_target(target))
+    }
+}
+
+fn end_condition(length: Length, timed: Condition) -> (EndCondition, f32) {
+    match length {
+        Length::Distance(distance, unit) => (EndCondition::new(Condition::Distance), PreferredEndConditionUnit::new(unit).to_meters(distance)),
+        Length::Time(secs) => (EndCondition::new(timed), secs),
+        Length::LapButton => (EndCondition::new(Condition::LapButton), 0.0),
+    }
+}
+
+fn modify_last<B: StepsBuilder>(mut builder: B, modifier: &str, modify: impl FnOnce(&mut ExecutableStepDTO)) -> B {
+    let steps = builder.steps();
+    let path = steps.len().checked_sub(1).map(|last| format!("workoutSteps[{}]", last)).unwrap_or_default();
+    match steps.last_mut() {
+        Some(WorkoutStep::Single(single)) => modify(single),
+        _ => builder.errors().push(Diagnostic {
+            severity: Severity::Error,
+            path,
+            issue: Issue::ModifierWithoutStep(modifier.to_string()),
+        }),
+    }
+    builder
+}
+
+fn clear_strokes(steps: &mut [WorkoutStep]) {
+    for step in steps {
+        match step {
+            WorkoutStep::Single(single) => single.set_stroke_type(StrokeType::new(None)),
+            WorkoutStep::Repeat(repeat) => clear_strokes(repeat.workout_steps_mut()),
+        }
+    }
+}
+
+fn join(parent: &str, path: &str) -> String {
+    if path.is_empty() { parent.to_string() } else { format!("{}.{}", parent, path) }
+}
+
+pub struct RepeatBuilder {
+    steps: Vec<WorkoutStep>,
+    errors: Vec<Diagnostic>,
+}
+
+impl StepsBuilder for RepeatBuilder {
+    fn steps(&mut self) -> &mut Vec<WorkoutStep> {
+        &mut self.steps
+    }
+
+    fn errors(&mut self) -> &mut Vec<Diagnostic> {
+        &mut self.errors
+    }
+}
+
+pub struct WorkoutBuilder {
+    sport: Sport,
+    workout_id: u64,
+    owner_id: u64,
+    name: String,
+    description: Option<String>,
+    pool: Length,
+    pace: SwimPace,
+    steps: Vec<WorkoutStep>,
+    errors: Vec<Diagnostic>,
+}
+
+impl WorkoutBuilder {
+    fn new(sport: Sport) -> Self {
+        WorkoutBuilder {
+            sport,
+            workout_id: 0,
+            owner_id: Author::default().user_profile_pk() as u64,
+            name: String::new(),
+            description: None,
+            pool: 25.m(),
+            pace: SwimPace::default(),
+            steps: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn workout_id(mut self, workout_id: u64) -> Self {
+        self.workout_id = workout_id;
+        self
+    }
+
+    pub fn owner_id(mut self, owner_id: u64) -> Self {
+        self.owner_id = owner_id;
+        self
+    }
+
+    /// Pool length, `25.m()` or `25.yd()`
+    pub fn pool(mut self, length: Length) -> Self {
+        self.pool = length;
+        self
+    }
+
+    /// Pace used for the estimates, 2:00/100m by default
+    pub fn pace(mut self, pace: SwimPace) -> Self {
+        self.pace = pace;
+        self
+    }
+
+    /// Numbers the steps, fills in the estimates and validates the result.
+    ///
+    /// Warnings are left for the caller to query with `Workout::validate`, only errors fail,
+    /// along with misuses of the builder like a `note` after a `repeat`.
+    pub fn build(self, clock: &impl Clock) -> Result<Workout, Vec<Diagnostic>> {
+        let now = clock.now();
+        let mut errors: Vec<Diagnostic> = self
+            .errors
+            .into_iter()
+            .map(|error| Diagnostic { path: join("workoutSegments[0]", &error.path), ..error })
+            .collect();
+        let pool = match self.pool {
+            Length::Distance(..) => self.pool,
+            _ if self.sport != Sport::Swimming => self.pool,
+            _ => {
+                errors.push(Diagnostic { severity: Severity::Error, path: "workout".to_string(), issue: Issue::PoolLengthNotDistance });
+                25.m()
+            }
+        };
+        let mut steps = self.steps;
+        if self.sport != Sport::Swimming {
+            clear_strokes(&mut steps);
+        }
+        let segment = WorkoutSegment::with_sport(1, self.sport.clone(), steps);
+        let mut workout = match (self.sport, pool) {
+            (Sport::Swimming, Length::Distance(pool_length, unit)) => Workout::new_swimming_workout(
+                self.workout_id,
+                self.owner_id,
+                self.name,
+                self.description,
+                now,
+                now,
+                vec![segment],
+                pool_length,
+                Unit::from(unit),
+            ),
+            (sport, _) => Workout::new(
+                sport,
+                self.workout_id,
+                self.owner_id,
+                self.name,
+                self.description,
+                now,
+                now,
+                vec![segment],
+            ),
+        };
+
+        workout.renumber_steps(FIRST_STEP_ID);
+        workout.fill_estimates(self.pace);
+
+        errors.extend(workout.validate().into_iter().filter(|diagnostic| diagnostic.severity == Severity::Error));
+        if errors.is_empty() {
+            Ok(workout)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+impl StepsBuilder for WorkoutBuilder {
+    fn steps(&mut self) -> &mut Vec<WorkoutStep> {
+        &mut self.steps
+    }
+
+    fn errors(&mut self) -> &mut Vec<Diagnostic> {
+        &mut self.errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garmin::timestamp::{self, FixedClock};
+    use std::fs;
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_matches_exported_workout() {
+        let built = swim()
+            .workout_id(1180301830)
+            .name("Test workout")
+            .warmup(400.m(), Free)
+            .rest(lap_button())
+            .repeat(8, |r| r.main(100.m(), Free).rest(15.s()))
+            .rest(lap_button())
+            .cooldown(200.m(), Free)
+            .build(&clock())
+            .unwrap();
+        let expected: Workout =
+            serde_json::from_str(&fs::read_to_string("src/workouts_json/expected_workout.json").unwrap()).unwrap();
+
+        let steps = |workout: &Workout| serde_json::to_value(workout).unwrap()["workoutSegments"][0]["workoutSteps"].clone();
+        let (built_steps, expected_steps) = (steps(&built), steps(&expected));
+        // Garmin leaves a meaningless value on lap button steps
+        let summary = |step: &serde_json::Value| {
+            let condition = step["endCondition"]["conditionTypeKey"].clone();
+            let value = if condition == "lap.button" { serde_json::Value::Null } else { step["endConditionValue"].clone() };
+            (step["stepOrder"].clone(), step["stepType"]["stepTypeKey"].clone(), condition, value)
+        };
+        for (built_step, expected_step) in built_steps.as_array().unwrap().iter().zip(expected_steps.as_array().unwrap()) {
+            assert_eq!(summary(built_step), summary(expected_step));
+        }
+        assert_eq!(built.estimate(SwimPace::default()).total, expected.estimate(SwimPace::default()).total);
+    }
+
+    #[test]
+    fn test_modifiers_and_yards() {
+        let workout = swim()
+            .pool(25.yd())
+            .repeat(4, |r| r.main(50.yd(), Free).equipment(Equipment::Kickboard).note("kick").rest(10.s()))
+            .build(&clock())
+            .unwrap();
+        let json = serde_json::to_value(&workout).unwrap();
+        let kick = &json["workoutSegments"][0]["workoutSteps"][0]["workoutSteps"][0];
+
+        assert_eq!(kick["description"], "kick");
+        assert_eq!(kick["equipmentType"]["equipmentTypeKey"], "kickboard");
+        assert_eq!(kick["preferredEndConditionUnit"]["unitKey"], "yard");
+        assert_eq!(json["poolLengthUnit"]["unitKey"], "yard");
+        assert_eq!(kick["childStepId"], 1);
+    }
+
+    #[test]
+    fn test_invalid_workout_is_refused() {
+        let result = swim().pool(10.m()).main(100.m(), Free).build(&clock());
+
+        let errors = result.err().unwrap();
+        assert_eq!(errors[0].issue, Issue::PoolLengthOutOfRange(10.0));
+    }
+
+    #[test]
+    fn test_misuse_is_refused() {
+        let errors = swim()
+            .pool(30.s())
+            .note("no step yet")
+            .repeat(2, |r| r.repeat(2, |r| r.main(50.m(), Free)).equipment(Equipment::Fins))
+            .target(StepTarget::NoTarget)
+            .build(&clock())
+            .err()
+            .unwrap();
+        let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            errors,
+            vec![
+                "error: workoutSegments[0]: note must follow a single step",
+                "error: workoutSegments[0].workoutSteps[0].workoutSteps[0]: equipment must follow a single step",
+                "error: workoutSegments[0].workoutSteps[0]: target must follow a single step",
+                "error: workout: pool length must be a distance",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_too_many_steps_is_refused() {
+        let mut workout = swim();
+        for _ in 0..300 {
+            workout = workout.main(50.m(), Free);
+        }
+        let errors = workout.build(&clock()).err().unwrap();
+        assert_eq!(errors.iter().map(|e| &e.issue).collect::<Vec<_>>(), vec![&Issue::TooManySteps(300)]);
+
+        let mut workout = swim();
+        for _ in 0..260 {
+            workout = workout.repeat(2, |r| r.main(50.m(), Free));
+        }
+        let errors = workout.build(&clock()).err().unwrap();
+        assert_eq!(
+            errors.iter().map(|e| &e.issue).collect::<Vec<_>>(),
+            vec![&Issue::TooManySteps(520), &Issue::TooManyRepeatGroups(260)]
+        );
+    }
+
+    #[test]
+    fn test_strokes_only_in_swims() {
+        let cycling = workout(Sport::Cycling).repeat(2, |r| r.main(5.mins(), AnyStroke)).build(&clock()).unwrap();
+        let json = serde_json::to_value(&cycling).unwrap();
+        let step = &json["workoutSegments"][0]["workoutSteps"][0]["workoutSteps"][0];
+        assert_eq!(step["strokeType"]["strokeTypeKey"], serde_json::Value::Null);
+
+        let swimming = swim().main(50.m(), AnyStroke).build(&clock()).unwrap();
+        let json = serde_json::to_value(&swimming).unwrap();
+        assert_eq!(json["workoutSegments"][0]["workoutSteps"][0]["strokeType"]["strokeTypeKey"], "any_stroke");
+    }
+}
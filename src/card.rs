@@ -0,0 +1,332 @@
+//! Printable pool-deck card: a self-contained HTML page to print and put at the end of the lane.
+
+use crate::garmin::estimate::{estimate_steps, SwimPace};
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::Condition;
+use crate::garmin::workout_steps::equipment_type::Equipment;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::{PreferredEndConditionUnit, Unit as DistanceUnit};
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::WorkoutStep;
+use crate::notation::{format_clock, format_rest, format_swim, is_rest_step};
+use std::fmt::Write;
+
+const STYLE: &str = "
+body { font-family: Helvetica, Arial, sans-serif; font-size: 14pt; margin: 0; }
+.card { max-width: 40em; margin: 1em auto; padding: 0 1em; }
+h1 { font-size: 22pt; margin: 0.5em 0 0.2em; }
+h2 { font-size: 16pt; margin: 0.8em 0 0.2em; border-bottom: 2px solid #000; }
+.meta { margin: 0; color: #444; }
+table { width: 100%; border-collapse: collapse; }
+td { padding: 0.15em 0.3em; vertical-align: top; }
+td.reps, td.distance { text-align: right; white-space: nowrap; }
+td.reps { width: 3em; font-weight: bold; }
+td.rest, td.send-off { white-space: nowrap; color: #333; }
+tr.subtotal td { border-top: 1px solid #000; font-weight: bold; }
+.totals { margin-top: 1em; font-size: 16pt; font-weight: bold; }
+@page { size: A5; margin: 10mm; }
+@media print { .card { margin: 0; max-width: none; } section { break-inside: avoid; } }
+";
+
+/// Renders the workout with warmup, main set and cooldown sections, per-set and grand totals
+/// and the equipment to bring. Durations are estimated from `pace`.
+pub fn render(workout: &Workout, pace: SwimPace) -> String {
+    let unit = match workout.pool_length_unit().distance_unit() {
+        Some(DistanceUnit::Yard) => DistanceUnit::Yard,
+        _ => DistanceUnit::Meter,
+    };
+    let card = Card { unit: PreferredEndConditionUnit::new(unit), pace };
+    let name = escape(workout.workout_name());
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>").unwrap();
+    writeln!(html, "<html lang=\"en\">").unwrap();
+    writeln!(html, "<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>", name, STYLE).unwrap();
+    writeln!(html, "<body>\n<article class=\"card\">\n<header>").unwrap();
+    writeln!(html, "<h1>{}</h1>", name).unwrap();
+    if let Some(description) = workout.description().filter(|description| !description.is_empty()) {
+        writeln!(html, "<p class=\"meta\">{}</p>", escape(description)).unwrap();
+    }
+    if workout.pool_length() > 0.0 {
+        writeln!(html, "<p class=\"meta\">{} {} pool</p>", workout.pool_length(), card.unit_label()).unwrap();
+    }
+    writeln!(html, "</header>").unwrap();
+
+    for (section, steps) in sections(workout) {
+        card.section(&mut html, section, &steps);
+    }
+
+    let equipment = equipment(workout);
+    if !equipment.is_empty() {
+        writeln!(html, "<section class=\"equipment\">\n<h2>Equipment</h2>\n<ul>").unwrap();
+        for equipment in equipment {
+            writeln!(html, "<li>{}</li>", equipment_name(equipment)).unwrap();
+        }
+        writeln!(html, "</ul>\n</section>").unwrap();
+    }
+
+    let total = workout.estimate(pace).total;
+    writeln!(
+        html,
+        "<p class=\"totals\">Total {} {} &middot; about {} at {}/100{}</p>",
+        card.distance(total.distance_in_meters),
+        card.unit_label(),
+        format_clock(total.duration_in_secs),
+        // The pace is per 100 of the pool's unit, 100yd take less time than 100m
+        format_clock(pace.secs_per_100m * card.unit.to_meters(100.0) / 100.0),
+        card.unit_label(),
+    )
+    .unwrap();
+    writeln!(html, "</article>\n</body>\n</html>").unwrap();
+    html
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Section {
+    Warmup,
+    Main,
+    Cooldown,
+}
+
+impl Section {
+    fn title(&self) -> &'static str {
+        match self {
+            Section::Warmup => "Warmup",
+            Section::Main => "Main set",
+            Section::Cooldown => "Cooldown",
+        }
+    }
+}
+
+/// Top level steps grouped by section in workout order, rests stay with the set before them
+fn sections(workout: &Workout) -> Vec<(Section, Vec<&WorkoutStep>)> {
+    let mut sections: Vec<(Section, Vec<&WorkoutStep>)> = Vec::new();
+    for step in workout.workout_segments().iter().flat_map(|segment| segment.workout_steps()) {
+        match (section(step), sections.last_mut()) {
+            (None, Some((_, steps))) => steps.push(step),
+            (Some(section), Some((current, steps))) if *current == section => steps.push(step),
+            (section, _) => sections.push((section.unwrap_or(Section::Main), vec![step])),
+        }
+    }
+    sections
+}
+
+/// `None` for rest steps, a repeat group takes the section of its first active step
+fn section(step: &WorkoutStep) -> Option<Section> {
+    match step {
+        WorkoutStep::Single(single) => match single.step_type().step_type_key {
+            Step::Rest => None,
+            Step::Warmup => Some(Section::Warmup),
+            Step::Cooldown => Some(Section::Cooldown),
+            _ => Some(Section::Main),
+        },
+        WorkoutStep::Repeat(repeat) => Some(repeat.workout_steps().iter().find_map(section).unwrap_or(Section::Main)),
+    }
+}
+
+fn equipment(workout: &Workout) -> Vec<Equipment> {
+    fn collect(steps: &[WorkoutStep], found: &mut Vec<Equipment>) {
+        for step in steps {
+            match step {
+                WorkoutStep::Single(single) => {
+                    if let Some(equipment) = &single.equipment_type().equipment_type_key
+                        && !found.contains(equipment)
+                    {
+                        found.push(equipment.clone());
+                    }
+                }
+                WorkoutStep::Repeat(repeat) => collect(repeat.workout_steps(), found),
+            }
+        }
+    }
+
+    let mut found = Vec::new();
+    for segment in workout.workout_segments() {
+        collect(segment.workout_steps(), &mut found);
+    }
+    found
+}
+
+fn equipment_name(equipment: Equipment) -> String {
+    match equipment {
+        Equipment::Fins => "Fins".to_string(),
+        Equipment::Kickboard => "Kickboard".to_string(),
+        Equipment::Paddles => "Paddles".to_string(),
+        Equipment::PullBuoy => "Pull buoy".to_string(),
+        Equipment::Snorkel => "Snorkel".to_string(),
+        Equipment::Unknown(key) => escape(&key.replace('_', " ")),
+    }
+}
+
+/// One line on the card, a swim with its rests or a repeat group
+struct Row {
+    depth: usize,
+    reps: Option<u8>,
+    set: String,
+    rests: Vec<String>,
+    send_off: Option<String>,
+    distance_in_meters: f32,
+    /// Only a single swim takes the rests that follow it
+    takes_rest: bool,
+}
+
+impl Row {
+    fn new(depth: usize, reps: Option<u8>, set: String, distance_in_meters: f32) -> Self {
+        Row { depth, reps, set, rests: Vec::new(), send_off: None, distance_in_meters, takes_rest: false }
+    }
+
+    fn add_rest(&mut self, rest: &ExecutableStepDTO) {
+        if rest.end_condition().condition_type_key == Condition::FixedRepetition {
+            self.send_off = Some(format_rest(rest));
+        } else {
+            self.rests.push(format_rest(rest));
+        }
+    }
+}
+
+struct Card {
+    unit: PreferredEndConditionUnit,
+    pace: SwimPace,
+}
+
+impl Card {
+    fn unit_label(&self) -> &'static str {
+        match self.unit.unit() {
+            DistanceUnit::Yard => "yd",
+            _ => "m",
+        }
+    }
+
+    fn distance(&self, meters: f32) -> String {
+        format!("{}", self.unit.from_meters(meters).round())
+    }
+
+    fn distance_in_meters(&self, step: &WorkoutStep) -> f32 {
+        estimate_steps(std::slice::from_ref(step), self.pace)[0].distance_in_meters
+    }
+
+    fn section(&self, html: &mut String, section: Section, steps: &[&WorkoutStep]) {
+        let mut rows = Vec::new();
+        self.rows(steps.iter().copied(), 0, &mut rows);
+        let total: f32 = steps.iter().map(|step| self.distance_in_meters(step)).sum();
+
+        writeln!(html, "<section class=\"{}\">", section.title().to_lowercase().replace(' ', "-")).unwrap();
+        writeln!(html, "<h2>{}</h2>\n<table>", section.title()).unwrap();
+        for row in rows {
+            let reps = row.reps.map(|reps| format!("{}&times;", reps)).unwrap_or_default();
+            let distance = if row.distance_in_meters > 0.0 { self.distance(row.distance_in_meters) } else { String::new() };
+            writeln!(
+                html,
+                "<tr><td class=\"reps\">{}</td><td class=\"set\" style=\"padding-left: {}em\">{}</td><td class=\"rest\">{}</td><td class=\"send-off\">{}</td><td class=\"distance\">{}</td></tr>",
+                reps,
+                0.3 + 1.5 * row.depth as f32,
+                escape(&row.set),
+                escape(&row.rests.join(" ")),
+                escape(row.send_off.as_deref().unwrap_or_default()),
+                distance,
+            )
+            .unwrap();
+        }
+        writeln!(
+            html,
+            "<tr class=\"subtotal\"><td></td><td colspan=\"3\">{}</td><td class=\"distance\">{} {}</td></tr>",
+            section.title(),
+            self.distance(total),
+            self.unit_label(),
+        )
+        .unwrap();
+        writeln!(html, "</table>\n</section>").unwrap();
+    }
+
+    fn rows<'a>(&self, steps: impl IntoIterator<Item = &'a WorkoutStep>, depth: usize, rows: &mut Vec<Row>) {
+        let first = rows.len();
+        for step in steps {
+            match step {
+                WorkoutStep::Single(single) if is_rest_step(single) => match rows[first..].last_mut() {
+                    Some(last) if last.depth == depth && last.takes_rest => last.add_rest(single),
+                    _ => {
+                        let mut row = Row::new(depth, None, "Rest".to_string(), 0.0);
+                        row.add_rest(single);
+                        rows.push(row);
+                    }
+                },
+                WorkoutStep::Single(single) => {
+                    let mut row = Row::new(depth, None, format_swim(single), self.distance_in_meters(step));
+                    row.takes_rest = true;
+                    rows.push(row);
+                }
+                WorkoutStep::Repeat(repeat) => self.repeat(repeat, self.distance_in_meters(step), depth, rows),
+            }
+        }
+    }
+
+    /// `8x 100 free r15` fits on one row, anything bigger gets a row per child
+    fn repeat(&self, repeat: &RepeatGroupDTO, distance_in_meters: f32, depth: usize, rows: &mut Vec<Row>) {
+        let reps = Some(repeat.number_of_iterations());
+        match repeat.workout_steps().split_first() {
+            Some((WorkoutStep::Single(swim), rests))
+                if !is_rest_step(swim)
+                    && rests.iter().all(|step| matches!(step, WorkoutStep::Single(rest) if is_rest_step(rest))) =>
+            {
+                let mut row = Row::new(depth, reps, format_swim(swim), distance_in_meters);
+                for rest in rests {
+                    if let WorkoutStep::Single(rest) = rest {
+                        row.add_rest(rest);
+                    }
+                }
+                rows.push(row);
+            }
+            _ => {
+                rows.push(Row::new(depth, reps, String::new(), distance_in_meters));
+                self.rows(repeat.workout_steps(), depth + 1, rows);
+            }
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Units;
+    use crate::garmin::timestamp::SystemClock;
+    use crate::notation;
+
+    #[test]
+    fn test_exported_workout() {
+        let json_string = std::fs::read_to_string("src/workouts_json/expected_workout.json").unwrap();
+        let workout: Workout = serde_json::from_str(&json_string).unwrap();
+        let html = render(&workout, SwimPace::default());
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        let headings: Vec<&str> = html.lines().filter(|line| line.starts_with("<h2>")).collect();
+        assert_eq!(headings, vec!["<h2>Warmup</h2>", "<h2>Main set</h2>", "<h2>Cooldown</h2>"]);
+        assert!(html.contains("<td class=\"reps\">8&times;</td><td class=\"set\" style=\"padding-left: 0.3em\">100 free</td><td class=\"rest\">r15</td>"));
+        assert!(html.contains("<td colspan=\"3\">Main set</td><td class=\"distance\">800 m</td>"));
+        assert!(html.contains("Total 1400 m &middot; about 30:00 at 2:00/100m"));
+        assert!(!html.contains("Equipment"));
+    }
+
+    #[test]
+    fn test_nested_sets_equipment_and_send_offs() {
+        let workout = notation::parse("3x(4x50yd kick w/ board @1:00, 200yd pull) / 100yd \"<easy>\"")
+            .unwrap()
+            .name("Kick & pull")
+            .pool(25.yd())
+            .build(&SystemClock)
+            .unwrap();
+        let html = render(&workout, SwimPace::default());
+
+        assert!(html.contains("<h1>Kick &amp; pull</h1>"));
+        assert!(html.contains("<p class=\"meta\">25 yd pool</p>"));
+        assert!(html.contains("<td class=\"reps\">3&times;</td><td class=\"set\" style=\"padding-left: 0.3em\"></td><td class=\"rest\"></td><td class=\"send-off\"></td><td class=\"distance\">1200</td>"));
+        assert!(html.contains("<td class=\"reps\">4&times;</td><td class=\"set\" style=\"padding-left: 1.8em\">50yd kick w/ board</td><td class=\"rest\"></td><td class=\"send-off\">@1:00</td>"));
+        assert!(html.contains("&quot;&lt;easy&gt;&quot;"));
+        assert!(html.contains("<li>Kickboard</li>\n<li>Pull buoy</li>"));
+        assert!(html.contains("at 1:50/100yd</p>"));
+    }
+}
@@ -0,0 +1,70 @@
+//! Shared by the converters to formats that can't hold everything a Garmin workout can.
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::EndCondition;
+use crate::garmin::workout_steps::equipment_type::EquipmentType;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::step_type::{Step, StepType};
+use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
+use crate::garmin::workout_steps::WorkoutStep;
+use std::cell::Cell;
+use std::fmt;
+
+/// Something a conversion left out or approximated
+#[derive(Debug, PartialEq, Clone)]
+pub struct Loss {
+    /// Step path on the Garmin side, e.g. `workoutSegments[0].workoutSteps[2]`, or the
+    /// position in the other format
+    pub path: String,
+    pub message: String,
+}
+
+impl Loss {
+    pub(crate) fn new(path: &str, message: impl ToString) -> Self {
+        Loss { path: path.to_string(), message: message.to_string() }
+    }
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// An active step, the stroke is only for swimming. Ids are left to `renumber_steps`.
+pub(crate) fn active_step(
+    step_type: Step,
+    end_condition: EndCondition,
+    value: f32,
+    stroke: Option<Stroke>,
+) -> ExecutableStepDTO {
+    ExecutableStepDTO::active_step(
+        0,
+        0,
+        StepType::new(step_type),
+        Cell::new(None),
+        None,
+        end_condition,
+        value,
+        None,
+        StrokeType::new(stroke),
+        EquipmentType::default(),
+    )
+}
+
+/// Every single step of the workout, repeat children included, in order
+pub(crate) fn singles(workout: &Workout) -> Vec<&ExecutableStepDTO> {
+    fn collect<'a>(steps: &'a [WorkoutStep], singles: &mut Vec<&'a ExecutableStepDTO>) {
+        for step in steps {
+            match step {
+                WorkoutStep::Single(single) => singles.push(single),
+                WorkoutStep::Repeat(repeat) => collect(repeat.workout_steps(), singles),
+            }
+        }
+    }
+
+    let mut singles = Vec::new();
+    for segment in workout.workout_segments() {
+        collect(segment.workout_steps(), &mut singles);
+    }
+    singles
+}
@@ -0,0 +1,317 @@
+//! Workout definition files: a short YAML document coaches can keep in git and review in pull
+//! requests, compiled into the full Garmin workout with ids, keys and display orders filled in.
+//!
+//! ```yaml
+//! name: Masters Tuesday
+//! pool: 25yd
+//! notes: Focus on turns
+//! sets:
+//!   - 400 free wu r30          # a set in coach notation, see `notation`
+//!   - reps: 8
+//!     swim: 50
+//!     stroke: kick
+//!     equipment: board
+//!     rest: 20
+//!     notes: fast legs
+//!   - reps: 3
+//!     sets:
+//!       - 100 IM @1:50
+//!       - rest: 1:00
+//!   - swim: 200
+//!     stroke: choice
+//!     type: cooldown
+//! ```
+//!
+//! A file may hold several workouts, one per document, separated by `---`.
+//!
+//! `pool` is `25m` unless given and bare distances, in notation sets too, are in the pool's
+//! unit. A set is either notation or a mapping of
+//! - `swim`: a distance (`50`, `100m`, `50yd`, `1km`), a time (`2:00`) or `lap`
+//! - `reps`: repeats the swim, or the `sets` listed under it
+//! - `stroke`, `type` (`warmup`, `main`, `cooldown`), `equipment` and `notes`
+//! - `rest` after each swim (`20`, `1:00`, `lap`) or a `send_off` (`1:45`); a set with only a
+//!   `rest` is a rest step
+use crate::builder::{self, lap_button, Length, Units, WorkoutBuilder};
+use crate::garmin::workout_steps::equipment_type::Equipment;
+use crate::garmin::workout_steps::preferred_end_condition_unit::Unit as DistanceUnit;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::stroke_type::Stroke;
+use crate::notation::{self, distance_unit, equipment, step_type, stroke, ParseError, Set, Swim};
+use crate::yaml::{self, Node, Value};
+
+/// Compiles each document of a definition file into a workout builder, positions in errors
+/// are in the file
+pub fn compile(text: &str) -> Result<Vec<WorkoutBuilder>, ParseError> {
+    yaml::parse(text)?.iter().map(compile_document).collect()
+}
+
+fn compile_document(document: &Node) -> Result<WorkoutBuilder, ParseError> {
+    let entries = map(document, &["name", "notes", "pool", "sets"])?;
+
+    let name = required(document, entries, "name")?;
+    let mut workout = builder::swim().name(scalar(name)?);
+    if let Some(notes) = get(entries, "notes") {
+        workout = workout.description(scalar(notes)?);
+    }
+    let pool_unit = match get(entries, "pool") {
+        Some(pool) => match length(pool, DistanceUnit::Meter)? {
+            Length::Distance(length, unit) => {
+                workout = workout.pool(Length::Distance(length, unit));
+                unit
+            }
+            _ => return Err(pool.error("pool must be a distance like 25m or 25yd")),
+        },
+        None => DistanceUnit::Meter,
+    };
+
+    let sets = required(document, entries, "sets")?;
+    Ok(notation::emit(workout, Compiler { pool_unit }.sets(sets)?))
+}
+
+struct Compiler {
+    /// Unit of bare distances
+    pool_unit: DistanceUnit,
+}
+
+impl Compiler {
+    fn sets(&self, node: &Node) -> Result<Vec<Set>, ParseError> {
+        let Value::List(items) = &node.value else {
+            return Err(node.error("expected a list of sets"));
+        };
+        if items.is_empty() {
+            return Err(node.error("no sets"));
+        }
+        let mut sets = Vec::new();
+        for item in items {
+            sets.extend(self.set(item)?);
+        }
+        Ok(sets)
+    }
+
+    fn set(&self, node: &Node) -> Result<Vec<Set>, ParseError> {
+        if let Value::Scalar(text) = &node.value {
+            // Notation errors point into the set, moved to where it is in the file
+            return notation::parse_sets(text, self.pool_unit).map_err(|e| {
+                let column = if e.line == 1 { node.column + e.column - 1 } else { e.column };
+                ParseError::new(node.line + e.line - 1, column, e.message)
+            });
+        }
+
+        let entries = map(node, &["reps", "swim", "stroke", "type", "equipment", "rest", "send_off", "notes", "sets"])?;
+        let reps = match get(entries, "reps") {
+            Some(reps) => Some(
+                scalar(reps)?
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|reps| *reps > 0)
+                    .ok_or_else(|| reps.error("reps must be a whole number from 1 to 255"))?,
+            ),
+            None => None,
+        };
+
+        let sets = if let Some(sets) = get(entries, "sets") {
+            if let Some((key, _)) = entries.iter().find(|(key, _)| !matches!(key.as_str(), "reps" | "sets")) {
+                return Err(node.error(format!("`{}` goes on the sets inside a repeat, not the repeat", key)));
+            }
+            self.sets(sets)?
+        } else if let Some(swim) = get(entries, "swim") {
+            self.swim(entries, swim)?
+        } else {
+            match (get(entries, "rest"), entries.len()) {
+                (Some(rest), 1) => vec![Set::Rest(time(rest)?)],
+                _ => return Err(node.error("a set needs `swim`, `sets`, or only a `rest`")),
+            }
+        };
+        Ok(match reps {
+            Some(reps) => vec![Set::Repeat(reps, sets)],
+            None => sets,
+        })
+    }
+
+    /// The swim and the rest after it
+    fn swim(&self, entries: &[(String, Node)], swim: &Node) -> Result<Vec<Set>, ParseError> {
+        let mut set = Swim {
+            step: Step::Main,
+            length: length(swim, self.pool_unit)?,
+            stroke: None,
+            equipment: None,
+            description: Vec::new(),
+        };
+        if let Some(node) = get(entries, "equipment") {
+            set.equipment = Some(named(node, "equipment", equipment)?);
+        }
+        if let Some(node) = get(entries, "stroke") {
+            let word = scalar(node)?.to_lowercase();
+            if word == "kick" {
+                set.description.push(word);
+                set.stroke = Some(Stroke::Drill);
+            } else if word == "pull" {
+                set.stroke = Some(Stroke::Free);
+                set.equipment.get_or_insert(Equipment::PullBuoy);
+            } else {
+                set.stroke = Some(named(node, "stroke", stroke)?);
+            }
+        }
+        if let Some(node) = get(entries, "type") {
+            set.step = named(node, "type", step_type)?;
+        }
+        if let Some(node) = get(entries, "notes") {
+            set.description.push(scalar(node)?.to_string());
+        }
+
+        let mut sets = vec![Set::Swim(set)];
+        match (get(entries, "rest"), get(entries, "send_off")) {
+            (Some(_), Some(send_off)) => return Err(send_off.error("give either `rest` or `send_off`")),
+            (Some(rest), None) => sets.push(Set::Rest(time(rest)?)),
+            (None, Some(send_off)) => sets.push(Set::SendOff(time(send_off)?)),
+            (None, None) => {}
+        }
+        Ok(sets)
+    }
+}
+
+fn map<'a>(node: &'a Node, keys: &[&str]) -> Result<&'a [(String, Node)], ParseError> {
+    let Value::Map(entries) = &node.value else {
+        return Err(node.error("expected `key: value` lines"));
+    };
+    if let Some((key, value)) = entries.iter().find(|(key, _)| !keys.contains(&key.as_str())) {
+        let message = format!("unknown key `{}`, expected one of {}", key, keys.join(", "));
+        return Err(ParseError::new(value.line, 1.max(value.column.saturating_sub(key.len() + 2)), message));
+    }
+    Ok(entries)
+}
+
+fn get<'a>(entries: &'a [(String, Node)], key: &str) -> Option<&'a Node> {
+    entries.iter().find(|(name, _)| name == key).map(|(_, node)| node)
+}
+
+fn required<'a>(parent: &Node, entries: &'a [(String, Node)], key: &str) -> Result<&'a Node, ParseError> {
+    get(entries, key).ok_or_else(|| parent.error(format!("missing `{}`", key)))
+}
+
+fn scalar(node: &Node) -> Result<&str, ParseError> {
+    match &node.value {
+        Value::Scalar(text) if !text.is_empty() => Ok(text),
+        _ => Err(node.error("expected a value")),
+    }
+}
+
+/// A word out of one of the notation vocabularies
+fn named<T>(node: &Node, what: &str, lookup: fn(&str) -> Option<T>) -> Result<T, ParseError> {
+    let text = scalar(node)?;
+    lookup(&text.to_lowercase()).ok_or_else(|| node.error(format!("unknown {} `{}`", what, text)))
+}
+
+/// `50`, `100m`, `50yd`, `1km`, `2:00` or `lap`
+fn length(node: &Node, default_unit: DistanceUnit) -> Result<Length, ParseError> {
+    let text = scalar(node)?.trim();
+    if text.eq_ignore_ascii_case("lap") {
+        return Ok(lap_button());
+    }
+    if text.contains(':') {
+        return time(node);
+    }
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let unit = match unit.trim() {
+        "" => Some(default_unit),
+        unit => distance_unit(unit),
+    };
+    match (number.parse::<f32>(), unit) {
+        (Ok(distance), Some(unit)) if distance > 0.0 => Ok(Length::Distance(distance, unit)),
+        _ => Err(node.error(format!("expected a distance like 100 or 50yd, a time like 2:00 or lap, got `{}`", text))),
+    }
+}
+
+/// Seconds, `m:ss` or `lap`
+fn time(node: &Node) -> Result<Length, ParseError> {
+    let text = scalar(node)?.trim();
+    if text.eq_ignore_ascii_case("lap") {
+        return Ok(lap_button());
+    }
+    // Like notation, `m:ss` has two digits of seconds below a minute
+    let secs = match text.split_once(':') {
+        Some((minutes, seconds)) => match (minutes.parse::<u32>(), seconds.parse::<u32>()) {
+            (Ok(minutes), Ok(secs)) if seconds.len() == 2 && secs < 60 => Some(minutes as f32 * 60.0 + secs as f32),
+            _ => None,
+        },
+        None => text.parse::<f32>().ok(),
+    };
+    match secs {
+        Some(secs) if secs > 0.0 && secs.is_finite() => Ok(secs.s()),
+        _ => Err(node.error(format!("expected seconds, a time like 1:30 or lap, got `{}`", text))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garmin::timestamp::{self, FixedClock};
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_compile_matches_notation() {
+        let text = "\
+name: Masters Tuesday
+pool: 25yd
+notes: Focus on turns
+sets:
+  - 400 free wu r30
+  - reps: 8
+    swim: 50
+    stroke: kick
+    equipment: board
+    rest: 20
+    notes: fast legs
+  - reps: 3
+    sets:
+      - 100 IM @1:50
+      - rest: 1:00
+  - swim: 200
+    stroke: choice
+    type: cooldown
+";
+        let workout = compile(text).unwrap().remove(0).build(&clock()).unwrap();
+        let notation = "400yd free wu r30 / 8x50yd kick w/ board \"fast legs\" r20 / 3x100yd IM @1:50 r1:00 / 200yd cd";
+        // Bare distances are in the pool's unit, in notation sets too
+        assert_eq!(notation::format(&workout), notation);
+        let expected = notation::parse(notation)
+            .unwrap()
+            .name("Masters Tuesday")
+            .description("Focus on turns")
+            .pool(25.yd())
+            .build(&clock())
+            .unwrap();
+        let json = serde_json::to_value(&workout).unwrap();
+        assert_eq!(json, serde_json::to_value(&expected).unwrap());
+        assert_eq!(json["workoutSegments"][0]["workoutSteps"][0]["stepId"], builder::FIRST_STEP_ID);
+        assert_eq!(json["workoutSegments"][0]["workoutSteps"][2]["workoutSteps"][1]["stepOrder"], 5);
+    }
+
+    #[test]
+    fn test_one_workout_per_document() {
+        let text = "name: A\nsets:\n  - 100 free\n---\nname: B\npool: 25yd\nsets:\n  - 4x50 r15\n";
+        let workouts: Vec<String> =
+            compile(text).unwrap().into_iter().map(|builder| notation::format(&builder.build(&clock()).unwrap())).collect();
+        assert_eq!(workouts, vec!["100 free", "4x50yd r15"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| compile(text).err().map(|e| e.to_string());
+        assert_eq!(error("name: A\n"), Some("1:1: missing `sets`".to_string()));
+        assert_eq!(
+            error("name: A\nsets:\n  - swim: 100\n    strok: free\n"),
+            Some("4:5: unknown key `strok`, expected one of reps, swim, stroke, type, equipment, rest, send_off, notes, sets".to_string())
+        );
+        assert_eq!(error("name: A\nsets:\n  - swim: 100\n    stroke: crawl\n"), Some("4:13: unknown stroke `crawl`".to_string()));
+        assert_eq!(error("name: A\nsets:\n  - reps: 0\n    swim: 50\n"), Some("3:11: reps must be a whole number from 1 to 255".to_string()));
+        assert_eq!(error("name: A\nsets:\n  - 4x50 free r\n"), Some("3:15: expected a time like 20 or 1:30, or lap".to_string()));
+        assert_eq!(error("name: A\nsets:\n  - 100 @99999999:00\n"), Some("3:10: time '99999999:00' is too long".to_string()));
+        assert_eq!(error("name: A\nsets:\n  - rest: 1:75\n"), Some("3:11: expected seconds, a time like 1:30 or lap, got `1:75`".to_string()));
+        assert_eq!(error("name: A\nsets:\n  - reps: 2\n    rest: 20\n    sets:\n      - 50\n"), Some("3:5: `rest` goes on the sets inside a repeat, not the repeat".to_string()));
+    }
+}
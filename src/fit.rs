@@ -0,0 +1,180 @@
+//! FIT workout files, the binary format watches load from `/GARMIN/NewFiles`.
+//!
+//! Only the messages a workout file needs are supported: `file_id`, `workout` and
+//! `workout_step`. Values follow the FIT profile, see the SDK's `Profile.xlsx`.
+
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout_steps::equipment_type::Equipment;
+use crate::garmin::workout_steps::stroke_type::Stroke;
+
+mod decode;
+mod encode;
+
+pub use decode::{decode, FitError};
+pub use encode::encode;
+
+/// FIT timestamps count seconds from 1989-12-31T00:00:00Z
+pub(crate) const FIT_EPOCH: i64 = 631065600;
+pub(crate) const PROTOCOL_VERSION: u8 = 0x10;
+pub(crate) const PROFILE_VERSION: u16 = 2132;
+
+// Global message numbers
+pub(crate) const FILE_ID: u16 = 0;
+pub(crate) const WORKOUT: u16 = 26;
+pub(crate) const WORKOUT_STEP: u16 = 27;
+pub(crate) const FILE_CREATOR: u16 = 49;
+
+pub(crate) mod base_type {
+    pub const ENUM: u8 = 0x00;
+    pub const STRING: u8 = 0x07;
+    pub const UINT16: u8 = 0x84;
+    pub const UINT32: u8 = 0x86;
+}
+
+/// `file` values of `file_id.type`
+pub(crate) const FILE_TYPE_WORKOUT: u8 = 5;
+/// `manufacturer` and `garmin_product` of the workout files Garmin Connect exports
+pub(crate) const MANUFACTURER_GARMIN: u16 = 1;
+pub(crate) const PRODUCT_CONNECT: u16 = 65534;
+
+/// `wkt_step_duration`
+pub(crate) mod duration {
+    pub const TIME: u8 = 0;
+    pub const DISTANCE: u8 = 1;
+    pub const HR_LESS_THAN: u8 = 2;
+    pub const HR_GREATER_THAN: u8 = 3;
+    pub const CALORIES: u8 = 4;
+    pub const OPEN: u8 = 5;
+    pub const REPEAT_UNTIL_STEPS_CMPLT: u8 = 6;
+    pub const POWER_LESS_THAN: u8 = 14;
+    pub const POWER_GREATER_THAN: u8 = 15;
+    pub const REPETITION_TIME: u8 = 28;
+    pub const REPS: u8 = 29;
+}
+
+/// `wkt_step_target`
+pub(crate) mod target {
+    pub const SPEED: u8 = 0;
+    pub const HEART_RATE: u8 = 1;
+    pub const OPEN: u8 = 2;
+    pub const CADENCE: u8 = 3;
+    pub const POWER: u8 = 4;
+    pub const SWIM_STROKE: u8 = 11;
+}
+
+/// `intensity`
+pub(crate) mod intensity {
+    pub const ACTIVE: u8 = 0;
+    pub const REST: u8 = 1;
+    pub const WARMUP: u8 = 2;
+    pub const COOLDOWN: u8 = 3;
+    pub const RECOVERY: u8 = 4;
+    pub const INTERVAL: u8 = 5;
+    pub const OTHER: u8 = 6;
+}
+
+/// FIT `sport` and `sub_sport` for each Garmin Connect sport, generic for keys this crate doesn't know
+pub(crate) fn sport(sport: Sport) -> (u8, u8) {
+    match sport {
+        Sport::Running => (1, 0),
+        Sport::Cycling => (2, 0),
+        Sport::Other | Sport::Unknown(_) => (0, 0),
+        // lap_swimming
+        Sport::Swimming => (5, 17),
+        Sport::StrengthTraining => (10, 20),
+        Sport::CardioTraining => (10, 26),
+        Sport::Yoga => (10, 43),
+        Sport::Pilates => (10, 44),
+        Sport::Hiit => (62, 70),
+        Sport::MultiSport => (18, 0),
+        Sport::Mobility => (10, 0),
+    }
+}
+
+/// Inverse of `sport`, sports Garmin Connect doesn't know become `Other`
+pub(crate) fn from_fit_sport(sport: u8, sub_sport: u8) -> Sport {
+    match (sport, sub_sport) {
+        (1, _) => Sport::Running,
+        (2, _) => Sport::Cycling,
+        (5, _) => Sport::Swimming,
+        (10, 20) => Sport::StrengthTraining,
+        (10, 26) => Sport::CardioTraining,
+        (10, 43) => Sport::Yoga,
+        (10, 44) => Sport::Pilates,
+        (10, 0) => Sport::Mobility,
+        (62, _) => Sport::Hiit,
+        (18, _) => Sport::MultiSport,
+        _ => Sport::Other,
+    }
+}
+
+/// `swim_stroke`, FIT has no value for "any stroke"
+pub(crate) fn swim_stroke(stroke: &Stroke) -> Option<u8> {
+    match stroke {
+        Stroke::Free => Some(0),
+        Stroke::Back => Some(1),
+        Stroke::Breast => Some(2),
+        Stroke::Butterfly => Some(3),
+        Stroke::Drill => Some(4),
+        Stroke::Mixed => Some(5),
+        Stroke::IndividualMedley => Some(6),
+        Stroke::AnyStroke | Stroke::Unknown(_) => None,
+    }
+}
+
+pub(crate) fn from_swim_stroke(value: u32) -> Option<Stroke> {
+    match value {
+        0 => Some(Stroke::Free),
+        1 => Some(Stroke::Back),
+        2 => Some(Stroke::Breast),
+        3 => Some(Stroke::Butterfly),
+        4 => Some(Stroke::Drill),
+        5 => Some(Stroke::Mixed),
+        6 => Some(Stroke::IndividualMedley),
+        _ => None,
+    }
+}
+
+/// `workout_equipment`, the same ids Garmin Connect uses
+pub(crate) fn equipment(value: u8) -> Option<Equipment> {
+    match value {
+        1 => Some(Equipment::Fins),
+        2 => Some(Equipment::Kickboard),
+        3 => Some(Equipment::Paddles),
+        4 => Some(Equipment::PullBuoy),
+        5 => Some(Equipment::Snorkel),
+        _ => None,
+    }
+}
+
+/// Heart rate and power thresholds are offset so zones 1-100 and absolute values don't overlap
+pub(crate) const HEART_RATE_OFFSET: u32 = 100;
+pub(crate) const POWER_OFFSET: u32 = 1000;
+
+/// CRC-16 used for the file header and the whole file
+pub(crate) fn crc(bytes: &[u8]) -> u16 {
+    const TABLE: [u16; 16] = [
+        0x0000, 0xCC01, 0xD801, 0x1400, 0xF001, 0x3C00, 0x2800, 0xE401, 0xA001, 0x6C00, 0x7800, 0xB401, 0x5000, 0x9C01,
+        0x8801, 0x4400,
+    ];
+
+    bytes.iter().fold(0, |mut crc, byte| {
+        for nibble in [byte & 0x0F, byte >> 4] {
+            let tmp = TABLE[(crc & 0x0F) as usize];
+            crc = ((crc >> 4) & 0x0FFF) ^ tmp ^ TABLE[nibble as usize];
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc() {
+        // CRC-16/ARC check value
+        assert_eq!(crc(b"123456789"), 0xBB3D);
+        assert_eq!(crc(&[]), 0);
+    }
+}
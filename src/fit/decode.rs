@@ -0,0 +1,555 @@
+use crate::builder::FIRST_STEP_ID;
+use crate::fit::{
+    base_type, crc, duration, equipment, from_fit_sport, from_swim_stroke, intensity, target, FILE_CREATOR, FILE_ID,
+    FILE_TYPE_WORKOUT, FIT_EPOCH, HEART_RATE_OFFSET, POWER_OFFSET, WORKOUT, WORKOUT_STEP,
+};
+use crate::garmin::author::Author;
+use crate::garmin::estimate::SwimPace;
+use crate::garmin::sport_type::Sport;
+use crate::garmin::unit::Unit;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_segments::WorkoutSegment;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition, EndConditionCompare};
+use crate::garmin::workout_steps::equipment_type::EquipmentType;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::{PreferredEndConditionUnit, Unit as DistanceUnit};
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::{Step, StepType};
+use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+use chrono::DateTime;
+use std::cell::Cell;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum FitError {
+    /// No `.FIT` signature, or a header size FIT doesn't define
+    NotFit,
+    /// The file ends inside the header, a message or the CRC
+    Truncated,
+    HeaderCrc,
+    FileCrc,
+    /// `file_id.type` is not a workout, e.g. 4 for an activity
+    NotAWorkout(u8),
+    /// A message a workout file has no business containing, by global message number
+    UnsupportedMessage(u16),
+    DeveloperFields,
+    CompressedTimestamp,
+    UndefinedLocalMessage(u8),
+    MissingWorkout,
+    UnsupportedDuration { message_index: u16, duration_type: u8 },
+    /// A repeat step pointing at a step after it, or into the middle of another repeat
+    InvalidRepeat { message_index: u16 },
+}
+
+impl fmt::Display for FitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FitError::NotFit => write!(f, "not a FIT file"),
+            FitError::Truncated => write!(f, "file is truncated"),
+            FitError::HeaderCrc => write!(f, "header CRC does not match"),
+            FitError::FileCrc => write!(f, "file CRC does not match"),
+            FitError::NotAWorkout(file_type) => write!(f, "file type {} is not a workout (5)", file_type),
+            FitError::UnsupportedMessage(global) => match message_name(*global) {
+                Some(name) => write!(f, "unsupported message {} ({}) in a workout file", name, global),
+                None => write!(f, "unsupported message {} in a workout file", global),
+            },
+            FitError::DeveloperFields => write!(f, "developer fields are not supported"),
+            FitError::CompressedTimestamp => write!(f, "compressed timestamp headers are not supported"),
+            FitError::UndefinedLocalMessage(local) => write!(f, "data for local message {} before its definition", local),
+            FitError::MissingWorkout => write!(f, "no workout message"),
+            FitError::UnsupportedDuration { message_index, duration_type } => {
+                write!(f, "workout step {}: unsupported duration type {}", message_index, duration_type)
+            }
+            FitError::InvalidRepeat { message_index } => {
+                write!(f, "workout step {}: repeat does not close a run of earlier steps", message_index)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FitError {}
+
+fn message_name(global: u16) -> Option<&'static str> {
+    match global {
+        18 => Some("session"),
+        19 => Some("lap"),
+        20 => Some("record"),
+        21 => Some("event"),
+        23 => Some("device_info"),
+        34 => Some("activity"),
+        158 => Some("workout_session"),
+        206 => Some("field_description"),
+        207 => Some("developer_data_id"),
+        264 => Some("exercise_title"),
+        _ => None,
+    }
+}
+
+/// Decodes a FIT workout file. Steps get fresh ids and the estimates are filled in, like
+/// workouts made with the builder.
+pub fn decode(bytes: &[u8]) -> Result<Workout, FitError> {
+    let messages = messages(bytes)?;
+
+    let file_id = messages.iter().find(|message| message.global == FILE_ID);
+    if let Some(file_type) = file_id.and_then(|file_id| file_id.int(0))
+        && file_type != FILE_TYPE_WORKOUT as u32
+    {
+        return Err(FitError::NotAWorkout(file_type as u8));
+    }
+    let time_created = file_id.and_then(|file_id| file_id.int(4)).unwrap_or(0);
+    let created = DateTime::from_timestamp(FIT_EPOCH + time_created as i64, 0).unwrap_or_default().naive_utc();
+
+    let workout = messages.iter().find(|message| message.global == WORKOUT).ok_or(FitError::MissingWorkout)?;
+    let sport = from_fit_sport(
+        workout.int(4).unwrap_or(0) as u8,
+        workout.int(11).unwrap_or(0) as u8,
+    );
+    let statute = workout.int(15) == Some(1);
+    let pool_unit = if statute { DistanceUnit::Yard } else { DistanceUnit::Meter };
+
+    let mut decoder = StepDecoder { sport: sport.clone(), pool_unit: PreferredEndConditionUnit::new(pool_unit), steps: Vec::new() };
+    for (position, step) in messages.iter().filter(|message| message.global == WORKOUT_STEP).enumerate() {
+        // The top bits of a message index are flags
+        let message_index = step.int(254).map(|index| index & 0x0FFF).unwrap_or(position as u32);
+        decoder.step(step, message_index as u16)?;
+    }
+    let steps = decoder.steps.into_iter().map(|(_, step)| step).collect();
+
+    let name = workout.text(8).unwrap_or_default();
+    let owner_id = Author::default().user_profile_pk() as u64;
+    let segments = vec![WorkoutSegment::with_sport(1, sport.clone(), steps)];
+    let mut workout = match (&sport, workout.int(14)) {
+        (Sport::Swimming, Some(pool_length)) => {
+            let pool_length = PreferredEndConditionUnit::new(pool_unit).from_meters(pool_length as f32 / 100.0);
+            Workout::new_swimming_workout(0, owner_id, name, None, created, created, segments, pool_length, Unit::from(pool_unit))
+        }
+        _ => Workout::new(sport, 0, owner_id, name, None, created, created, segments),
+    };
+    workout.renumber_steps(FIRST_STEP_ID);
+    workout.fill_estimates(SwimPace::default());
+    Ok(workout)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub(super) enum Value {
+    Int(u32),
+    Text(String),
+}
+
+pub(super) struct Message {
+    pub(super) global: u16,
+    pub(super) fields: Vec<(u8, Value)>,
+}
+
+impl Message {
+    fn int(&self, number: u8) -> Option<u32> {
+        self.fields.iter().find_map(|(field, value)| match value {
+            Value::Int(int) if *field == number => Some(*int),
+            _ => None,
+        })
+    }
+
+    fn text(&self, number: u8) -> Option<String> {
+        self.fields.iter().find_map(|(field, value)| match value {
+            Value::Text(text) if *field == number => Some(text.clone()),
+            _ => None,
+        })
+    }
+}
+
+struct Definition {
+    global: u16,
+    big_endian: bool,
+    /// Field number, size and base type
+    fields: Vec<(u8, u8, u8)>,
+}
+
+/// Checks the header and CRCs and reads every data message, invalid values left out
+pub(super) fn messages(bytes: &[u8]) -> Result<Vec<Message>, FitError> {
+    let header_size = *bytes.first().ok_or(FitError::Truncated)? as usize;
+    if header_size != 12 && header_size != 14 {
+        return Err(FitError::NotFit);
+    }
+    let header = bytes.get(..header_size).ok_or(FitError::Truncated)?;
+    if &header[8..12] != b".FIT" {
+        return Err(FitError::NotFit);
+    }
+    // A zero header CRC means it wasn't computed
+    if header_size == 14 && header[12..14] != [0, 0] && crc(&header[..12]) != u16::from_le_bytes([header[12], header[13]]) {
+        return Err(FitError::HeaderCrc);
+    }
+
+    let data_size = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let end = header_size + data_size;
+    if bytes.len() < end + 2 {
+        return Err(FitError::Truncated);
+    }
+    if crc(&bytes[..end + 2]) != 0 {
+        return Err(FitError::FileCrc);
+    }
+
+    let mut definitions: [Option<Definition>; 16] = Default::default();
+    let mut messages = Vec::new();
+    let mut reader = Reader { bytes: &bytes[..end], pos: header_size };
+    while reader.pos < end {
+        let record_header = reader.take(1)?[0];
+        if record_header & 0x80 != 0 {
+            return Err(FitError::CompressedTimestamp);
+        }
+        let local = record_header & 0x0F;
+
+        if record_header & 0x40 != 0 {
+            if record_header & 0x20 != 0 {
+                return Err(FitError::DeveloperFields);
+            }
+            let fixed = reader.take(5)?;
+            let big_endian = fixed[1] == 1;
+            let global = if big_endian { u16::from_be_bytes([fixed[2], fixed[3]]) } else { u16::from_le_bytes([fixed[2], fixed[3]]) };
+            if ![FILE_ID, FILE_CREATOR, WORKOUT, WORKOUT_STEP].contains(&global) {
+                return Err(FitError::UnsupportedMessage(global));
+            }
+            let fields = reader.take(fixed[4] as usize * 3)?.chunks(3).map(|field| (field[0], field[1], field[2])).collect();
+            definitions[local as usize] = Some(Definition { global, big_endian, fields });
+            continue;
+        }
+
+        let definition = definitions[local as usize].as_ref().ok_or(FitError::UndefinedLocalMessage(local))?;
+        let mut fields = Vec::new();
+        for &(number, size, field_type) in &definition.fields {
+            let raw = reader.take(size as usize)?;
+            if let Some(value) = value(raw, field_type, definition.big_endian) {
+                fields.push((number, value));
+            }
+        }
+        messages.push(Message { global: definition.global, fields });
+    }
+    Ok(messages)
+}
+
+/// Strings and unsigned integers up to 32 bits, anything else is skipped
+fn value(raw: &[u8], field_type: u8, big_endian: bool) -> Option<Value> {
+    if field_type == base_type::STRING {
+        let text = raw.split(|byte| *byte == 0).next().unwrap_or_default();
+        return (!text.is_empty()).then(|| Value::Text(String::from_utf8_lossy(text).into_owned()));
+    }
+
+    // All ones is FIT's invalid value for unsigned types
+    if raw.is_empty() || raw.len() > 4 || raw.iter().all(|byte| *byte == 0xFF) {
+        return None;
+    }
+    let int = if big_endian {
+        raw.iter().fold(0, |int, byte| (int << 8) | *byte as u32)
+    } else {
+        raw.iter().rev().fold(0, |int, byte| (int << 8) | *byte as u32)
+    };
+    Some(Value::Int(int))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], FitError> {
+        let taken = self.bytes.get(self.pos..self.pos + len).ok_or(FitError::Truncated)?;
+        self.pos += len;
+        Ok(taken)
+    }
+}
+
+struct StepDecoder {
+    sport: Sport,
+    pool_unit: PreferredEndConditionUnit,
+    /// Finished steps with the message index they start at, repeats fold them up
+    steps: Vec<(u16, WorkoutStep)>,
+}
+
+impl StepDecoder {
+    fn step(&mut self, step: &Message, message_index: u16) -> Result<(), FitError> {
+        let duration_type = step.int(1).unwrap_or(duration::OPEN as u32) as u8;
+        if duration_type == duration::REPEAT_UNTIL_STEPS_CMPLT {
+            return self.repeat(step, message_index);
+        }
+
+        let value = step.int(2).unwrap_or(0);
+        let (condition, compare, value) = match duration_type {
+            duration::DISTANCE => (Condition::Distance, None, value as f32 / 100.0),
+            duration::TIME => (Condition::Time, None, value as f32 / 1000.0),
+            duration::REPETITION_TIME => (Condition::FixedRepetition, None, value as f32 / 1000.0),
+            duration::CALORIES => (Condition::Calories, None, value as f32),
+            duration::HR_LESS_THAN | duration::HR_GREATER_THAN => (
+                Condition::HeartRate,
+                Some(compare(duration_type == duration::HR_LESS_THAN)),
+                value.saturating_sub(HEART_RATE_OFFSET) as f32,
+            ),
+            duration::POWER_LESS_THAN | duration::POWER_GREATER_THAN => (
+                Condition::Power,
+                Some(compare(duration_type == duration::POWER_LESS_THAN)),
+                value.saturating_sub(POWER_OFFSET) as f32,
+            ),
+            duration::REPS => (Condition::Reps, None, value as f32),
+            duration::OPEN => (Condition::LapButton, None, 0.0),
+            _ => return Err(FitError::UnsupportedDuration { message_index, duration_type }),
+        };
+
+        // Many tools put the step's name in `wkt_step_name` rather than `notes`
+        let description = step.text(8).or_else(|| step.text(0));
+        let step_type = match step.int(7).map(|intensity| intensity as u8) {
+            Some(intensity::REST) => StepType::new(Step::Rest),
+            Some(intensity::WARMUP) => StepType::new(Step::Warmup),
+            Some(intensity::COOLDOWN) => StepType::new(Step::Cooldown),
+            Some(intensity::INTERVAL) => StepType::new(Step::Interval),
+            Some(intensity::RECOVERY) => StepType::new(Step::Recovery),
+            Some(intensity::OTHER) => StepType::new(Step::Other),
+            _ => StepType::new(Step::Main),
+        };
+
+        let single = if step_type.step_type_key == Step::Rest {
+            // Garmin Connect writes timed swim rests as `fixed.rest`
+            let condition = if condition == Condition::Time { Condition::FixedRest } else { condition };
+            ExecutableStepDTO::rest_step(0, 0, Cell::new(None), description, EndCondition::new(condition), value)
+        } else {
+            let swimming = self.sport == Sport::Swimming;
+            let target_type = step.int(3).unwrap_or(target::OPEN as u32) as u8;
+            let stroke = match (swimming, target_type) {
+                (true, target::SWIM_STROKE) => step.int(4).and_then(from_swim_stroke).or(Some(Stroke::AnyStroke)),
+                (true, _) => Some(Stroke::AnyStroke),
+                (false, _) => None,
+            };
+            let equipment = step.int(9).and_then(|value| equipment(value as u8));
+
+            let mut single = ExecutableStepDTO::active_step(
+                0,
+                0,
+                step_type,
+                Cell::new(None),
+                description,
+                EndCondition::new(condition.clone()),
+                value,
+                None,
+                StrokeType::new(stroke),
+                EquipmentType::new(equipment),
+            );
+            if condition == Condition::Distance && swimming {
+                single.set_distance(self.pool_unit.from_meters(value), self.pool_unit.unit());
+            }
+            single.set_end_condition_compare(compare);
+            if !swimming {
+                single.set_target(self.target(step, target_type));
+            }
+            single
+        };
+        self.steps.push((message_index, WorkoutStep::Single(single)));
+        Ok(())
+    }
+
+    fn target(&self, step: &Message, target_type: u8) -> StepTarget {
+        let zone = step.int(4).filter(|zone| *zone > 0);
+        let low = step.int(5).unwrap_or(0);
+        let high = step.int(6).unwrap_or(0);
+        match (target_type, zone) {
+            (target::HEART_RATE, Some(zone)) => StepTarget::HeartRateZone(zone as u8),
+            (target::HEART_RATE, None) => StepTarget::HeartRate {
+                min_bpm: low.saturating_sub(HEART_RATE_OFFSET) as u16,
+                max_bpm: high.saturating_sub(HEART_RATE_OFFSET) as u16,
+            },
+            (target::POWER, Some(zone)) => StepTarget::PowerZone(zone as u8),
+            (target::POWER, None) => StepTarget::Power {
+                min_watts: low.saturating_sub(POWER_OFFSET) as u16,
+                max_watts: high.saturating_sub(POWER_OFFSET) as u16,
+            },
+            (target::CADENCE, None) => StepTarget::Cadence { min: low as u16, max: high as u16 },
+            // Millimeters per second, runners think in whole seconds per kilometer
+            (target::SPEED, None) if low > 0 && high > 0 && self.sport == Sport::Running => StepTarget::Pace {
+                slowest_secs_per_km: (1_000_000.0 / low as f64).round(),
+                fastest_secs_per_km: (1_000_000.0 / high as f64).round(),
+            },
+            (target::SPEED, None) if low > 0 && high > 0 => StepTarget::Speed {
+                min_kph: low as f64 / 1000.0 * 3.6,
+                max_kph: high as f64 / 1000.0 * 3.6,
+            },
+            // Speed and cadence zones have no Garmin Connect equivalent
+            _ => StepTarget::NoTarget,
+        }
+    }
+
+    /// Folds the steps from the repeat's first step up to here into a repeat group
+    fn repeat(&mut self, step: &Message, message_index: u16) -> Result<(), FitError> {
+        let first = step.int(2).ok_or(FitError::InvalidRepeat { message_index })?;
+        let split = self
+            .steps
+            .iter()
+            .position(|(start, _)| *start as u32 == first)
+            .ok_or(FitError::InvalidRepeat { message_index })?;
+
+        let children: Vec<WorkoutStep> = self.steps.drain(split..).map(|(_, step)| step).collect();
+        let iterations = step.int(4).unwrap_or(1).min(u8::MAX as u32) as u8;
+        self.steps.push((first as u16, WorkoutStep::Repeat(RepeatGroupDTO::new(0, 0, 0, iterations, children))));
+        Ok(())
+    }
+}
+
+fn compare(less_than: bool) -> EndConditionCompare {
+    if less_than { EndConditionCompare::LessThan } else { EndConditionCompare::GreaterThan }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::fit::encode;
+    use crate::garmin::timestamp::{self, FixedClock};
+    use crate::notation;
+    use std::fs;
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    /// Wraps records in a header and CRC
+    fn file(records: &[u8]) -> Vec<u8> {
+        let mut file = vec![12, 0x10, 0x54, 0x08];
+        file.extend((records.len() as u32).to_le_bytes());
+        file.extend(b".FIT");
+        file.extend(records);
+        let crc = crc(&file);
+        file.extend(crc.to_le_bytes());
+        file
+    }
+
+    #[test]
+    fn test_round_trip_exported_workouts() {
+        for name in ["expected_workout.json", "Core-workout-2.json"] {
+            let json_string = fs::read_to_string(format!("src/workouts_json/{}", name)).unwrap();
+            let workout: Workout = serde_json::from_str(&json_string).unwrap();
+
+            let decoded = decode(&encode(&workout)).unwrap();
+            assert_eq!(notation::format(&decoded), notation::format(&workout), "{}", name);
+            assert_eq!(decoded.workout_name(), workout.workout_name());
+            assert_eq!(decoded.pool_length(), 25.0);
+            assert_eq!(decoded.estimate(SwimPace::default()).total, workout.estimate(SwimPace::default()).total);
+            assert_eq!(decoded.validate(), vec![], "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_nested_repeats_and_yards() {
+        let workout = notation::parse("300yd wu / 3x(2x50yd fly w/ fins @1:00, 100yd kick w/ board) r30 / 200yd cd")
+            .unwrap()
+            .pool(25.yd())
+            .build(&clock())
+            .unwrap();
+
+        let decoded = decode(&encode(&workout)).unwrap();
+        assert_eq!(notation::format(&decoded), notation::format(&workout));
+        assert_eq!(decoded.pool_length_unit(), &Unit::from(DistanceUnit::Yard));
+        assert!((decoded.pool_length() - 25.0).abs() < 1e-3);
+        assert_eq!(serde_json::to_value(&decoded).unwrap()["updatedDate"], "2025-04-03T15:49:08.0");
+    }
+
+    #[test]
+    fn test_targets_and_thresholds() {
+        let workout = workout(Sport::Running)
+            .main(10.mins(), AnyStroke)
+            .target(StepTarget::Pace { slowest_secs_per_km: 300.0, fastest_secs_per_km: 250.0 })
+            .main(5.mins(), AnyStroke)
+            .target(StepTarget::HeartRateZone(3))
+            .build(&clock())
+            .unwrap();
+
+        let decoded = decode(&encode(&workout)).unwrap();
+        let step = |i: usize| match &decoded.workout_segments()[0].workout_steps()[i] {
+            WorkoutStep::Single(single) => single.target(),
+            _ => None,
+        };
+        assert_eq!(decoded.sport_type().sport(), Sport::Running);
+        assert_eq!(step(0), Some(StepTarget::Pace { slowest_secs_per_km: 300.0, fastest_secs_per_km: 250.0 }));
+        assert_eq!(step(1), Some(StepTarget::HeartRateZone(3)));
+    }
+
+    #[test]
+    fn test_third_party_steps() {
+        // Names in wkt_step_name, flag bits on the message indexes, interval and recovery
+        #[rustfmt::skip]
+        let records = [
+            0x40, 0, 0, 26, 0, 1, 4, 1, 0x00,
+            0x00, 1,
+            0x40, 0, 0, 27, 0, 5, 254, 2, 0x84, 0, 8, 0x07, 1, 1, 0x00, 2, 4, 0x86, 7, 1, 0x00,
+            0x00, 0x00, 0x10, b'T', b'e', b'm', b'p', b'o', 0, 0, 0, 0, 0xC0, 0x27, 0x09, 0x00, 5,
+            0x00, 0x01, 0x20, b'E', b'a', b's', b'y', 0, 0, 0, 0, 0, 0xC0, 0xD4, 0x01, 0x00, 4,
+            0x00, 0x02, 0x00, 0, 0, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0xFF,
+        ];
+        let decoded = decode(&file(&records)).unwrap();
+        let WorkoutStep::Repeat(repeat) = &decoded.workout_segments()[0].workout_steps()[0] else { panic!() };
+        let steps: Vec<_> = repeat
+            .workout_steps()
+            .iter()
+            .map(|step| match step {
+                WorkoutStep::Single(single) => (
+                    single.description().map(str::to_string),
+                    single.step_type().step_type_key.clone(),
+                    single.step_type().step_type_id(),
+                    single.end_condition_value(),
+                ),
+                _ => panic!(),
+            })
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                (Some("Tempo".to_string()), Step::Interval, 3, 600.0),
+                (Some("Easy".to_string()), Step::Recovery, 4, 120.0),
+            ]
+        );
+        let segments = |workout: &Workout| serde_json::to_value(workout).unwrap()["workoutSegments"].clone();
+        assert_eq!(segments(&decode(&encode(&decoded)).unwrap()), segments(&decoded));
+    }
+
+    #[test]
+    fn test_errors() {
+        let valid = encode(&swim().main(100.m(), Free).build(&clock()).unwrap());
+
+        let mut corrupt = valid.clone();
+        corrupt[20] ^= 1;
+        assert_eq!(decode(&corrupt).err(), Some(FitError::FileCrc));
+        assert_eq!(decode(&valid[..valid.len() - 3]).err(), Some(FitError::Truncated));
+        assert_eq!(decode(b"not a fit file").err(), Some(FitError::NotFit));
+
+        // A record message (20) definition
+        let activity = file(&[0x40, 0, 0, 20, 0, 1, 253, 4, 0x86]);
+        let error = decode(&activity).err().unwrap();
+        assert_eq!(error, FitError::UnsupportedMessage(20));
+        assert_eq!(error.to_string(), "unsupported message record (20) in a workout file");
+
+        // Repeat step 0 pointing at step 3
+        #[rustfmt::skip]
+        let records = [
+            0x40, 0, 0, 26, 0, 1, 4, 1, 0x00, 0, 5,
+            0x40, 0, 0, 27, 0, 3, 254, 2, 0x84, 1, 1, 0x00, 2, 4, 0x86, 0, 0, 0, 6, 3, 0, 0, 0,
+        ];
+        assert_eq!(decode(&file(&records)).err(), Some(FitError::InvalidRepeat { message_index: 0 }));
+    }
+
+    /// The Garmin Connect downloads the encoder is checked against, read back
+    #[test]
+    #[ignore = "needs a swim with repeats, strokes, equipment and a pool length downloaded from Garmin Connect as FIT and JSON"]
+    fn test_decode_connect_downloads() {
+        let mut checked = 0;
+        for entry in fs::read_dir("src/workouts_fit").expect("Missing workouts_fit directory") {
+            let path = entry.unwrap().path();
+            let json_path = std::path::Path::new("src/workouts_json").join(path.file_stem().unwrap()).with_extension("json");
+            let workout: Workout = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+
+            let decoded = decode(&fs::read(&path).unwrap()).unwrap();
+            assert_eq!(notation::format(&decoded), notation::format(&workout), "{}", path.display());
+            assert_eq!(decoded.workout_name(), workout.workout_name());
+            assert_eq!((decoded.pool_length(), decoded.pool_length_unit()), (workout.pool_length(), workout.pool_length_unit()));
+            assert_eq!(decoded.validate(), vec![], "{}", path.display());
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
@@ -0,0 +1,374 @@
+use crate::fit::{
+    base_type, crc, duration, intensity, sport, swim_stroke, target, FILE_ID, FILE_TYPE_WORKOUT, FIT_EPOCH,
+    HEART_RATE_OFFSET, MANUFACTURER_GARMIN, POWER_OFFSET, PRODUCT_CONNECT, PROFILE_VERSION, PROTOCOL_VERSION,
+    WORKOUT, WORKOUT_STEP,
+};
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::{Condition, EndConditionCompare};
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::Unit as DistanceUnit;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+
+/// Encodes the workout as a FIT workout file. FIT has no segments, their steps are concatenated.
+pub fn encode(workout: &Workout) -> Vec<u8> {
+    let mut writer = Writer::default();
+
+    let time_created = (workout.updated_date().and_utc().timestamp() - FIT_EPOCH).max(0) as u32;
+    writer.message(
+        FILE_ID,
+        vec![
+            Field::enumeration(0, FILE_TYPE_WORKOUT),
+            Field::uint16(1, MANUFACTURER_GARMIN),
+            Field::uint16(2, PRODUCT_CONNECT),
+            Field::uint32(4, time_created),
+        ],
+    );
+
+    let swimming = workout.sport_type().sport() == Sport::Swimming;
+    let mut steps = Vec::new();
+    for segment in workout.workout_segments() {
+        flatten(segment.workout_steps(), swimming, &mut steps);
+    }
+
+    let (sport, sub_sport) = sport(workout.sport_type().sport());
+    let mut fields = vec![
+        Field::string(8, workout.workout_name()),
+        Field::enumeration(4, sport),
+        Field::enumeration(11, sub_sport),
+        Field::uint16(6, steps.len() as u16),
+    ];
+    if swimming {
+        // Always meters, the unit only says how the watch displays it
+        let unit = workout.pool_length_unit();
+        let pool_length = unit.to_meters(workout.pool_length()).unwrap_or(workout.pool_length());
+        let statute = unit.distance_unit() == Some(DistanceUnit::Yard);
+        fields.push(Field::uint16(14, (pool_length * 100.0).round() as u16));
+        fields.push(Field::enumeration(15, statute as u8));
+    }
+    writer.message(WORKOUT, fields);
+
+    for (message_index, step) in steps.into_iter().enumerate() {
+        let mut fields = vec![Field::uint16(254, message_index as u16)];
+        fields.extend(step);
+        writer.message(WORKOUT_STEP, fields);
+    }
+    writer.finish()
+}
+
+/// FIT puts a repeat step after the steps it repeats, pointing back at the first of them
+fn flatten(steps: &[WorkoutStep], swimming: bool, flat: &mut Vec<Vec<Field>>) {
+    for step in steps {
+        match step {
+            WorkoutStep::Single(single) => flat.push(single_step(single, swimming)),
+            WorkoutStep::Repeat(repeat) => {
+                let first = flat.len();
+                flatten(repeat.workout_steps(), swimming, flat);
+                flat.push(vec![
+                    Field::enumeration(1, duration::REPEAT_UNTIL_STEPS_CMPLT),
+                    Field::uint32(2, first as u32),
+                    Field::uint32(4, repeat.number_of_iterations() as u32),
+                ]);
+            }
+        }
+    }
+}
+
+fn single_step(step: &ExecutableStepDTO, swimming: bool) -> Vec<Field> {
+    let value = step.end_condition_value();
+    let less_than = step.end_condition_compare() == Some(EndConditionCompare::LessThan);
+    let (duration_type, duration_value) = match step.end_condition().condition_type_key {
+        // Centimeters and milliseconds
+        Condition::Distance => (duration::DISTANCE, Some(value * 100.0)),
+        Condition::Time | Condition::FixedRest => (duration::TIME, Some(value * 1000.0)),
+        Condition::FixedRepetition => (duration::REPETITION_TIME, Some(value * 1000.0)),
+        Condition::Calories => (duration::CALORIES, Some(value)),
+        Condition::HeartRate if less_than => (duration::HR_LESS_THAN, Some(value + HEART_RATE_OFFSET as f32)),
+        Condition::HeartRate => (duration::HR_GREATER_THAN, Some(value + HEART_RATE_OFFSET as f32)),
+        Condition::Power if less_than => (duration::POWER_LESS_THAN, Some(value + POWER_OFFSET as f32)),
+        Condition::Power => (duration::POWER_GREATER_THAN, Some(value + POWER_OFFSET as f32)),
+        Condition::Reps => (duration::REPS, Some(value)),
+        _ => (duration::OPEN, None),
+    };
+
+    let mut fields = vec![Field::enumeration(1, duration_type)];
+    if let Some(duration_value) = duration_value {
+        fields.push(Field::uint32(2, duration_value.round() as u32));
+    }
+
+    let step_type = &step.step_type().step_type_key;
+    if swimming && *step_type != Step::Rest {
+        // Swim steps use the target for the stroke
+        match step.stroke_type().stroke_type_key.as_ref().and_then(swim_stroke) {
+            Some(stroke) => fields.extend([Field::enumeration(3, target::SWIM_STROKE), Field::uint32(4, stroke as u32)]),
+            None => fields.push(Field::enumeration(3, target::OPEN)),
+        }
+    } else {
+        fields.extend(target_fields(step.target().unwrap_or(StepTarget::NoTarget)));
+    }
+
+    let intensity = match step_type {
+        Step::Warmup => intensity::WARMUP,
+        Step::Cooldown => intensity::COOLDOWN,
+        Step::Rest => intensity::REST,
+        Step::Recovery => intensity::RECOVERY,
+        Step::Interval => intensity::INTERVAL,
+        Step::Other => intensity::OTHER,
+        _ => intensity::ACTIVE,
+    };
+    fields.push(Field::enumeration(7, intensity));
+
+    if let Some(description) = step.description().filter(|description| !description.is_empty()) {
+        fields.push(Field::string(8, description));
+    }
+    if step.equipment_type().equipment_type_key.is_some() {
+        fields.push(Field::enumeration(9, step.equipment_type().equipment_type_id()));
+    }
+    fields
+}
+
+fn target_fields(step_target: StepTarget) -> Vec<Field> {
+    // A zero target value means the custom range applies
+    let custom = |target_type: u8, low: f64, high: f64| {
+        vec![
+            Field::enumeration(3, target_type),
+            Field::uint32(4, 0),
+            Field::uint32(5, low.round() as u32),
+            Field::uint32(6, high.round() as u32),
+        ]
+    };
+
+    match step_target {
+        StepTarget::NoTarget => vec![Field::enumeration(3, target::OPEN)],
+        // Speeds in millimeters per second
+        StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km } => {
+            custom(target::SPEED, 1_000_000.0 / slowest_secs_per_km, 1_000_000.0 / fastest_secs_per_km)
+        }
+        StepTarget::Speed { min_kph, max_kph } => custom(target::SPEED, min_kph / 3.6 * 1000.0, max_kph / 3.6 * 1000.0),
+        StepTarget::HeartRateZone(zone) => vec![Field::enumeration(3, target::HEART_RATE), Field::uint32(4, zone as u32)],
+        StepTarget::HeartRate { min_bpm, max_bpm } => custom(
+            target::HEART_RATE,
+            (min_bpm as u32 + HEART_RATE_OFFSET) as f64,
+            (max_bpm as u32 + HEART_RATE_OFFSET) as f64,
+        ),
+        StepTarget::Cadence { min, max } => custom(target::CADENCE, min as f64, max as f64),
+        StepTarget::PowerZone(zone) => vec![Field::enumeration(3, target::POWER), Field::uint32(4, zone as u32)],
+        StepTarget::Power { min_watts, max_watts } => custom(
+            target::POWER,
+            (min_watts as u32 + POWER_OFFSET) as f64,
+            (max_watts as u32 + POWER_OFFSET) as f64,
+        ),
+    }
+}
+
+struct Field {
+    number: u8,
+    base_type: u8,
+    bytes: Vec<u8>,
+}
+
+impl Field {
+    fn enumeration(number: u8, value: u8) -> Self {
+        Field { number, base_type: base_type::ENUM, bytes: vec![value] }
+    }
+
+    fn uint16(number: u8, value: u16) -> Self {
+        Field { number, base_type: base_type::UINT16, bytes: value.to_le_bytes().to_vec() }
+    }
+
+    fn uint32(number: u8, value: u32) -> Self {
+        Field { number, base_type: base_type::UINT32, bytes: value.to_le_bytes().to_vec() }
+    }
+
+    /// Null terminated, cut to fit the 255 byte field size limit
+    fn string(number: u8, value: &str) -> Self {
+        let mut end = value.len().min(254);
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        let mut bytes = value.as_bytes()[..end].to_vec();
+        bytes.push(0);
+        Field { number, base_type: base_type::STRING, bytes }
+    }
+}
+
+/// Field number, size and base type of each field
+type Layout = (u16, Vec<(u8, u8, u8)>);
+
+#[derive(Default)]
+struct Writer {
+    records: Vec<u8>,
+    definition: Option<Layout>,
+}
+
+impl Writer {
+    /// Every message uses local message type 0, redefined whenever the layout changes
+    fn message(&mut self, global: u16, fields: Vec<Field>) {
+        let layout: Layout = (
+            global,
+            fields.iter().map(|field| (field.number, field.bytes.len() as u8, field.base_type)).collect(),
+        );
+        if self.definition.as_ref() != Some(&layout) {
+            // Definition header, reserved byte, little endian architecture
+            self.records.extend([0x40, 0, 0]);
+            self.records.extend(global.to_le_bytes());
+            self.records.push(fields.len() as u8);
+            for (number, size, base_type) in &layout.1 {
+                self.records.extend([*number, *size, *base_type]);
+            }
+            self.definition = Some(layout);
+        }
+
+        self.records.push(0x00);
+        for field in fields {
+            self.records.extend(field.bytes);
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        let mut file = vec![14, PROTOCOL_VERSION];
+        file.extend(PROFILE_VERSION.to_le_bytes());
+        file.extend((self.records.len() as u32).to_le_bytes());
+        file.extend(b".FIT");
+        let header_crc = crc(&file);
+        file.extend(header_crc.to_le_bytes());
+
+        file.extend(self.records);
+        let file_crc = crc(&file);
+        file.extend(file_crc.to_le_bytes());
+        file
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::fit::decode::{self, Value};
+    use crate::garmin::timestamp::{self, FixedClock};
+    use std::fs;
+    use std::path::Path;
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_bytes() {
+        let workout = swim().name("Hi").main(100.m(), Free).rest(20.s()).build(&clock()).unwrap();
+        let file = encode(&workout);
+
+        #[rustfmt::skip]
+        let records: Vec<u8> = vec![
+            // file_id definition: type, manufacturer, product, time_created
+            0x40, 0, 0, 0, 0, 4, 0, 1, 0x00, 1, 2, 0x84, 2, 2, 0x84, 4, 4, 0x86,
+            // workout file, Garmin, Connect, 2025-04-03T15:49:08Z
+            0, 5, 1, 0, 0xFE, 0xFF, 0xF4, 0x61, 0x51, 0x42,
+            // workout definition: wkt_name, sport, sub_sport, num_valid_steps, pool_length, pool_length_unit
+            0x40, 0, 0, 26, 0, 6, 8, 3, 0x07, 4, 1, 0x00, 11, 1, 0x00, 6, 2, 0x84, 14, 2, 0x84, 15, 1, 0x00,
+            // "Hi", swimming, lap swimming, 2 steps, 25.00m, metric
+            0, b'H', b'i', 0, 5, 17, 2, 0, 0xC4, 0x09, 0,
+            // workout_step definition: message_index, duration_type, duration_value, target_type, target_value, intensity
+            0x40, 0, 0, 27, 0, 6, 254, 2, 0x84, 1, 1, 0x00, 2, 4, 0x86, 3, 1, 0x00, 4, 4, 0x86, 7, 1, 0x00,
+            // step 0, 10000cm, freestyle, active
+            0, 0, 0, 1, 0x10, 0x27, 0, 0, 11, 0, 0, 0, 0, 0,
+            // workout_step definition without target_value
+            0x40, 0, 0, 27, 0, 5, 254, 2, 0x84, 1, 1, 0x00, 2, 4, 0x86, 3, 1, 0x00, 7, 1, 0x00,
+            // step 1, 20000ms, open, rest
+            0, 1, 0, 0, 0x20, 0x4E, 0, 0, 2, 1,
+        ];
+
+        assert_eq!(&file[..12], &[14, 0x10, 0x54, 0x08, records.len() as u8, 0, 0, 0, b'.', b'F', b'I', b'T']);
+        assert_eq!(&file[12..14], &crc(&file[..12]).to_le_bytes());
+        assert_eq!(&file[14..file.len() - 2], &records[..]);
+        assert_eq!(crc(&file), 0, "a file ending in its own CRC checks to zero");
+    }
+
+    #[test]
+    fn test_repeat_steps_point_back() {
+        let workout = swim()
+            .pool(25.yd())
+            .warmup(200.yd(), Free)
+            .repeat(3, |r| r.repeat(2, |r| r.main(50.yd(), Butterfly).equipment(Equipment::Fins)).rest(30.s()))
+            .build(&clock())
+            .unwrap();
+        let file = encode(&workout);
+
+        // Steps: 0 warmup, 1 fly, 2 repeat from 1 x2, 3 rest, 4 repeat from 1 x3
+        let repeat = [0x40, 0, 0, 27, 0, 4, 254, 2, 0x84, 1, 1, 0x00, 2, 4, 0x86, 4, 4, 0x86];
+        let inner = [0, 2, 0, 6, 1, 0, 0, 0, 2, 0, 0, 0];
+        let outer = [0, 4, 0, 6, 1, 0, 0, 0, 3, 0, 0, 0];
+        let position = file.windows(repeat.len()).position(|window| window == repeat).unwrap();
+        assert_eq!(&file[position + repeat.len()..][..inner.len()], &inner);
+        assert!(file.windows(outer.len()).any(|window| window == outer));
+
+        // 22.86m pool displayed in yards
+        assert!(file.windows(3).any(|window| window == [0xEE, 0x08, 1]));
+        // Fins after the intensity of the fly step: butterfly, active, fins
+        assert!(file.windows(6).any(|window| window == [7, 1, 0x00, 9, 1, 0x00]));
+        assert!(file.windows(6).any(|window| window == [3, 0, 0, 0, 0, 1]));
+    }
+
+    /// Each message's fields by number, `file_id.time_created` left out since it only says
+    /// when the file was written
+    fn messages(file: &[u8]) -> Vec<(u16, Vec<(u8, Value)>)> {
+        decode::messages(file)
+            .unwrap()
+            .into_iter()
+            .filter(|message| [FILE_ID, WORKOUT, WORKOUT_STEP].contains(&message.global))
+            .map(|message| {
+                let mut fields: Vec<(u8, Value)> = message
+                    .fields
+                    .into_iter()
+                    .filter(|(number, _)| message.global != FILE_ID || *number != 4)
+                    .collect();
+                fields.sort_by_key(|(number, _)| *number);
+                (message.global, fields)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_messages_leave_out_time_created() {
+        let workout = swim().main(100.m(), Free).build(&clock()).unwrap();
+        let later = swim().main(100.m(), Free).build(&FixedClock(timestamp::parse("2025-05-01T08:00:00.0").unwrap())).unwrap();
+        assert_ne!(encode(&workout), encode(&later));
+        assert_eq!(messages(&encode(&workout)), messages(&encode(&later)));
+        assert_eq!(messages(&encode(&workout))[0], (FILE_ID, vec![(0, Value::Int(5)), (1, Value::Int(1)), (2, Value::Int(65534))]));
+    }
+
+    /// Each `src/workouts_fit/<name>.fit` is a workout downloaded from Garmin Connect as a FIT
+    /// file, `src/workouts_json/<name>.json` the same workout as Connect's JSON
+    #[test]
+    #[ignore = "needs a swim with repeats, strokes, equipment and a pool length downloaded from Garmin Connect as FIT and JSON"]
+    fn test_matches_connect_downloads() {
+        let mut checked = 0;
+        for entry in fs::read_dir("src/workouts_fit").expect("Missing workouts_fit directory") {
+            let path = entry.unwrap().path();
+            let json_path = Path::new("src/workouts_json").join(path.file_stem().unwrap()).with_extension("json");
+            let workout: Workout = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+
+            let (encoded, downloaded) = (messages(&encode(&workout)), messages(&fs::read(&path).unwrap()));
+            assert_eq!(encoded.len(), downloaded.len(), "{}: message count", path.display());
+            for (index, (encoded, downloaded)) in encoded.iter().zip(&downloaded).enumerate() {
+                assert_eq!(encoded.0, downloaded.0, "{}: message {}", path.display(), index);
+                for number in downloaded.1.iter().chain(&encoded.1).map(|(number, _)| *number) {
+                    let field = |fields: &[(u8, Value)]| fields.iter().find(|(field, _)| *field == number).map(|(_, value)| value.clone());
+                    assert_eq!(
+                        field(&encoded.1),
+                        field(&downloaded.1),
+                        "{}: message {} ({}) field {}",
+                        path.display(),
+                        index,
+                        encoded.0,
+                        number
+                    );
+                }
+            }
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
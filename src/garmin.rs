@@ -4,5 +4,7 @@ pub mod sport_type;
 pub mod workout;
 pub mod unit;
 pub mod author;
-
-
+pub mod timestamp;
+pub mod training_api;
+pub mod validation;
+pub mod estimate;
@@ -14,6 +14,12 @@ pub struct Author {
 }
 
 
+impl Author {
+    pub fn user_profile_pk(&self) -> u32 {
+        self.user_profile_pk
+    }
+}
+
 impl Default for Author {
     fn default() -> Self {
         Author{
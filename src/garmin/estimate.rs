@@ -0,0 +1,198 @@
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::Condition;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::WorkoutStep;
+
+pub const ESTIMATE_TYPE: &str = "TIME_ESTIMATED";
+
+/// How fast the swimmer covers 100 meters, rest excluded
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SwimPace {
+    pub secs_per_100m: f32,
+}
+
+impl SwimPace {
+    pub fn new(secs_per_100m: f32) -> Self {
+        SwimPace { secs_per_100m }
+    }
+
+    /// Meters per second, the unit of `avgTrainingSpeed`
+    pub fn speed(&self) -> f32 {
+        100.0 / self.secs_per_100m
+    }
+}
+
+impl Default for SwimPace {
+    /// 2:00 per 100m, what Garmin Connect assumes without a swimmer profile
+    fn default() -> Self {
+        SwimPace::new(120.0)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct Estimate {
+    pub distance_in_meters: f32,
+    /// Total time including rest
+    pub duration_in_secs: f32,
+    pub rest_in_secs: f32,
+}
+
+impl Estimate {
+    fn add(&mut self, other: Estimate) {
+        self.distance_in_meters += other.distance_in_meters;
+        self.duration_in_secs += other.duration_in_secs;
+        self.rest_in_secs += other.rest_in_secs;
+    }
+
+    fn scale(self, times: f32) -> Estimate {
+        Estimate {
+            distance_in_meters: self.distance_in_meters * times,
+            duration_in_secs: self.duration_in_secs * times,
+            rest_in_secs: self.rest_in_secs * times,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SegmentEstimate {
+    pub total: Estimate,
+    /// One entry per top level step, repeat groups include all their iterations
+    pub steps: Vec<Estimate>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct WorkoutEstimate {
+    pub pace: SwimPace,
+    pub total: Estimate,
+    pub segments: Vec<SegmentEstimate>,
+}
+
+pub fn estimate(workout: &Workout, pace: SwimPace) -> WorkoutEstimate {
+    let mut total = Estimate::default();
+    let segments = workout
+        .workout_segments()
+        .iter()
+        .map(|segment| {
+            let steps = estimate_steps(segment.workout_steps(), pace);
+            let mut segment_total = Estimate::default();
+            steps.iter().for_each(|step| segment_total.add(*step));
+            total.add(segment_total);
+            SegmentEstimate { total: segment_total, steps }
+        })
+        .collect();
+
+    WorkoutEstimate { pace, total, segments }
+}
+
+/// One estimate per step, a send-off rest depends on the step before it
+pub(crate) fn estimate_steps(steps: &[WorkoutStep], pace: SwimPace) -> Vec<Estimate> {
+    let mut estimates: Vec<Estimate> = Vec::with_capacity(steps.len());
+    for step in steps {
+        let estimate = match step {
+            WorkoutStep::Single(single) if is_send_off(single) => {
+                // Rest until the interval is up, counted from the start of the previous step
+                let swum = estimates.last().map(|previous| previous.duration_in_secs).unwrap_or(0.0);
+                let rest = (single.end_condition_value() - swum).max(0.0);
+                Estimate { distance_in_meters: 0.0, duration_in_secs: rest, rest_in_secs: rest }
+            }
+            WorkoutStep::Single(single) => estimate_single(single, pace),
+            WorkoutStep::Repeat(repeat) => estimate_repeat(repeat, pace),
+        };
+        estimates.push(estimate);
+    }
+    estimates
+}
+
+fn is_send_off(step: &ExecutableStepDTO) -> bool {
+    step.step_type().step_type_key == Step::Rest && step.end_condition().condition_type_key == Condition::FixedRepetition
+}
+
+fn estimate_repeat(repeat: &RepeatGroupDTO, pace: SwimPace) -> Estimate {
+    let steps = estimate_steps(repeat.workout_steps(), pace);
+    let mut round = Estimate::default();
+    steps.iter().for_each(|step| round.add(*step));
+    let mut total = round.scale(repeat.number_of_iterations() as f32);
+
+    if repeat.skip_last_rest_step() == Some(true)
+        && let Some(WorkoutStep::Single(last)) = repeat.workout_steps().last()
+        && last.step_type().step_type_key == Step::Rest
+    {
+        let skipped = steps.last().unwrap();
+        total.duration_in_secs -= skipped.duration_in_secs;
+        total.rest_in_secs -= skipped.rest_in_secs;
+    }
+    total
+}
+
+fn estimate_single(step: &ExecutableStepDTO, pace: SwimPace) -> Estimate {
+    let value = step.end_condition_value();
+    let is_rest = step.step_type().step_type_key == Step::Rest;
+
+    match (&step.end_condition().condition_type_key, is_rest) {
+        (Condition::Time | Condition::FixedRest, true) => Estimate {
+            distance_in_meters: 0.0,
+            duration_in_secs: value,
+            rest_in_secs: value,
+        },
+        (Condition::Distance, false) => Estimate {
+            distance_in_meters: value,
+            duration_in_secs: value / pace.speed(),
+            rest_in_secs: 0.0,
+        },
+        (Condition::Time, false) => Estimate {
+            distance_in_meters: value * pace.speed(),
+            duration_in_secs: value,
+            rest_in_secs: 0.0,
+        },
+        // Lap button and threshold steps last as long as the swimmer wants
+        _ => Estimate::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(name: &str) -> Workout {
+        let json_string = fs::read_to_string(format!("src/workouts_json/{}", name)).unwrap();
+        serde_json::from_str(&json_string).unwrap()
+    }
+
+    #[test]
+    fn test_distance_matches_garmin() {
+        assert_eq!(estimate(&load("expected_workout.json"), SwimPace::default()).total.distance_in_meters, 1400.0);
+        assert_eq!(estimate(&load("Core-workout-2.json"), SwimPace::default()).total.distance_in_meters, 1300.0);
+    }
+
+    #[test]
+    fn test_breakdown() {
+        let result = estimate(&load("expected_workout.json"), SwimPace::new(100.0));
+
+        assert_eq!(result.segments.len(), 1);
+        let steps = &result.segments[0].steps;
+        // 400 warmup
+        assert_eq!(steps[0], Estimate { distance_in_meters: 400.0, duration_in_secs: 400.0, rest_in_secs: 0.0 });
+        // Lap button rest
+        assert_eq!(steps[1], Estimate::default());
+        // 8x(100 + 15s rest)
+        assert_eq!(steps[2], Estimate { distance_in_meters: 800.0, duration_in_secs: 920.0, rest_in_secs: 120.0 });
+        // 200 cooldown
+        assert_eq!(steps[4], Estimate { distance_in_meters: 200.0, duration_in_secs: 200.0, rest_in_secs: 0.0 });
+
+        assert_eq!(result.total, Estimate { distance_in_meters: 1400.0, duration_in_secs: 1520.0, rest_in_secs: 120.0 });
+    }
+
+    #[test]
+    fn test_skip_last_rest_step() {
+        let json_string = fs::read_to_string("src/workouts_json/expected_workout.json").unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+        json["workoutSegments"][0]["workoutSteps"][2]["skipLastRestStep"] = true.into();
+        let workout: Workout = serde_json::from_value(json).unwrap();
+
+        let result = estimate(&workout, SwimPace::new(100.0));
+        assert_eq!(result.segments[0].steps[2], Estimate { distance_in_meters: 800.0, duration_in_secs: 905.0, rest_in_secs: 105.0 });
+    }
+}
@@ -1,31 +1,70 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Sport{
-    Swimming
+    Running,
+    Cycling,
+    Other,
+    Swimming,
+    StrengthTraining,
+    CardioTraining,
+    Yoga,
+    Pilates,
+    Hiit,
+    MultiSport,
+    Mobility,
+    /// Key added by Garmin after this crate was written
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SportType {
-    sport_type_key: Sport
+    sport_type_key: Sport,
+    // Only used for unknown keys, known keys derive them
+    #[serde(default, rename = "sportTypeId")]
+    received_id: Option<u8>,
+    #[serde(default, rename = "displayOrder")]
+    received_display_order: Option<u8>,
 }
 
 impl SportType {
-    fn sport_type_id(&self) -> u8{
+    pub fn sport_type_id(&self) -> u8{
         match self.sport_type_key {
+            Sport::Running => 1,
+            Sport::Cycling => 2,
+            Sport::Other => 3,
             Sport::Swimming => 4,
+            Sport::StrengthTraining => 5,
+            Sport::CardioTraining => 6,
+            Sport::Yoga => 7,
+            Sport::Pilates => 8,
+            Sport::Hiit => 9,
+            Sport::MultiSport => 10,
+            Sport::Mobility => 11,
+            Sport::Unknown(_) => self.received_id.unwrap_or_default(),
         }
     }
 
-    fn display_order(&self) -> u8 {
-        self.sport_type_id() - 1
+    pub fn display_order(&self) -> u8 {
+        // Garmin lists swimming before "other" in the sport picker
+        match self.sport_type_key {
+            Sport::Other => 4,
+            Sport::Swimming => 3,
+            Sport::Unknown(_) => self.received_display_order.unwrap_or_default(),
+            _ => self.sport_type_id(),
+        }
+    }
+
+    pub fn sport(&self) -> Sport {
+        self.sport_type_key.clone()
     }
 
     pub fn new(sport: Sport) -> SportType {
-        SportType{sport_type_key: sport}
+        SportType{sport_type_key: sport, received_id: None, received_display_order: None}
     }
 }
 
@@ -72,10 +111,41 @@ mod tests {
          }
          "#.chars().filter(|c| !c.is_whitespace()).collect::<String>();
 
-        let object = SportType{sport_type_key: Sport::Swimming};
+        let object = SportType::new(Sport::Swimming);
 
         let json_result = serde_json::to_string(&object).unwrap();
 
         assert_eq!(json_result, expected_json);
     }
+
+    #[test]
+    fn test_sport_catalogue() {
+        let cases = [
+            (Sport::Running, r#"{"sportTypeId":1,"sportTypeKey":"running","displayOrder":1}"#),
+            (Sport::Cycling, r#"{"sportTypeId":2,"sportTypeKey":"cycling","displayOrder":2}"#),
+            (Sport::Other, r#"{"sportTypeId":3,"sportTypeKey":"other","displayOrder":4}"#),
+            (Sport::StrengthTraining, r#"{"sportTypeId":5,"sportTypeKey":"strength_training","displayOrder":5}"#),
+            (Sport::CardioTraining, r#"{"sportTypeId":6,"sportTypeKey":"cardio_training","displayOrder":6}"#),
+            (Sport::Yoga, r#"{"sportTypeId":7,"sportTypeKey":"yoga","displayOrder":7}"#),
+            (Sport::Pilates, r#"{"sportTypeId":8,"sportTypeKey":"pilates","displayOrder":8}"#),
+            (Sport::Hiit, r#"{"sportTypeId":9,"sportTypeKey":"hiit","displayOrder":9}"#),
+            (Sport::MultiSport, r#"{"sportTypeId":10,"sportTypeKey":"multi_sport","displayOrder":10}"#),
+            (Sport::Mobility, r#"{"sportTypeId":11,"sportTypeKey":"mobility","displayOrder":11}"#),
+        ];
+
+        for (sport, expected_json) in cases {
+            assert_eq!(serde_json::to_string(&SportType::new(sport.clone())).unwrap(), expected_json);
+
+            let result: SportType = serde_json::from_str(expected_json).unwrap();
+            assert_eq!(result.sport(), sport);
+        }
+    }
+
+    #[test]
+    fn test_unknown_sport() {
+        let json = r#"{"sportTypeId":21,"sportTypeKey":"e_bike","displayOrder":18}"#;
+        let result: SportType = serde_json::from_str(json).unwrap();
+        assert_eq!(result.sport(), Sport::Unknown("e_bike".to_string()));
+        assert_eq!(serde_json::to_string(&result).unwrap(), json);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+use chrono::{NaiveDateTime, Timelike, Utc};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Garmin writes timestamps with a single fractional digit, e.g. `2025-04-03T15:49:08.0`
+const PARSE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.f";
+const SECONDS_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Writes at least one fractional digit, and as many as it takes to keep the fraction
+pub fn format(timestamp: &NaiveDateTime) -> String {
+    let nanos = format!("{:09}", timestamp.nanosecond() % 1_000_000_000);
+    let fraction = nanos.trim_end_matches('0');
+    let fraction = if fraction.is_empty() { "0" } else { fraction };
+    format!("{}.{}", timestamp.format(SECONDS_FORMAT), fraction)
+}
+
+pub fn parse(value: &str) -> Result<NaiveDateTime, chrono::ParseError> {
+    NaiveDateTime::parse_from_str(value, PARSE_FORMAT)
+}
+
+pub fn serialize<S>(timestamp: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format(timestamp))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    parse(&value).map_err(serde::de::Error::custom)
+}
+
+pub mod option {
+    use chrono::NaiveDateTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(timestamp: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match timestamp {
+            Some(timestamp) => super::serialize(timestamp, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| super::parse(&value).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Source of the current time, so generated workouts can be made reproducible
+pub trait Clock {
+    fn now(&self) -> NaiveDateTime;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    /// Rounded down to tenths, the way Garmin Connect writes them
+    fn now(&self) -> NaiveDateTime {
+        let now = Utc::now().naive_utc();
+        now.with_nanosecond(now.nanosecond() / 100_000_000 * 100_000_000).unwrap_or(now)
+    }
+}
+
+pub struct FixedClock(pub NaiveDateTime);
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use serde::Serialize;
+
+    #[derive(Serialize, Deserialize)]
+    struct Dates {
+        #[serde(with = "super")]
+        created: NaiveDateTime,
+        #[serde(with = "super::option")]
+        uploaded: Option<NaiveDateTime>,
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let timestamp = parse("2025-04-03T15:49:08.0").unwrap();
+        assert_eq!(timestamp, NaiveDate::from_ymd_opt(2025, 4, 3).unwrap().and_hms_opt(15, 49, 8).unwrap());
+        assert_eq!(format(&timestamp), "2025-04-03T15:49:08.0");
+    }
+
+    #[test]
+    fn test_format_keeps_the_fraction() {
+        let timestamp = NaiveDate::from_ymd_opt(2025, 4, 3).unwrap().and_hms_milli_opt(15, 49, 8, 789).unwrap();
+        assert_eq!(format(&timestamp), "2025-04-03T15:49:08.789");
+        assert_eq!(format(&parse("2025-04-03T15:49:08.123").unwrap()), "2025-04-03T15:49:08.123");
+        assert_eq!(format(&parse("2025-04-03T15:49:08").unwrap()), "2025-04-03T15:49:08.0");
+        assert_eq!(SystemClock.now().nanosecond() % 100_000_000, 0);
+    }
+
+    #[test]
+    fn test_serde_adapters() {
+        let json = r#"{"created":"2025-04-06T19:55:57.0","uploaded":null}"#;
+        let dates: Dates = serde_json::from_str(json).unwrap();
+        assert_eq!(dates.uploaded, None);
+        assert_eq!(serde_json::to_string(&dates).unwrap(), json);
+
+        let json = r#"{"created":"2025-04-06T19:55:57.0","uploaded":"2025-04-07T08:00:00.5"}"#;
+        let dates: Dates = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&dates).unwrap(), json);
+    }
+
+    #[test]
+    fn test_fixed_clock() {
+        let now = parse("2025-04-06T19:55:57.0").unwrap();
+        assert_eq!(FixedClock(now).now(), now);
+    }
+}
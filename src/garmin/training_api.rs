@@ -0,0 +1,998 @@
+//! The workout schema of Garmin's partner Training API: one flat `steps` list of
+//! `WorkoutStep`s and `WorkoutRepeatStep`s described by `intensity`, `durationType`,
+//! `targetType` and `repeatValue`, where Connect uses nested type objects.
+//!
+//! What a step means survives the trip both ways, apart from what export reports as a
+//! [`Loss`]: fields this crate doesn't know and distances shown in kilometers or miles.
+//! Multi-sport workouts, with more than one segment, are refused. Connect bookkeeping the
+//! schema has no field for (step orders, child step ids, display orders, the author) is
+//! derived again on import.
+use crate::builder::FIRST_STEP_ID;
+use crate::convert::Loss;
+use crate::garmin::author::Author;
+use crate::garmin::sport_type::Sport;
+use crate::garmin::timestamp::Clock;
+use crate::garmin::unit::Unit;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_segments::WorkoutSegment;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition, EndConditionCompare};
+use crate::garmin::workout_steps::equipment_type::{Equipment, EquipmentType};
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::{PreferredEndConditionUnit, Unit as DistanceUnit};
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::{Step, StepType};
+use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
+use crate::garmin::workout_steps::target_type::{Target, TargetFields};
+use crate::garmin::workout_steps::WorkoutStep;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cell::Cell;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TrainingApiError {
+    /// The schema has one step list, so which steps belong to which sport would be lost
+    MultipleSegments(usize),
+}
+
+impl fmt::Display for TrainingApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrainingApiError::MultipleSegments(segments) => {
+                write!(f, "{} segments, a Training API workout has one step list", segments)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrainingApiError {}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiWorkout {
+    workout_id: Option<u64>,
+    owner_id: Option<u64>,
+    workout_name: String,
+    description: Option<String>,
+    #[serde(default, with = "crate::garmin::timestamp::option")]
+    updated_date: Option<NaiveDateTime>,
+    #[serde(default, with = "crate::garmin::timestamp::option")]
+    created_date: Option<NaiveDateTime>,
+    sport: ApiSport,
+    estimated_duration_in_secs: Option<u32>,
+    estimated_distance_in_meters: Option<f32>,
+    pool_length: Option<f32>,
+    pool_length_unit: Option<PoolUnit>,
+    workout_provider: Option<String>,
+    workout_source_id: Option<String>,
+    steps: Vec<ApiStep>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ApiSport {
+    Running,
+    Cycling,
+    LapSwimming,
+    StrengthTraining,
+    CardioTraining,
+    Generic,
+    Yoga,
+    Pilates,
+    Hiit,
+    MultiSport,
+    Mobility,
+}
+
+const SPORTS: [(Sport, ApiSport); 11] = [
+    (Sport::Running, ApiSport::Running),
+    (Sport::Cycling, ApiSport::Cycling),
+    (Sport::Swimming, ApiSport::LapSwimming),
+    (Sport::StrengthTraining, ApiSport::StrengthTraining),
+    (Sport::CardioTraining, ApiSport::CardioTraining),
+    (Sport::Other, ApiSport::Generic),
+    (Sport::Yoga, ApiSport::Yoga),
+    (Sport::Pilates, ApiSport::Pilates),
+    (Sport::Hiit, ApiSport::Hiit),
+    (Sport::MultiSport, ApiSport::MultiSport),
+    (Sport::Mobility, ApiSport::Mobility),
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum PoolUnit {
+    Meter,
+    Yard,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
+enum ApiStep {
+    #[serde(rename = "WorkoutStep")]
+    Single(ApiSingle),
+    #[serde(rename = "WorkoutRepeatStep")]
+    Repeat(ApiRepeat),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiSingle {
+    step_id: Option<u64>,
+    step_order: Option<u64>,
+    intensity: Intensity,
+    description: Option<String>,
+    duration_type: DurationType,
+    /// Seconds, meters, calories, bpm, watts or reps
+    duration_value: Option<f32>,
+    duration_value_type: Option<ValueType>,
+    #[serde(flatten)]
+    target: ApiTarget,
+    #[serde(flatten)]
+    secondary_target: ApiSecondaryTarget,
+    stroke_type: Option<ApiStroke>,
+    drill_type: Option<String>,
+    equipment_type: Option<ApiEquipment>,
+    exercise_category: Option<String>,
+    exercise_name: Option<String>,
+    /// Kilograms
+    weight_value: Option<f32>,
+    weight_display_unit: Option<WeightUnit>,
+}
+
+/// Zones go in `targetValue`, ranges in `targetValueLow` and `targetValueHigh`, with speed
+/// and pace as meters per second
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ApiTarget {
+    target_type: Option<TargetKind>,
+    target_value: Option<f64>,
+    target_value_low: Option<f64>,
+    target_value_high: Option<f64>,
+    target_value_type: Option<ValueType>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ApiSecondaryTarget {
+    secondary_target_type: Option<TargetKind>,
+    secondary_target_value: Option<f64>,
+    secondary_target_value_low: Option<f64>,
+    secondary_target_value_high: Option<f64>,
+    secondary_target_value_type: Option<ValueType>,
+}
+
+impl From<ApiSecondaryTarget> for ApiTarget {
+    fn from(secondary: ApiSecondaryTarget) -> Self {
+        ApiTarget {
+            target_type: secondary.secondary_target_type,
+            target_value: secondary.secondary_target_value,
+            target_value_low: secondary.secondary_target_value_low,
+            target_value_high: secondary.secondary_target_value_high,
+            target_value_type: secondary.secondary_target_value_type,
+        }
+    }
+}
+
+impl From<ApiTarget> for ApiSecondaryTarget {
+    fn from(target: ApiTarget) -> Self {
+        ApiSecondaryTarget {
+            secondary_target_type: target.target_type,
+            secondary_target_value: target.target_value,
+            secondary_target_value_low: target.target_value_low,
+            secondary_target_value_high: target.target_value_high,
+            secondary_target_value_type: target.target_value_type,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiRepeat {
+    step_id: Option<u64>,
+    step_order: Option<u64>,
+    repeat_type: RepeatType,
+    repeat_value: f32,
+    skip_last_rest_step: Option<bool>,
+    steps: Vec<ApiStep>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum Intensity {
+    Warmup,
+    Active,
+    Rest,
+    Cooldown,
+    Interval,
+    Recovery,
+    Main,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum DurationType {
+    Open,
+    Time,
+    Distance,
+    Calories,
+    HrLessThan,
+    HrGreaterThan,
+    PowerLessThan,
+    PowerGreaterThan,
+    FixedRest,
+    FixedRepetition,
+    Reps,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// The only modifier the API defines, the value is a percentage of a threshold
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ValueType {
+    Percent,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TargetKind {
+    Open,
+    Speed,
+    Pace,
+    HeartRate,
+    Cadence,
+    Power,
+    SwimInstruction,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+const TARGETS: [(Target, TargetKind); 7] = [
+    (Target::NoTarget, TargetKind::Open),
+    (Target::SpeedZone, TargetKind::Speed),
+    (Target::PaceZone, TargetKind::Pace),
+    (Target::HeartRateZone, TargetKind::HeartRate),
+    (Target::Cadence, TargetKind::Cadence),
+    (Target::PowerZone, TargetKind::Power),
+    (Target::SwimInstruction, TargetKind::SwimInstruction),
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum RepeatType {
+    RepeatUntilStepsCmplt,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ApiStroke {
+    Backstroke,
+    Breaststroke,
+    Butterfly,
+    Freestyle,
+    Im,
+    Mixed,
+    Drill,
+    /// Swimmer's choice
+    Choice,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+const STROKES: [(Stroke, ApiStroke); 8] = [
+    (Stroke::Back, ApiStroke::Backstroke),
+    (Stroke::Breast, ApiStroke::Breaststroke),
+    (Stroke::Butterfly, ApiStroke::Butterfly),
+    (Stroke::Free, ApiStroke::Freestyle),
+    (Stroke::IndividualMedley, ApiStroke::Im),
+    (Stroke::Mixed, ApiStroke::Mixed),
+    (Stroke::Drill, ApiStroke::Drill),
+    (Stroke::AnyStroke, ApiStroke::Choice),
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum ApiEquipment {
+    None,
+    SwimFins,
+    SwimKickboard,
+    SwimPaddles,
+    SwimPullBuoy,
+    SwimSnorkel,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+const EQUIPMENT: [(Equipment, ApiEquipment); 5] = [
+    (Equipment::Fins, ApiEquipment::SwimFins),
+    (Equipment::Kickboard, ApiEquipment::SwimKickboard),
+    (Equipment::Paddles, ApiEquipment::SwimPaddles),
+    (Equipment::PullBuoy, ApiEquipment::SwimPullBuoy),
+    (Equipment::Snorkel, ApiEquipment::SwimSnorkel),
+];
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum WeightUnit {
+    Kilogram,
+    Pound,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+/// Connect's weight units, `(key, unitId, factor)`
+const WEIGHT_UNITS: [(&str, u8, f32, WeightUnit); 2] =
+    [("kilogram", 8, 1000.0, WeightUnit::Kilogram), ("pound", 9, 453.59237, WeightUnit::Pound)];
+
+/// Looks `key` up in one of the mapping tables above, left to right
+fn lookup<A: PartialEq, B: Clone>(table: &[(A, B)], key: &A) -> Option<B> {
+    table.iter().find(|(a, _)| a == key).map(|(_, b)| b.clone())
+}
+
+/// And right to left
+fn reverse<A: Clone, B: PartialEq>(table: &[(A, B)], key: &B) -> Option<A> {
+    table.iter().find(|(_, b)| b == key).map(|(a, _)| a.clone())
+}
+
+/// Writes the workout in the Training API schema, distances shown in kilometers or miles are
+/// sent as meters
+pub fn export(workout: &Workout) -> Result<(String, Vec<Loss>), TrainingApiError> {
+    let segments = workout.workout_segments();
+    if segments.len() > 1 {
+        return Err(TrainingApiError::MultipleSegments(segments.len()));
+    }
+    let sport = workout.sport_type().sport();
+    let pool_unit = pool_unit(workout);
+    let mut exporter = Exporter { pool_unit, losses: Vec::new() };
+    let api_sport = lookup(&SPORTS, &sport).unwrap_or_else(|| {
+        exporter.losses.push(Loss::new("workout", format!("sport {} sent as GENERIC", key(&sport))));
+        ApiSport::Generic
+    });
+    exporter.extra(workout.extra(), "workout");
+
+    let mut steps = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let path = format!("workoutSegments[{}]", i);
+        exporter.extra(segment.extra(), &path);
+        for (j, step) in segment.workout_steps().iter().enumerate() {
+            steps.push(exporter.step(step, &format!("{}.workoutSteps[{}]", path, j)));
+        }
+    }
+
+    let swimming = sport == Sport::Swimming;
+    let api = ApiWorkout {
+        workout_id: Some(workout.workout_id()).filter(|id| *id != 0),
+        owner_id: Some(workout.owner_id()),
+        workout_name: workout.workout_name().to_string(),
+        description: workout.description().map(str::to_string),
+        updated_date: Some(workout.updated_date()),
+        created_date: Some(workout.created_date()),
+        sport: api_sport,
+        estimated_duration_in_secs: Some(workout.estimated_duration_in_secs()),
+        estimated_distance_in_meters: workout.estimated_distance_in_meters(),
+        pool_length: swimming.then_some(workout.pool_length()),
+        pool_length_unit: swimming.then_some(if pool_unit == DistanceUnit::Yard { PoolUnit::Yard } else { PoolUnit::Meter }),
+        workout_provider: workout.workout_provider().0.map(str::to_string),
+        workout_source_id: workout.workout_provider().1.map(str::to_string),
+        steps,
+    };
+    let json = serde_json::to_string_pretty(&api).expect("the Training API schema always serializes");
+    Ok((json, exporter.losses))
+}
+
+/// The unit a distance step is shown in when nothing says otherwise
+fn pool_unit(workout: &Workout) -> DistanceUnit {
+    match workout.pool_length_unit().distance_unit() {
+        Some(DistanceUnit::Yard) if workout.sport_type().sport() == Sport::Swimming => DistanceUnit::Yard,
+        _ => DistanceUnit::Meter,
+    }
+}
+
+struct Exporter {
+    pool_unit: DistanceUnit,
+    losses: Vec<Loss>,
+}
+
+impl Exporter {
+    /// The schema has nowhere to keep them
+    fn extra(&mut self, extra: &Map<String, Value>, path: &str) {
+        for field in extra.keys() {
+            self.losses.push(Loss::new(path, format!("unknown field `{}` left out", field)));
+        }
+    }
+
+    fn step(&mut self, step: &WorkoutStep, path: &str) -> ApiStep {
+        match step {
+            WorkoutStep::Single(single) => ApiStep::Single(self.single(single, path)),
+            WorkoutStep::Repeat(repeat) => {
+                self.extra(repeat.extra(), path);
+                let steps = repeat
+                    .workout_steps()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, step)| self.step(step, &format!("{}.workoutSteps[{}]", path, i)))
+                    .collect();
+                ApiStep::Repeat(ApiRepeat {
+                    step_id: Some(repeat.step_id()),
+                    step_order: Some(repeat.step_order().into()),
+                    repeat_type: RepeatType::RepeatUntilStepsCmplt,
+                    repeat_value: repeat.number_of_iterations() as f32,
+                    skip_last_rest_step: repeat.skip_last_rest_step(),
+                    steps,
+                })
+            }
+        }
+    }
+
+    fn single(&mut self, step: &ExecutableStepDTO, path: &str) -> ApiSingle {
+        let intensity = match &step.step_type().step_type_key {
+            Step::Warmup => Intensity::Warmup,
+            Step::Cooldown => Intensity::Cooldown,
+            Step::Rest => Intensity::Rest,
+            Step::Main => Intensity::Active,
+            Step::Interval => Intensity::Interval,
+            Step::Recovery => Intensity::Recovery,
+            other => {
+                self.losses.push(Loss::new(path, format!("step type {} sent as ACTIVE", key(other))));
+                Intensity::Active
+            }
+        };
+
+        let compare = step.end_condition_compare();
+        let duration_type = match &step.end_condition().condition_type_key {
+            Condition::LapButton => DurationType::Open,
+            Condition::Time => DurationType::Time,
+            Condition::Distance => DurationType::Distance,
+            Condition::Calories => DurationType::Calories,
+            Condition::HeartRate if compare == Some(EndConditionCompare::LessThan) => DurationType::HrLessThan,
+            Condition::HeartRate => DurationType::HrGreaterThan,
+            Condition::Power if compare == Some(EndConditionCompare::LessThan) => DurationType::PowerLessThan,
+            Condition::Power => DurationType::PowerGreaterThan,
+            Condition::FixedRest => DurationType::FixedRest,
+            Condition::FixedRepetition => DurationType::FixedRepetition,
+            Condition::Reps => DurationType::Reps,
+            other => {
+                self.losses.push(Loss::new(path, format!("end condition {} sent as OPEN", key(other))));
+                DurationType::Open
+            }
+        };
+        if step.end_condition().requires_compare() && compare.is_none() {
+            self.losses.push(Loss::new(path, "threshold without a direction sent as greater than"));
+        }
+        if duration_type == DurationType::Distance
+            && let Some(unit) = step.preferred_end_condition_unit().map(PreferredEndConditionUnit::unit)
+            && unit != self.pool_unit
+            && unit != DistanceUnit::Meter
+        {
+            self.losses.push(Loss::new(path, format!("distance shown in {}s sent in meters", unit.key())));
+        }
+
+        let stroke_type = step.stroke_type().stroke_type_key.as_ref().and_then(|stroke| {
+            let api_stroke = lookup(&STROKES, stroke);
+            if api_stroke.is_none() {
+                self.losses.push(Loss::new(path, format!("stroke {} left out", key(stroke))));
+            }
+            api_stroke
+        });
+        let weight = step.weight().and_then(|(value, unit)| {
+            let api_unit = WEIGHT_UNITS
+                .iter()
+                .find(|(key, ..)| unit.unit_key.as_deref() == Some(key))
+                .map(|(_, _, factor, api_unit)| (value * factor / 1000.0, api_unit.clone()));
+            if api_unit.is_none() {
+                self.losses.push(Loss::new(path, format!("weight in {:?} left out", unit.unit_key)));
+            }
+            api_unit
+        });
+        let equipment_type = step.equipment_type().equipment_type_key.as_ref().and_then(|equipment| {
+            let api_equipment = lookup(&EQUIPMENT, equipment);
+            if api_equipment.is_none() {
+                self.losses.push(Loss::new(path, format!("equipment {} left out", key(equipment))));
+            }
+            api_equipment
+        });
+        let (category, exercise_name) = step.exercise();
+        self.extra(step.extra(), path);
+
+        ApiSingle {
+            step_id: Some(step.step_id()),
+            step_order: Some(step.step_order().into()),
+            intensity,
+            description: step.description().map(str::to_string),
+            duration_type,
+            duration_value: Some(step.end_condition_value()),
+            duration_value_type: None,
+            target: self.target(step.target_fields(), path),
+            secondary_target: self.target(step.secondary_target_fields(), path).into(),
+            stroke_type,
+            drill_type: None,
+            equipment_type,
+            exercise_category: category.map(str::to_string),
+            exercise_name: exercise_name.map(str::to_string),
+            weight_value: weight.as_ref().map(|(kilograms, _)| *kilograms),
+            weight_display_unit: weight.map(|(_, unit)| unit),
+        }
+    }
+
+    fn target(&mut self, fields: TargetFields, path: &str) -> ApiTarget {
+        let Some(target) = fields.target else {
+            return ApiTarget::default();
+        };
+        let Some(kind) = lookup(&TARGETS, &target) else {
+            self.losses.push(Loss::new(path, format!("target {} left out", key(&target))));
+            return ApiTarget::default();
+        };
+        // Connect keeps the swim instruction intensity in the first value
+        let (value, low, high) = match kind {
+            TargetKind::Open => (None, None, None),
+            TargetKind::SwimInstruction => (fields.value_one, None, None),
+            _ => (fields.zone_number.map(f64::from), fields.value_one, fields.value_two),
+        };
+        ApiTarget { target_type: Some(kind), target_value: value, target_value_low: low, target_value_high: high, target_value_type: None }
+    }
+}
+
+/// The key a value is written as, Connect's or the Training API's
+fn key(value: &impl Serialize) -> String {
+    serde_json::to_value(value).ok().and_then(|key| key.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+/// Reads a Training API workout. Dates missing from a workout that was never saved are taken
+/// from the clock, step ids are handed out again from the first one.
+pub fn import(json: &str, clock: &impl Clock) -> Result<(Workout, Vec<Loss>), serde_json::Error> {
+    let api: ApiWorkout = serde_json::from_str(json)?;
+    let sport = reverse(&SPORTS, &api.sport).unwrap_or(Sport::Other);
+    let pool_unit = match api.pool_length_unit {
+        Some(PoolUnit::Yard) if sport == Sport::Swimming => DistanceUnit::Yard,
+        _ => DistanceUnit::Meter,
+    };
+
+    let first_step_id = first_step_id(&api.steps).unwrap_or(FIRST_STEP_ID);
+    let mut importer = Importer { pool_unit, losses: Vec::new() };
+    let steps = api
+        .steps
+        .into_iter()
+        .enumerate()
+        .map(|(i, step)| importer.step(step, &format!("steps[{}]", i)))
+        .collect();
+
+    let now = clock.now();
+    let updated = api.updated_date.unwrap_or(now);
+    let created = api.created_date.unwrap_or(updated);
+    let owner_id = api.owner_id.unwrap_or(Author::default().user_profile_pk() as u64);
+    let segments = vec![WorkoutSegment::with_sport(1, sport.clone(), steps)];
+    let mut workout = match sport {
+        Sport::Swimming => Workout::new_swimming_workout(
+            api.workout_id.unwrap_or(0),
+            owner_id,
+            api.workout_name,
+            api.description,
+            updated,
+            created,
+            segments,
+            api.pool_length.unwrap_or(25.0),
+            Unit::from(pool_unit),
+        ),
+        _ => Workout::new(
+            sport,
+            api.workout_id.unwrap_or(0),
+            owner_id,
+            api.workout_name,
+            api.description,
+            updated,
+            created,
+            segments,
+        ),
+    };
+    workout.renumber_steps(first_step_id);
+    workout.set_estimated(api.estimated_duration_in_secs.unwrap_or(0), api.estimated_distance_in_meters);
+    workout.set_workout_provider(api.workout_provider, api.workout_source_id);
+    Ok((workout, importer.losses))
+}
+
+fn first_step_id(steps: &[ApiStep]) -> Option<u64> {
+    match steps.first()? {
+        ApiStep::Single(single) => single.step_id,
+        ApiStep::Repeat(repeat) => repeat.step_id,
+    }
+}
+
+struct Importer {
+    pool_unit: DistanceUnit,
+    losses: Vec<Loss>,
+}
+
+impl Importer {
+    fn step(&mut self, step: ApiStep, path: &str) -> WorkoutStep {
+        match step {
+            ApiStep::Single(single) => WorkoutStep::Single(self.single(single, path)),
+            ApiStep::Repeat(repeat) => {
+                let iterations = match repeat.repeat_type {
+                    RepeatType::RepeatUntilStepsCmplt => {
+                        let iterations = repeat.repeat_value.round();
+                        if (1.0..=u8::MAX as f32).contains(&iterations) {
+                            iterations as u8
+                        } else {
+                            let clamped = if iterations > 1.0 { u8::MAX } else { 1 };
+                            self.losses.push(Loss::new(path, format!("repeatValue {} read as {}", repeat.repeat_value, clamped)));
+                            clamped
+                        }
+                    }
+                    RepeatType::Unknown(repeat_type) => {
+                        let message = format!("{} {} read as a single pass", repeat_type, repeat.repeat_value);
+                        self.losses.push(Loss::new(path, message));
+                        1
+                    }
+                };
+                let steps = repeat
+                    .steps
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, step)| self.step(step, &format!("{}.steps[{}]", path, i)))
+                    .collect();
+                let mut group = RepeatGroupDTO::new(0, 0, 0, iterations, steps);
+                group.set_skip_last_rest_step(repeat.skip_last_rest_step);
+                WorkoutStep::Repeat(group)
+            }
+        }
+    }
+
+    fn single(&mut self, single: ApiSingle, path: &str) -> ExecutableStepDTO {
+        let step_type = match single.intensity {
+            Intensity::Warmup => StepType::new(Step::Warmup),
+            Intensity::Cooldown => StepType::new(Step::Cooldown),
+            Intensity::Rest => StepType::new(Step::Rest),
+            Intensity::Active | Intensity::Main => StepType::new(Step::Main),
+            // Kinds Connect has ids for but this crate no key
+            Intensity::Interval => StepType::new(Step::Interval),
+            Intensity::Recovery => StepType::new(Step::Recovery),
+            Intensity::Unknown(intensity) => {
+                self.losses.push(Loss::new(path, format!("{} read as a main step", intensity)));
+                StepType::new(Step::Main)
+            }
+        };
+
+        let (condition, compare) = match single.duration_type {
+            DurationType::Open => (Condition::LapButton, None),
+            DurationType::Time => (Condition::Time, None),
+            DurationType::Distance => (Condition::Distance, None),
+            DurationType::Calories => (Condition::Calories, None),
+            DurationType::HrLessThan => (Condition::HeartRate, Some(EndConditionCompare::LessThan)),
+            DurationType::HrGreaterThan => (Condition::HeartRate, Some(EndConditionCompare::GreaterThan)),
+            DurationType::PowerLessThan => (Condition::Power, Some(EndConditionCompare::LessThan)),
+            DurationType::PowerGreaterThan => (Condition::Power, Some(EndConditionCompare::GreaterThan)),
+            DurationType::FixedRest => (Condition::FixedRest, None),
+            DurationType::FixedRepetition => (Condition::FixedRepetition, None),
+            DurationType::Reps => (Condition::Reps, None),
+            DurationType::Unknown(duration_type) => {
+                self.losses.push(Loss::new(path, format!("duration {} read as lap button", duration_type)));
+                (Condition::LapButton, None)
+            }
+        };
+        if single.duration_value_type.is_some() {
+            self.losses.push(Loss::new(path, "duration as a percentage of threshold read as an absolute value"));
+        }
+
+        let stroke = single.stroke_type.and_then(|stroke| {
+            let ours = reverse(&STROKES, &stroke);
+            if ours.is_none() {
+                self.losses.push(Loss::new(path, format!("stroke {} left out", key(&stroke))));
+            }
+            ours
+        });
+        let equipment = match single.equipment_type {
+            None | Some(ApiEquipment::None) => None,
+            Some(equipment) => {
+                let ours = reverse(&EQUIPMENT, &equipment);
+                if ours.is_none() {
+                    self.losses.push(Loss::new(path, format!("equipment {} left out", key(&equipment))));
+                }
+                ours
+            }
+        };
+        if let Some(drill_type) = single.drill_type {
+            self.losses.push(Loss::new(path, format!("drill type {} left out", drill_type)));
+        }
+
+        let value = single.duration_value.unwrap_or(0.0);
+        let distance = condition == Condition::Distance;
+        let mut step = ExecutableStepDTO::new(
+            single.step_id.unwrap_or(0),
+            0,
+            step_type,
+            Cell::new(None),
+            single.description,
+            EndCondition::new(condition),
+            value,
+            None,
+            StrokeType::new(stroke),
+            EquipmentType::new(equipment),
+            !distance,
+        );
+        if distance {
+            step.set_distance(PreferredEndConditionUnit::new(self.pool_unit).from_meters(value), self.pool_unit);
+        }
+        step.set_end_condition_compare(compare);
+        step.set_target_fields(self.target(single.target, path));
+        step.set_secondary_target_fields(self.target(single.secondary_target.into(), path));
+        step.set_exercise(single.exercise_category, single.exercise_name);
+
+        let weight = single.weight_value.zip(single.weight_display_unit).and_then(|(kilograms, unit)| {
+            let found = WEIGHT_UNITS.iter().find(|(.., api_unit)| *api_unit == unit);
+            if found.is_none() {
+                self.losses.push(Loss::new(path, format!("weight in {} left out", key(&unit))));
+            }
+            found.map(|(key, unit_id, factor, _)| {
+                let unit = Unit { unit_id: Some(*unit_id), unit_key: Some(key.to_string()), factor: Some(*factor) };
+                (kilograms * 1000.0 / factor, unit)
+            })
+        });
+        step.set_weight(weight);
+        step
+    }
+
+    fn target(&mut self, target: ApiTarget, path: &str) -> TargetFields {
+        let Some(kind) = target.target_type else {
+            return TargetFields::default();
+        };
+        let Some(ours) = reverse(&TARGETS, &kind) else {
+            self.losses.push(Loss::new(path, format!("target {} left out", key(&kind))));
+            return TargetFields::default();
+        };
+        if target.target_value_type.is_some() {
+            let message = format!("target {} as a percentage of threshold left out", key(&kind));
+            self.losses.push(Loss::new(path, message));
+            return TargetFields { target: Some(Target::NoTarget), ..TargetFields::default() };
+        }
+        match ours {
+            Target::NoTarget => TargetFields { target: Some(ours), ..TargetFields::default() },
+            Target::SwimInstruction => TargetFields { target: Some(ours), value_one: target.target_value, ..TargetFields::default() },
+            _ => {
+                let zone_number = target.target_value.map(f64::round);
+                if let Some(zone) = zone_number.filter(|zone| !(1.0..=u8::MAX as f64).contains(zone)) {
+                    self.losses.push(Loss::new(path, format!("target {} zone {} left out", key(&kind), zone)));
+                    return TargetFields { target: Some(Target::NoTarget), ..TargetFields::default() };
+                }
+                TargetFields {
+                    target: Some(ours),
+                    value_one: target.target_value_low,
+                    value_two: target.target_value_high,
+                    zone_number: zone_number.map(|zone| zone as u8),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{self, AnyStroke, Free, StepsBuilder, Units};
+    use crate::garmin::timestamp::{self, FixedClock};
+    use crate::garmin::workout_steps::target_type::StepTarget;
+    use std::fs;
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    /// What a step means, leaving out how Connect happens to have stored it
+    fn meaning(workout: &Workout) -> Vec<String> {
+        fn collect(steps: &[WorkoutStep], lines: &mut Vec<String>) {
+            for step in steps {
+                match step {
+                    WorkoutStep::Single(single) => {
+                        let secondary = single.secondary_target_fields();
+                        lines.push(format!(
+                            "{} {:?} {:?} {} {:?} {:?} {:?} {:?}/{:?} {:?} {:?} {:?} {:?} {:?}",
+                            single.step_id(),
+                            single.step_type().step_type_key,
+                            single.end_condition().condition_type_key,
+                            single.end_condition_value(),
+                            single.preferred_end_condition_unit().map(PreferredEndConditionUnit::unit),
+                            single.end_condition().requires_compare().then(|| single.end_condition_compare()),
+                            single.target(),
+                            secondary.target,
+                            secondary.value_one,
+                            single.stroke_type().stroke_type_key,
+                            single.equipment_type().equipment_type_key,
+                            single.description(),
+                            single.exercise(),
+                            single.weight().map(|(value, unit)| (value, unit.unit_key.clone())),
+                        ));
+                        lines.push(format!("  shown as {} {:?}", single.distance_in_preferred_unit(), single.extra()));
+                    }
+                    WorkoutStep::Repeat(repeat) => {
+                        let (id, iterations, skip) = (repeat.step_id(), repeat.number_of_iterations(), repeat.skip_last_rest_step());
+                        lines.push(format!("{} {}x {:?} {:?}", id, iterations, skip, repeat.extra()));
+                        collect(repeat.workout_steps(), lines);
+                    }
+                }
+            }
+        }
+
+        let mut lines = vec![format!(
+            "{} {} {:?} {:?} {} {:?} {:?}",
+            workout.workout_id(),
+            workout.workout_name(),
+            workout.sport_type().sport(),
+            workout.description(),
+            workout.pool_length(),
+            workout.pool_length_unit().distance_unit(),
+            workout.estimated_distance_in_meters(),
+        )];
+        lines.push(format!("{:?}", workout.extra()));
+        for segment in workout.workout_segments() {
+            lines.push(format!("{:?}", segment.extra()));
+            collect(segment.workout_steps(), &mut lines);
+        }
+        lines
+    }
+
+    #[test]
+    fn test_round_trip_exported_workouts() {
+        for name in ["expected_workout.json", "Core-workout-2.json"] {
+            let json_string = fs::read_to_string(format!("src/workouts_json/{}", name)).unwrap();
+            let workout: Workout = serde_json::from_str(&json_string).unwrap();
+
+            let (api_json, losses) = export(&workout).unwrap();
+            assert_eq!(losses, vec![], "{}", name);
+            let (imported, losses) = import(&api_json, &clock()).unwrap();
+            assert_eq!(losses, vec![], "{}", name);
+
+            assert_eq!(meaning(&imported), meaning(&workout), "{}", name);
+            assert_eq!(imported.updated_date(), workout.updated_date());
+            assert_eq!(export(&imported).unwrap().0, api_json, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_schema() {
+        let mut workout = builder::workout(Sport::Running)
+            .name("Threshold")
+            .warmup(10.mins(), AnyStroke)
+            .repeat(3, |r| {
+                r.main(1.km(), Free)
+                    .target(StepTarget::Pace { slowest_secs_per_km: 250.0, fastest_secs_per_km: 240.0 })
+                    .rest(90.s())
+            })
+            .build(&clock())
+            .unwrap();
+        workout.set_workout_provider(Some("Coach app".to_string()), Some("w-17".to_string()));
+
+        let (json, losses) = export(&workout).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(messages, vec!["workoutSegments[0].workoutSteps[1].workoutSteps[0]: distance shown in kilometers sent in meters"]);
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["sport"], "RUNNING");
+        assert_eq!(json["workoutProvider"], "Coach app");
+        assert!(json["poolLength"].is_null());
+        let repeat = &json["steps"][1];
+        assert_eq!((&repeat["type"], &repeat["repeatType"], &repeat["repeatValue"]), (&"WorkoutRepeatStep".into(), &"REPEAT_UNTIL_STEPS_CMPLT".into(), &3.0.into()));
+        let interval = &repeat["steps"][0];
+        assert_eq!((&interval["intensity"], &interval["durationType"], &interval["durationValue"]), (&"ACTIVE".into(), &"DISTANCE".into(), &1000.0.into()));
+        assert_eq!(interval["targetType"], "PACE");
+        assert_eq!(interval["targetValueLow"].as_f64().map(|speed| (speed * 1000.0).round()), Some(4000.0));
+        assert_eq!(repeat["steps"][1]["intensity"], "REST");
+    }
+
+    #[test]
+    fn test_import_partner_workout() {
+        let json = r#"{
+            "workoutName": "Sweet spot",
+            "description": "Pushed from a partner app",
+            "sport": "CYCLING",
+            "steps": [
+                {"type": "WorkoutStep", "stepOrder": 1, "intensity": "WARMUP", "durationType": "TIME", "durationValue": 600,
+                 "targetType": "POWER", "targetValue": 2},
+                {"type": "WorkoutRepeatStep", "stepOrder": 2, "repeatType": "REPEAT_UNTIL_STEPS_CMPLT", "repeatValue": 2,
+                 "steps": [
+                    {"type": "WorkoutStep", "stepOrder": 3, "intensity": "INTERVAL", "durationType": "TIME", "durationValue": 720,
+                     "targetType": "POWER", "targetValueLow": 220, "targetValueHigh": 235,
+                     "secondaryTargetType": "CADENCE", "secondaryTargetValueLow": 85, "secondaryTargetValueHigh": 95},
+                    {"type": "WorkoutStep", "stepOrder": 4, "intensity": "RECOVERY", "durationType": "HR_LESS_THAN", "durationValue": 120,
+                     "targetType": "HEART_RATE", "targetValueLow": 80, "targetValueHigh": 90, "targetValueType": "PERCENT"}
+                 ]},
+                {"type": "WorkoutStep", "stepOrder": 5, "intensity": "COOLDOWN", "durationType": "OPEN", "targetType": "GRADE"}
+            ]
+        }"#;
+
+        let (workout, losses) = import(json, &clock()).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "steps[1].steps[1]: target HEART_RATE as a percentage of threshold left out",
+                "steps[2]: target GRADE left out",
+            ]
+        );
+        assert_eq!(workout.validate(), vec![]);
+        assert_eq!(workout.created_date(), clock().now());
+
+        let steps = workout.workout_segments()[0].workout_steps();
+        let WorkoutStep::Single(warmup) = &steps[0] else { panic!() };
+        assert_eq!((warmup.step_id(), warmup.target()), (FIRST_STEP_ID, Some(StepTarget::PowerZone(2))));
+        let WorkoutStep::Repeat(repeat) = &steps[1] else { panic!() };
+        assert_eq!((repeat.number_of_iterations(), repeat.child_step_id()), (2, 1));
+        let WorkoutStep::Single(interval) = &repeat.workout_steps()[0] else { panic!() };
+        assert_eq!(interval.target(), Some(StepTarget::Power { min_watts: 220, max_watts: 235 }));
+        let cadence = interval.secondary_target_fields();
+        assert_eq!((cadence.target, cadence.value_one, cadence.value_two), (Some(Target::Cadence), Some(85.0), Some(95.0)));
+        let WorkoutStep::Single(recovery) = &repeat.workout_steps()[1] else { panic!() };
+        assert_eq!(recovery.step_type().step_type_key, Step::Recovery);
+        assert_eq!(recovery.step_type().step_type_id(), 4);
+        assert_eq!(recovery.end_condition_compare(), Some(EndConditionCompare::LessThan));
+        assert_eq!(recovery.target(), Some(StepTarget::NoTarget));
+
+        let json: serde_json::Value = serde_json::from_str(&export(&workout).unwrap().0).unwrap();
+        assert_eq!((&json["steps"][1]["steps"][0]["intensity"], &json["steps"][1]["steps"][1]["intensity"]), (&"INTERVAL".into(), &"RECOVERY".into()));
+    }
+
+    #[test]
+    fn test_out_of_range_values() {
+        let json = r#"{
+            "workoutName": "Odd", "sport": "CYCLING",
+            "steps": [
+                {"type": "WorkoutRepeatStep", "stepOrder": 300, "repeatType": "REPEAT_UNTIL_STEPS_CMPLT", "repeatValue": 300,
+                 "steps": [
+                    {"type": "WorkoutStep", "stepOrder": 301, "intensity": "ACTIVE", "durationType": "TIME", "durationValue": 60,
+                     "targetType": "POWER", "targetValue": 0},
+                    {"type": "WorkoutStep", "stepOrder": 302, "intensity": "ACTIVE", "durationType": "TIME", "durationValue": 60,
+                     "targetType": "HEART_RATE", "targetValue": 1000}
+                 ]}
+            ]
+        }"#;
+        let (workout, losses) = import(json, &clock()).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "steps[0]: repeatValue 300 read as 255",
+                "steps[0].steps[0]: target POWER zone 0 left out",
+                "steps[0].steps[1]: target HEART_RATE zone 1000 left out",
+            ]
+        );
+        let WorkoutStep::Repeat(repeat) = &workout.workout_segments()[0].workout_steps()[0] else { panic!() };
+        assert_eq!((repeat.step_order(), repeat.number_of_iterations()), (1, 255));
+    }
+
+    #[test]
+    fn test_unknown_fields_are_reported() {
+        let json_string = fs::read_to_string("src/workouts_json/expected_workout.json").unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+        value["coachNotes"] = 12.into();
+        value["workoutSegments"][0]["workoutSteps"][0]["stepAudioNote"] = "go".into();
+        let workout: Workout = serde_json::from_value(value).unwrap();
+
+        let (_, losses) = export(&workout).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "workout: unknown field `coachNotes` left out",
+                "workoutSegments[0].workoutSteps[0]: unknown field `stepAudioNote` left out",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multi_sport_is_refused() {
+        let json_string = fs::read_to_string("src/workouts_json/expected_workout.json").unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+        let segment = value["workoutSegments"][0].clone();
+        value["workoutSegments"].as_array_mut().unwrap().push(segment);
+        let workout: Workout = serde_json::from_value(value).unwrap();
+
+        let error = export(&workout).err().unwrap();
+        assert_eq!(error, TrainingApiError::MultipleSegments(2));
+        assert_eq!(error.to_string(), "2 segments, a Training API workout has one step list");
+    }
+}
@@ -1,6 +1,7 @@
+use crate::garmin::workout_steps::preferred_end_condition_unit::{self, PreferredEndConditionUnit};
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct Unit{
     pub(crate) unit_id: Option<u8>,
@@ -9,13 +10,52 @@ pub struct Unit{
 }
 
 
-impl Default for Unit {
-    fn default() -> Self {
+impl Unit {
+    /// Pool length in meters, `None` if the unit is missing or not a distance unit
+    pub fn to_meters(&self, value: f32) -> Option<f32> {
+        self.factor.map(|factor| value * factor / 100.0)
+    }
+
+    pub fn distance_unit(&self) -> Option<preferred_end_condition_unit::Unit> {
+        let unit_key = self.unit_key.as_deref()?;
+        preferred_end_condition_unit::Unit::ALL
+            .into_iter()
+            .find(|unit| unit.key() == unit_key)
+    }
+}
+
+impl From<preferred_end_condition_unit::Unit> for Unit {
+    fn from(unit: preferred_end_condition_unit::Unit) -> Self {
+        let unit = PreferredEndConditionUnit::new(unit);
         Unit{
-            unit_id: Some(1),
-            unit_key: Some("meter".to_string()),
-            factor: Some(100.0),
+            unit_id: Some(unit.unit_id()),
+            unit_key: Some(unit.unit().key().to_string()),
+            factor: Some(unit.factor()),
         }
     }
+}
+
+impl Default for Unit {
+    fn default() -> Self {
+        Unit::from(preferred_end_condition_unit::Unit::Meter)
+    }
+
+}
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yard_pool_unit() {
+        let unit = Unit::from(preferred_end_condition_unit::Unit::Yard);
+        assert_eq!(serde_json::to_string(&unit).unwrap(), r#"{"unitId":230,"unitKey":"yard","factor":91.44}"#);
+        assert_eq!(unit.distance_unit(), Some(preferred_end_condition_unit::Unit::Yard));
+        assert!((unit.to_meters(25.0).unwrap() - 22.86).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_default_is_meter() {
+        assert_eq!(serde_json::to_string(&Unit::default()).unwrap(), r#"{"unitId":1,"unitKey":"meter","factor":100.0}"#);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,360 @@
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::Condition;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::WorkoutStep;
+use std::fmt;
+
+/// Pool lengths Garmin accepts, in meters
+pub const MIN_POOL_LENGTH: f32 = 13.0;
+pub const MAX_POOL_LENGTH: f32 = 200.0;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Severity {
+    /// Garmin Connect rejects the workout or the watch misbehaves
+    Error,
+    /// The workout loads, but probably not as intended
+    Warning,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Issue {
+    StepOrderGap { expected: u8, found: u8 },
+    ChildStepIdMismatch { expected: Option<u8>, found: Option<u8> },
+    DuplicateChildStepId(u8),
+    RestStepWithStroke,
+    MissingEndConditionCompare,
+    DistanceNotMultipleOfPoolLength { distance: f32, pool_length: f32 },
+    PoolLengthOutOfRange(f32),
+    /// Step orders are numbered 1 to 255 in each segment
+    TooManySteps(usize),
+    /// Child step ids are numbered 1 to 255 in the whole workout
+    TooManyRepeatGroups(usize),
+    /// A builder modifier like `note` that came after a repeat group or before any step
+    ModifierWithoutStep(String),
+    PoolLengthNotDistance,
+    UnknownData(String),
+    /// An id no Garmin Connect export has confirmed yet, e.g. `("stroke", "fly", 5)`
+    UnconfirmedId(&'static str, String, u8),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// Location of the offending step, e.g. `workoutSegments[0].workoutSteps[2].workoutSteps[1]`
+    pub path: String,
+    pub issue: Issue,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::StepOrderGap { expected, found } => {
+                write!(f, "step order {} found where {} was expected", found, expected)
+            }
+            Issue::ChildStepIdMismatch { expected, found } => {
+                write!(f, "child step id {:?} does not match the enclosing repeat group ({:?})", found, expected)
+            }
+            Issue::DuplicateChildStepId(id) => write!(f, "child step id {} is used by more than one repeat group", id),
+            Issue::RestStepWithStroke => write!(f, "rest step has a stroke type"),
+            Issue::MissingEndConditionCompare => write!(f, "end condition needs a comparison (gt or lt)"),
+            Issue::DistanceNotMultipleOfPoolLength { distance, pool_length } => {
+                write!(f, "distance {}m is not a multiple of the {}m pool length", distance, pool_length)
+            }
+            Issue::PoolLengthOutOfRange(pool_length) => write!(
+                f,
+                "pool length {}m is outside {}-{}m",
+                pool_length, MIN_POOL_LENGTH, MAX_POOL_LENGTH
+            ),
+            Issue::TooManySteps(count) => write!(f, "{} steps, at most {} fit in a segment", count, u8::MAX),
+            Issue::TooManyRepeatGroups(count) => write!(f, "{} repeat groups, at most {} fit in a workout", count, u8::MAX),
+            Issue::ModifierWithoutStep(modifier) => write!(f, "{} must follow a single step", modifier),
+            Issue::PoolLengthNotDistance => write!(f, "pool length must be a distance"),
+            Issue::UnknownData(description) => write!(f, "{}", description),
+            Issue::UnconfirmedId(kind, key, id) => {
+                write!(f, "{} `{}` is sent with id {}, which no Garmin Connect export has confirmed", kind, key, id)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{}: {}: {}", severity, self.path, self.issue)
+    }
+}
+
+pub fn validate(workout: &Workout) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        diagnostics: Vec::new(),
+        pool_length: None,
+        child_step_ids: Vec::new(),
+        next_step_order: 1,
+    };
+
+    if workout.sport_type().sport() == Sport::Swimming {
+        // A missing unit is treated as meters, like Garmin does for older workouts
+        let pool_length = workout
+            .pool_length_unit()
+            .to_meters(workout.pool_length())
+            .unwrap_or(workout.pool_length());
+        if !(MIN_POOL_LENGTH..=MAX_POOL_LENGTH).contains(&pool_length) {
+            validator.push(Severity::Error, "workout", Issue::PoolLengthOutOfRange(pool_length));
+        } else {
+            validator.pool_length = Some(pool_length);
+        }
+    }
+
+    let mut repeat_groups = 0;
+    for (i, segment) in workout.workout_segments().iter().enumerate() {
+        validator.next_step_order = 1;
+        let path = format!("workoutSegments[{}]", i);
+        let (steps, repeats) = count(segment.workout_steps());
+        if steps > u8::MAX as usize {
+            validator.push(Severity::Error, &path, Issue::TooManySteps(steps));
+        }
+        repeat_groups += repeats;
+        validator.steps(segment.workout_steps(), None, &path);
+    }
+    if repeat_groups > u8::MAX as usize {
+        validator.push(Severity::Error, "workout", Issue::TooManyRepeatGroups(repeat_groups));
+    }
+
+    for warning in workout.unknown_data_warnings() {
+        validator.push(Severity::Warning, &warning.path, Issue::UnknownData(warning.message));
+    }
+
+    validator.diagnostics
+}
+
+/// Steps and repeat groups, nested ones included
+fn count(steps: &[WorkoutStep]) -> (usize, usize) {
+    steps.iter().fold((0, 0), |(steps, repeats), step| match step {
+        WorkoutStep::Single(_) => (steps + 1, repeats),
+        WorkoutStep::Repeat(repeat) => {
+            let (nested_steps, nested_repeats) = count(repeat.workout_steps());
+            (steps + 1 + nested_steps, repeats + 1 + nested_repeats)
+        }
+    })
+}
+
+struct Validator {
+    diagnostics: Vec<Diagnostic>,
+    pool_length: Option<f32>,
+    child_step_ids: Vec<u8>,
+    next_step_order: u8,
+}
+
+impl Validator {
+    fn push(&mut self, severity: Severity, path: &str, issue: Issue) {
+        self.diagnostics.push(Diagnostic { severity, path: path.to_string(), issue });
+    }
+
+    fn steps(&mut self, steps: &[WorkoutStep], child_step_id: Option<u8>, parent_path: &str) {
+        for (i, step) in steps.iter().enumerate() {
+            let path = format!("{}.workoutSteps[{}]", parent_path, i);
+            match step {
+                WorkoutStep::Single(single) => self.single(single, child_step_id, &path),
+                WorkoutStep::Repeat(repeat) => self.repeat(repeat, &path),
+            }
+        }
+    }
+
+    /// Orders past 255 are reported once as too many steps, not as a gap at every step
+    fn step_order(&mut self, found: u8, path: &str) {
+        if found != self.next_step_order {
+            let expected = self.next_step_order;
+            self.push(Severity::Error, path, Issue::StepOrderGap { expected, found });
+        }
+        self.next_step_order = found.saturating_add(1);
+    }
+
+    fn repeat(&mut self, repeat: &RepeatGroupDTO, path: &str) {
+        self.step_order(repeat.step_order(), path);
+
+        let child_step_id = repeat.child_step_id();
+        // Child step ids past 255 are reported once as too many repeat groups
+        if child_step_id != u8::MAX && self.child_step_ids.contains(&child_step_id) {
+            self.push(Severity::Error, path, Issue::DuplicateChildStepId(child_step_id));
+        }
+        self.child_step_ids.push(child_step_id);
+
+        self.steps(repeat.workout_steps(), Some(child_step_id), path);
+    }
+
+    fn single(&mut self, step: &ExecutableStepDTO, child_step_id: Option<u8>, path: &str) {
+        self.step_order(step.step_order(), path);
+
+        let found = step.child_step_id.get();
+        if found != child_step_id {
+            self.push(Severity::Error, path, Issue::ChildStepIdMismatch { expected: child_step_id, found });
+        }
+
+        if step.step_type().step_type_key == Step::Rest && step.stroke_type().stroke_type_key.is_some() {
+            self.push(Severity::Warning, path, Issue::RestStepWithStroke);
+        }
+
+        let stroke_type = step.stroke_type();
+        if let Some(stroke) = &stroke_type.stroke_type_key
+            && !stroke_type.id_is_confirmed()
+        {
+            let key = serde_json::to_value(stroke).ok().and_then(|key| key.as_str().map(str::to_string)).unwrap_or_default();
+            self.push(Severity::Warning, path, Issue::UnconfirmedId("stroke", key, stroke_type.stroke_type_id()));
+        }
+        let equipment_type = step.equipment_type();
+        if let Some(equipment) = &equipment_type.equipment_type_key
+            && !equipment_type.id_is_confirmed()
+        {
+            let key = serde_json::to_value(equipment).ok().and_then(|key| key.as_str().map(str::to_string)).unwrap_or_default();
+            self.push(Severity::Warning, path, Issue::UnconfirmedId("equipment", key, equipment_type.equipment_type_id()));
+        }
+
+        if !step.has_valid_end_condition() {
+            self.push(Severity::Error, path, Issue::MissingEndConditionCompare);
+        }
+
+        if let Some(pool_length) = self.pool_length {
+            let distance = step.end_condition_value();
+            let lengths = distance / pool_length;
+            if step.end_condition().condition_type_key == Condition::Distance
+                && (lengths - lengths.round()).abs() > 1e-3
+            {
+                self.push(
+                    Severity::Warning,
+                    path,
+                    Issue::DistanceNotMultipleOfPoolLength { distance, pool_length },
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn load(name: &str) -> serde_json::Value {
+        let json_string = fs::read_to_string(format!("src/workouts_json/{}", name)).unwrap();
+        serde_json::from_str(&json_string).unwrap()
+    }
+
+    fn issues(json: serde_json::Value) -> Vec<(Severity, String, Issue)> {
+        let workout: Workout = serde_json::from_value(json).unwrap();
+        validate(&workout)
+            .into_iter()
+            .map(|d| (d.severity, d.path, d.issue))
+            .collect()
+    }
+
+    #[test]
+    fn test_exported_workouts_are_valid() {
+        for entry in fs::read_dir("src/workouts_json").unwrap() {
+            let path = entry.unwrap().path();
+            let workout: Workout = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(validate(&workout), vec![], "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_step_order_gap() {
+        let mut json = load("expected_workout.json");
+        json["workoutSegments"][0]["workoutSteps"][1]["stepOrder"] = 5.into();
+
+        assert_eq!(
+            issues(json),
+            vec![
+                (Severity::Error, "workoutSegments[0].workoutSteps[1]".to_string(), Issue::StepOrderGap { expected: 2, found: 5 }),
+                (Severity::Error, "workoutSegments[0].workoutSteps[2]".to_string(), Issue::StepOrderGap { expected: 6, found: 3 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_child_step_id_mismatch() {
+        let mut json = load("expected_workout.json");
+        json["workoutSegments"][0]["workoutSteps"][2]["workoutSteps"][1]["childStepId"] = 2.into();
+
+        assert_eq!(
+            issues(json),
+            vec![(
+                Severity::Error,
+                "workoutSegments[0].workoutSteps[2].workoutSteps[1]".to_string(),
+                Issue::ChildStepIdMismatch { expected: Some(1), found: Some(2) }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_rest_step_with_stroke() {
+        let mut json = load("expected_workout.json");
+        json["workoutSegments"][0]["workoutSteps"][1]["strokeType"] =
+            serde_json::json!({"strokeTypeId": 6, "strokeTypeKey": "free", "displayOrder": 6});
+
+        assert_eq!(
+            issues(json),
+            vec![(Severity::Warning, "workoutSegments[0].workoutSteps[1]".to_string(), Issue::RestStepWithStroke)]
+        );
+    }
+
+    #[test]
+    fn test_distance_not_multiple_of_pool_length() {
+        let mut json = load("expected_workout.json");
+        json["poolLength"] = 33.0.into();
+
+        let result = issues(json);
+        assert_eq!(result.len(), 3);
+        assert_eq!(
+            result[0],
+            (
+                Severity::Warning,
+                "workoutSegments[0].workoutSteps[0]".to_string(),
+                Issue::DistanceNotMultipleOfPoolLength { distance: 400.0, pool_length: 33.0 }
+            )
+        );
+    }
+
+    #[test]
+    fn test_pool_length_out_of_range() {
+        let mut json = load("expected_workout.json");
+        json["poolLength"] = 10.0.into();
+
+        assert_eq!(
+            issues(json),
+            vec![(Severity::Error, "workout".to_string(), Issue::PoolLengthOutOfRange(10.0))]
+        );
+    }
+
+    #[test]
+    fn test_unknown_data_is_a_warning() {
+        let mut json = load("expected_workout.json");
+        json["workoutSegments"][0]["workoutSteps"][0]["newStepField"] = true.into();
+
+        let diagnostics: Vec<String> = {
+            let workout: Workout = serde_json::from_value(json).unwrap();
+            validate(&workout).iter().map(|d| d.to_string()).collect()
+        };
+        assert_eq!(diagnostics, vec!["warning: workoutSegments[0].workoutSteps[0]: unknown field `newStepField`"]);
+    }
+
+    #[test]
+    fn test_unconfirmed_id_is_a_warning() {
+        let mut json = load("expected_workout.json");
+        json["workoutSegments"][0]["workoutSteps"][0]["strokeType"] = serde_json::json!({"strokeTypeKey": "fly"});
+
+        assert_eq!(
+            issues(json),
+            vec![(
+                Severity::Warning,
+                "workoutSegments[0].workoutSteps[0]".to_string(),
+                Issue::UnconfirmedId("stroke", "fly".to_string(), 5)
+            )]
+        );
+    }
+}
@@ -1,8 +1,13 @@
 use crate::garmin::author::Author;
+use crate::garmin::estimate::{self, SwimPace, WorkoutEstimate, ESTIMATE_TYPE};
 use crate::garmin::sport_type::{Sport, SportType};
 use crate::garmin::unit::Unit;
+use crate::garmin::validation::{self, Diagnostic};
 use crate::garmin::workout_segments::WorkoutSegment;
+use crate::garmin::workout_steps::{unknown_fields, StepNumbering, UnknownData};
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -10,14 +15,15 @@ pub struct Workout {
     /*
     Todo:
         - Test complete generation
-        - DateTime representation
      */
     workout_id: u64,
     owner_id: u64,
     workout_name: String,
     description: Option<String>,
-    updated_date: String, // Find datetime representation
-    created_date: String, // Find datetime representation
+    #[serde(with = "crate::garmin::timestamp")]
+    updated_date: NaiveDateTime,
+    #[serde(with = "crate::garmin::timestamp")]
+    created_date: NaiveDateTime,
     sport_type: SportType,
     sub_sport_type: Option<SportType>,
     training_plan_id: Option<u64>,
@@ -31,7 +37,8 @@ pub struct Workout {
     locale: Option<String>,
     workout_provider: Option<String>,
     workout_source_id: Option<String>,
-    upload_timestamp: Option<String>,
+    #[serde(with = "crate::garmin::timestamp::option")]
+    upload_timestamp: Option<NaiveDateTime>,
     atp_plan_id: Option<String>,
     consumer: Option<String>,
     consumer_name: Option<String>,
@@ -39,23 +46,192 @@ pub struct Workout {
     consumer_website_u_r_l: Option<String>,
     workout_name_i18n_key: Option<String>,
     description_i18n_key: Option<String>,
-    avg_training_speed: Option<f32>,
+    avg_training_speed: Option<f64>,
     estimate_type: String,
     estimated_distance_unit: Unit,
     workout_thumbnail_url: Option<String>,
-    is_session_transition_enabled: Option<String>,
-    shared: bool
+    is_session_transition_enabled: Option<bool>,
+    shared: bool,
+    /// Fields Garmin added after this crate was written, kept so they survive a save
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 
 impl Workout {
+    #[allow(clippy::too_many_arguments)]
     pub fn new_swimming_workout(
         workout_id: u64,
         owner_id: u64,
         workout_name: String,
         description: Option<String>,
-        updated_date: String, // Find datetime representation
-        created_date: String, // Find datetime representation
+        updated_date: NaiveDateTime,
+        created_date: NaiveDateTime,
+        workout_segments: Vec<WorkoutSegment>,
+        pool_length: f32,
+        pool_length_unit: Unit,
+    ) -> Self {
+        Self::with_pool(
+            Sport::Swimming,
+            workout_id,
+            owner_id,
+            workout_name,
+            description,
+            updated_date,
+            created_date,
+            workout_segments,
+            pool_length,
+            pool_length_unit,
+        )
+    }
+
+    /// A workout without a pool, for any sport but swimming
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        sport: Sport,
+        workout_id: u64,
+        owner_id: u64,
+        workout_name: String,
+        description: Option<String>,
+        updated_date: NaiveDateTime,
+        created_date: NaiveDateTime,
+        workout_segments: Vec<WorkoutSegment>,
+    ) -> Self {
+        // Garmin sends zero and an empty unit when there's no pool
+        Self::with_pool(
+            sport,
+            workout_id,
+            owner_id,
+            workout_name,
+            description,
+            updated_date,
+            created_date,
+            workout_segments,
+            0.0,
+            Unit{ unit_id: None, unit_key: None, factor: None},
+        )
+    }
+
+    /// Assigns unique step ids starting at `first_step_id`, depth-first step orders and
+    /// repeat group child step ids, replacing whatever the steps were created with
+    pub fn renumber_steps(&mut self, first_step_id: u64) {
+        let mut numbering = StepNumbering::new(first_step_id);
+        for segment in self.workout_segments.iter_mut() {
+            segment.renumber(&mut numbering);
+        }
+    }
+
+    pub fn estimate(&self, pace: SwimPace) -> WorkoutEstimate {
+        estimate::estimate(self, pace)
+    }
+
+    /// Fills the `estimated*` fields of the workout and its segments from the swimmer's pace
+    pub fn fill_estimates(&mut self, pace: SwimPace) -> WorkoutEstimate {
+        let estimate = self.estimate(pace);
+        for (segment, segment_estimate) in self.workout_segments.iter_mut().zip(&estimate.segments) {
+            segment.set_estimate(&segment_estimate.total, pace);
+        }
+        self.estimated_duration_in_secs = estimate.total.duration_in_secs.round() as u32;
+        self.estimated_distance_in_meters = Some(estimate.total.distance_in_meters);
+        self.avg_training_speed = Some(pace.speed() as f64);
+        self.estimate_type = ESTIMATE_TYPE.to_string();
+        estimate
+    }
+
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        validation::validate(self)
+    }
+
+    /// Fields this crate doesn't know, as they were received
+    pub(crate) fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+
+    /// Describes every field and key that was kept without being understood
+    pub fn unknown_data_warnings(&self) -> Vec<UnknownData> {
+        let mut warnings = Vec::new();
+        if let Sport::Unknown(key) = self.sport_type.sport() {
+            warnings.push(UnknownData::new("workout", format!("unknown sport type `{}`", key)));
+        }
+        unknown_fields(&self.extra, "workout", &mut warnings);
+        for (i, segment) in self.workout_segments.iter().enumerate() {
+            segment.unknown_data(&format!("workoutSegments[{}]", i), &mut warnings);
+        }
+        warnings
+    }
+
+    pub fn workout_id(&self) -> u64 {
+        self.workout_id
+    }
+
+    pub fn owner_id(&self) -> u64 {
+        self.owner_id
+    }
+
+    pub fn workout_name(&self) -> &str {
+        &self.workout_name
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn updated_date(&self) -> NaiveDateTime {
+        self.updated_date
+    }
+
+    pub fn created_date(&self) -> NaiveDateTime {
+        self.created_date
+    }
+
+    pub fn sport_type(&self) -> &SportType {
+        &self.sport_type
+    }
+
+    pub fn workout_segments(&self) -> &[WorkoutSegment] {
+        &self.workout_segments
+    }
+
+    pub fn pool_length(&self) -> f32 {
+        self.pool_length
+    }
+
+    pub fn pool_length_unit(&self) -> &Unit {
+        &self.pool_length_unit
+    }
+
+    pub fn estimated_duration_in_secs(&self) -> u32 {
+        self.estimated_duration_in_secs
+    }
+
+    pub fn estimated_distance_in_meters(&self) -> Option<f32> {
+        self.estimated_distance_in_meters
+    }
+
+    pub(crate) fn set_estimated(&mut self, duration_in_secs: u32, distance_in_meters: Option<f32>) {
+        self.estimated_duration_in_secs = duration_in_secs;
+        self.estimated_distance_in_meters = distance_in_meters;
+    }
+
+    /// The partner app a workout was pushed from, and its id for it there
+    pub fn workout_provider(&self) -> (Option<&str>, Option<&str>) {
+        (self.workout_provider.as_deref(), self.workout_source_id.as_deref())
+    }
+
+    pub(crate) fn set_workout_provider(&mut self, provider: Option<String>, source_id: Option<String>) {
+        self.workout_provider = provider;
+        self.workout_source_id = source_id;
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn with_pool(
+        sport: Sport,
+        workout_id: u64,
+        owner_id: u64,
+        workout_name: String,
+        description: Option<String>,
+        updated_date: NaiveDateTime,
+        created_date: NaiveDateTime,
         workout_segments: Vec<WorkoutSegment>,
         pool_length: f32,
         pool_length_unit: Unit,
@@ -67,7 +243,7 @@ impl Workout {
             description,
             updated_date,
             created_date,
-            sport_type: SportType::new(Sport::Swimming),
+            sport_type: SportType::new(sport),
             sub_sport_type: None,
             training_plan_id: None,
             author: Default::default(),
@@ -89,11 +265,12 @@ impl Workout {
             workout_name_i18n_key: None,
             description_i18n_key: None,
             avg_training_speed: None,
-            estimate_type: "TIME_ESTIMATED".to_string(),
+            estimate_type: ESTIMATE_TYPE.to_string(),
             estimated_distance_unit: Unit{ unit_id: None, unit_key: None, factor: None},
             workout_thumbnail_url: None,
             is_session_transition_enabled: None,
             shared: false,
+            extra: Map::new(),
         }
     }
 }
@@ -101,6 +278,8 @@ impl Workout {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::garmin::timestamp;
+    use crate::garmin::workout_steps::preferred_end_condition_unit::Unit as DistanceUnit;
     use std::fs;
 
     #[test]
@@ -114,5 +293,163 @@ mod tests {
         assert_eq!(workout.owner_id, 100441918);
         assert_eq!(workout.pool_length, 25.0);
         assert_eq!(workout.estimated_distance_in_meters, Some(1400.0));
+        assert_eq!(workout.created_date, timestamp::parse("2025-04-06T19:55:57.0").unwrap());
+    }
+
+    #[test]
+    fn test_new_workout() {
+        let workout = Workout::new(
+            Sport::Running,
+            1,
+            100441918,
+            "Intervals".to_string(),
+            None,
+            timestamp::parse("2025-04-06T19:55:57.0").unwrap(),
+            timestamp::parse("2025-04-06T19:55:57.0").unwrap(),
+            vec![WorkoutSegment::with_sport(1, Sport::Running, vec![])],
+        );
+
+        let json = serde_json::to_value(&workout).unwrap();
+        assert_eq!(json["sportType"]["sportTypeKey"], "running");
+        assert_eq!(json["sportType"]["sportTypeId"], 1);
+        assert_eq!(json["workoutSegments"][0]["sportType"]["sportTypeKey"], "running");
+        assert_eq!(json["poolLength"], 0.0);
+        assert!(json["poolLengthUnit"]["unitKey"].is_null());
+    }
+
+    #[test]
+    fn test_yard_pool() {
+        let workout = Workout::new_swimming_workout(
+            1,
+            100441918,
+            "Masters".to_string(),
+            None,
+            timestamp::parse("2025-04-06T19:55:57.0").unwrap(),
+            timestamp::parse("2025-04-06T19:55:57.0").unwrap(),
+            vec![],
+            25.0,
+            Unit::from(DistanceUnit::Yard),
+        );
+
+        let json = serde_json::to_value(&workout).unwrap();
+        assert_eq!(json["poolLength"], 25.0);
+        assert_eq!(json["poolLengthUnit"]["unitKey"], "yard");
+        assert_eq!(json["poolLengthUnit"]["unitId"], 230);
+        assert_eq!(workout.pool_length_unit().distance_unit(), Some(DistanceUnit::Yard));
+    }
+
+    #[test]
+    fn test_keeps_unknown_data() {
+        let json_string = fs::read_to_string("src/workouts_json/Core-workout-2.json").unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+        json["newWorkoutField"] = "kept".into();
+        json["workoutSegments"][0]["newSegmentField"] = 1.into();
+        let step = &mut json["workoutSegments"][0]["workoutSteps"][0]["workoutSteps"][0];
+        step["newStepField"] = true.into();
+        step["strokeType"] = serde_json::json!({"strokeTypeId": 9, "strokeTypeKey": "rimo", "displayOrder": 9});
+        step["equipmentType"] = serde_json::json!({"equipmentTypeId": 7, "equipmentTypeKey": "swim_fins_x", "displayOrder": 6});
+        json["workoutSegments"][0]["sportType"] = serde_json::json!({"sportTypeId": 21, "sportTypeKey": "e_bike", "displayOrder": 18});
+
+        let workout: Workout = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(
+            workout.unknown_data_warnings().iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "workout: unknown field `newWorkoutField`",
+                "workoutSegments[0]: unknown sport type `e_bike`",
+                "workoutSegments[0]: unknown field `newSegmentField`",
+                "workoutSegments[0].workoutSteps[0].workoutSteps[0]: unknown stroke type `rimo`",
+                "workoutSegments[0].workoutSteps[0].workoutSteps[0]: unknown equipment type `swim_fins_x`",
+                "workoutSegments[0].workoutSteps[0].workoutSteps[0]: unknown field `newStepField`",
+            ]
+        );
+
+        assert_eq!(serde_json::to_value(&workout).unwrap(), json);
+    }
+
+    #[test]
+    fn test_renumber_matches_garmin_connect() {
+        for name in ["expected_workout.json", "Core-workout-2.json"] {
+            let json_string = fs::read_to_string(format!("src/workouts_json/{}", name)).unwrap();
+            let expected: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+
+            let mut scrambled = expected.clone();
+            for step in scrambled["workoutSegments"][0]["workoutSteps"].as_array_mut().unwrap() {
+                step["stepId"] = 1.into();
+                step["stepOrder"] = 99.into();
+                if step["type"] == "RepeatGroupDTO" {
+                    step["childStepId"] = 42.into();
+                }
+            }
+
+            let mut workout: Workout = serde_json::from_value(scrambled).unwrap();
+            let first_step_id = expected["workoutSegments"][0]["workoutSteps"][0]["stepId"].as_u64().unwrap();
+            workout.renumber_steps(first_step_id);
+
+            assert_eq!(serde_json::to_value(&workout).unwrap(), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_renumber_nested_repeat() {
+        let json_string = fs::read_to_string("src/workouts_json/expected_workout.json").unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&json_string).unwrap();
+        // Wrap the 8x100 repeat in a 3x repeat
+        let steps = json["workoutSegments"][0]["workoutSteps"].as_array_mut().unwrap();
+        let mut outer = steps[2].clone();
+        outer["workoutSteps"] = serde_json::Value::Array(vec![steps[2].clone(), steps[3].clone()]);
+        steps.splice(2..4, [outer]);
+
+        let mut workout: Workout = serde_json::from_value(json).unwrap();
+        workout.renumber_steps(100);
+        let json = serde_json::to_value(&workout).unwrap();
+
+        let outer = &json["workoutSegments"][0]["workoutSteps"][2];
+        assert_eq!((outer["stepId"].as_u64(), outer["stepOrder"].as_u64(), outer["childStepId"].as_u64()), (Some(102), Some(3), Some(1)));
+        let inner = &outer["workoutSteps"][0];
+        assert_eq!((inner["stepOrder"].as_u64(), inner["childStepId"].as_u64()), (Some(4), Some(2)));
+        assert_eq!((inner["workoutSteps"][0]["stepOrder"].as_u64(), inner["workoutSteps"][0]["childStepId"].as_u64()), (Some(5), Some(2)));
+        assert_eq!((inner["workoutSteps"][1]["stepOrder"].as_u64(), inner["workoutSteps"][1]["childStepId"].as_u64()), (Some(6), Some(2)));
+        assert_eq!((outer["workoutSteps"][1]["stepOrder"].as_u64(), outer["workoutSteps"][1]["childStepId"].as_u64()), (Some(7), Some(1)));
+        let last = &json["workoutSegments"][0]["workoutSteps"][3];
+        assert_eq!((last["stepId"].as_u64(), last["stepOrder"].as_u64(), last["childStepId"].as_u64()), (Some(107), Some(8), None));
+
+        assert_eq!(workout.validate(), vec![]);
+    }
+
+    #[test]
+    fn test_fill_estimates() {
+        let json_string = fs::read_to_string("src/workouts_json/Core-workout-2.json").unwrap();
+        let mut workout: Workout = serde_json::from_str(&json_string).unwrap();
+        workout.fill_estimates(SwimPace::default());
+
+        let json = serde_json::to_value(&workout).unwrap();
+        assert_eq!(json["estimatedDistanceInMeters"], 1300.0);
+        assert_eq!(json["avgTrainingSpeed"].as_f64().map(|s| (s * 1e4).round()), Some(8333.0));
+        assert_eq!(json["estimateType"], "TIME_ESTIMATED");
+        assert!(json["estimatedDurationInSecs"].as_u64().unwrap() > 1300 * 120 / 100);
+        assert_eq!(json["workoutSegments"][0]["estimatedDistanceInMeters"], 1300.0);
+        assert_eq!(
+            json["workoutSegments"][0]["estimatedDurationInSecs"].as_f64().unwrap().round() as u64,
+            json["estimatedDurationInSecs"].as_u64().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_round_trip_exported_workouts() {
+        let strip = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>();
+
+        for entry in fs::read_dir("src/workouts_json").expect("Missing workouts_json directory") {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let json_string = fs::read_to_string(&path).unwrap();
+            let workout: Workout = serde_json::from_str(&json_string)
+                .unwrap_or_else(|e| panic!("Failed to deserialize {}: {}", path.display(), e));
+            let serialized = serde_json::to_string(&workout).unwrap();
+
+            assert_eq!(strip(&serialized), strip(&json_string), "Round trip differs for {}", path.display());
+        }
     }
 }
\ No newline at end of file
@@ -1,6 +1,8 @@
+use crate::garmin::estimate::{Estimate, SwimPace, ESTIMATE_TYPE};
 use crate::garmin::sport_type::{Sport, SportType};
-use crate::garmin::workout_steps::WorkoutStep;
+use crate::garmin::workout_steps::{unknown_fields, StepNumbering, UnknownData, WorkoutStep};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -13,26 +15,66 @@ pub struct WorkoutSegment {
     estimated_duration_in_secs: Option<f32>,
     estimated_distance_in_meters: Option<f32>,
     estimated_distance_unit: Option<String>,
-    estimated_type: Option<String>,
+    estimate_type: Option<String>,
     description: Option<String>,
-    workout_steps: Vec<WorkoutStep>
+    workout_steps: Vec<WorkoutStep>,
+    #[serde(flatten)]
+    extra: Map<String, Value>,
 }
 
 impl WorkoutSegment {
     pub fn new(workout_steps: Vec<WorkoutStep>) -> WorkoutSegment {
+        Self::with_sport(1, Sport::Swimming, workout_steps)
+    }
+
+    /// Multi-sport workouts carry one segment per leg, each with its own sport and order
+    pub fn with_sport(segment_order: u8, sport: Sport, workout_steps: Vec<WorkoutStep>) -> WorkoutSegment {
         WorkoutSegment{
-            segment_order: 1,
-            sport_type: SportType::new(Sport::Swimming),
+            segment_order,
+            sport_type: SportType::new(sport),
             pool_length_unit: None,
             pool_length: None,
             avg_training_speed: None,
             estimated_duration_in_secs: None,
             estimated_distance_in_meters: None,
             estimated_distance_unit: None,
-            estimated_type: None,
+            estimate_type: None,
             description: None,
             workout_steps,
+            extra: Map::new(),
+        }
+
+    }
+
+    pub fn workout_steps(&self) -> &[WorkoutStep] {
+        &self.workout_steps
+    }
+
+    pub(crate) fn set_estimate(&mut self, estimate: &Estimate, pace: SwimPace) {
+        self.avg_training_speed = Some(pace.speed());
+        self.estimated_duration_in_secs = Some(estimate.duration_in_secs);
+        self.estimated_distance_in_meters = Some(estimate.distance_in_meters);
+        self.estimate_type = Some(ESTIMATE_TYPE.to_string());
+    }
+
+    pub(crate) fn renumber(&mut self, numbering: &mut StepNumbering) {
+        numbering.start_segment();
+        for step in self.workout_steps.iter_mut() {
+            step.renumber(numbering, None);
         }
+    }
+
+    pub(crate) fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
 
+    pub(crate) fn unknown_data(&self, path: &str, warnings: &mut Vec<UnknownData>) {
+        if let Sport::Unknown(key) = self.sport_type.sport() {
+            warnings.push(UnknownData::new(path, format!("unknown sport type `{}`", key)));
+        }
+        unknown_fields(&self.extra, path, warnings);
+        for (i, step) in self.workout_steps.iter().enumerate() {
+            step.unknown_data(&format!("{}.workoutSteps[{}]", path, i), warnings);
+        }
     }
-}
\ No newline at end of file
+}
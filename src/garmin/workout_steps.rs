@@ -1,21 +1,122 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+use std::fmt;
 
 pub mod step_type;
 pub mod end_condition;
 pub mod target_type;
-mod preferred_end_condition_unit;
+pub mod preferred_end_condition_unit;
 pub mod stroke_type;
+pub mod equipment_type;
 pub mod executable_step_dto;
 pub mod repeat_group_dto;
 
 use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
 use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
 
-#[derive(Serialize, Deserialize, PartialEq)]
+#[derive(Deserialize, PartialEq)]
 #[serde(tag = "type")]
+#[allow(clippy::large_enum_variant)]
 pub enum WorkoutStep {
     #[serde(rename = "ExecutableStepDTO")]
     Single(ExecutableStepDTO),
     #[serde(rename = "RepeatGroupDTO")]
     Repeat(RepeatGroupDTO),
-}
\ No newline at end of file
+}
+
+/// Hands out step ids, step orders and repeat group child step ids the way Garmin Connect does:
+/// depth first, with a repeat group numbered before the steps it contains. Orders and child step
+/// ids stop at 255, validation reports workouts that need more.
+pub(crate) struct StepNumbering {
+    next_step_id: u64,
+    next_step_order: u8,
+    next_child_step_id: u8,
+}
+
+impl StepNumbering {
+    pub(crate) fn new(first_step_id: u64) -> Self {
+        StepNumbering { next_step_id: first_step_id, next_step_order: 1, next_child_step_id: 1 }
+    }
+
+    /// Step order restarts for every segment, ids keep counting
+    pub(crate) fn start_segment(&mut self) {
+        self.next_step_order = 1;
+    }
+
+    pub(crate) fn next_step(&mut self) -> (u64, u8) {
+        let numbers = (self.next_step_id, self.next_step_order);
+        self.next_step_id += 1;
+        self.next_step_order = self.next_step_order.saturating_add(1);
+        numbers
+    }
+
+    pub(crate) fn next_child_step_id(&mut self) -> u8 {
+        let child_step_id = self.next_child_step_id;
+        self.next_child_step_id = self.next_child_step_id.saturating_add(1);
+        child_step_id
+    }
+}
+
+impl WorkoutStep {
+    pub(crate) fn renumber(&mut self, numbering: &mut StepNumbering, child_step_id: Option<u8>) {
+        match self {
+            WorkoutStep::Single(step) => step.renumber(numbering, child_step_id),
+            WorkoutStep::Repeat(step) => step.renumber(numbering),
+        }
+    }
+
+    pub(crate) fn unknown_data(&self, path: &str, warnings: &mut Vec<UnknownData>) {
+        match self {
+            WorkoutStep::Single(step) => step.unknown_data(path, warnings),
+            WorkoutStep::Repeat(step) => step.unknown_data(path, warnings),
+        }
+    }
+}
+
+impl Serialize for WorkoutStep {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // The step DTOs write their own "type" tag
+        match self {
+            WorkoutStep::Single(step) => step.serialize(serializer),
+            WorkoutStep::Repeat(step) => step.serialize(serializer),
+        }
+    }
+}
+
+/// Unknown fields of a step, without the "type" tag the step DTOs write themselves
+pub(crate) fn deserialize_extra<'de, D>(deserializer: D) -> Result<Map<String, Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut extra = Map::deserialize(deserializer)?;
+    extra.remove("type");
+    Ok(extra)
+}
+
+/// A field or key this crate doesn't know, kept as it was received
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnknownData {
+    pub path: String,
+    pub message: String,
+}
+
+impl UnknownData {
+    pub(crate) fn new(path: &str, message: String) -> Self {
+        UnknownData { path: path.to_string(), message }
+    }
+}
+
+impl fmt::Display for UnknownData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+pub(crate) fn unknown_fields(extra: &Map<String, Value>, path: &str, warnings: &mut Vec<UnknownData>) {
+    for key in extra.keys() {
+        warnings.push(UnknownData::new(path, format!("unknown field `{}`", key)));
+    }
+}
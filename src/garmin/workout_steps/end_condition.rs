@@ -1,45 +1,99 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum Condition{
-    // Todo: Find all cases
     #[serde(rename = "lap.button")]
     LapButton,
     Time,
     Distance,
+    Calories,
+    #[serde(rename = "heart.rate")]
+    HeartRate,
+    Power,
     Iterations,
     #[serde(rename = "fixed.rest")]
     FixedRest,
+    #[serde(rename = "fixed.repetition")]
+    FixedRepetition,
+    Reps,
+    /// Key added by Garmin after this crate was written
+    #[serde(untagged)]
+    Unknown(String),
+}
 
+/// Direction of an `endConditionValue` threshold, e.g. "until heart rate is below 130"
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone, Copy)]
+pub enum EndConditionCompare {
+    #[serde(rename = "gt")]
+    GreaterThan,
+    #[serde(rename = "lt")]
+    LessThan,
 }
 
-#[derive(Deserialize, PartialEq)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EndCondition {
     pub condition_type_key: Condition,
     pub displayable: bool,
+    // Only used for unknown keys, known keys derive them
+    #[serde(default, rename = "conditionTypeId")]
+    received_id: Option<u8>,
+    #[serde(default, rename = "displayOrder")]
+    received_display_order: Option<u8>,
 }
 
 
 impl EndCondition {
 
     pub fn new(condition: Condition) -> Self {
-        EndCondition{condition_type_key: condition, displayable: true}
+        Self::with_displayable(condition, true)
+    }
+
+    pub fn with_displayable(condition: Condition, displayable: bool) -> Self {
+        EndCondition{
+            condition_type_key: condition,
+            displayable,
+            received_id: None,
+            received_display_order: None,
+        }
     }
     pub fn condition_type_id(&self) -> u8 {
         match self.condition_type_key {
             Condition::LapButton => 1,
             Condition::Time => 2,
             Condition::Distance => 3,
+            Condition::Calories => 4,
+            Condition::HeartRate => 5,
+            Condition::Power => 6,
             Condition::Iterations => 7,
             Condition::FixedRest => 8,
+            Condition::FixedRepetition => 9,
+            Condition::Reps => 10,
+            Condition::Unknown(_) => self.received_id.unwrap_or_default(),
         }
     }
 
+    /// Threshold conditions only make sense with a direction, the others ignore it
+    pub fn requires_compare(&self) -> bool {
+        matches!(self.condition_type_key, Condition::HeartRate | Condition::Power)
+    }
+
     pub fn display_order(&self) -> u8 {
-        self.condition_type_id()
+        match self.condition_type_key {
+            Condition::Unknown(_) => self.received_display_order.unwrap_or_default(),
+            _ => self.condition_type_id(),
+        }
+    }
+}
+
+impl PartialEq for EndCondition {
+    fn eq(&self, other: &Self) -> bool {
+        self.condition_type_key == other.condition_type_key
+            && self.displayable == other.displayable
+            && self.condition_type_id() == other.condition_type_id()
+            && self.display_order() == other.display_order()
     }
 }
 
@@ -77,17 +131,50 @@ mod tests {
         assert_eq!(json.condition_type_id(), 3);
         assert_eq!(json.condition_type_key, Condition::Distance);
         assert_eq!(json.display_order(), 3);
-        assert_eq!(json.displayable, true);
+        assert!(json.displayable);
     }
 
     #[test]
     fn test_serialize_step() {
-        let step = EndCondition {
-            condition_type_key: Condition::LapButton,
-            displayable: true,
-        };
+        let step = EndCondition::new(Condition::LapButton);
         let json_str = serde_json::to_string(&step).unwrap();
         let expected = r#"{"conditionTypeId":1,"conditionTypeKey":"lap.button","displayOrder":1,"displayable":true}"#;
         assert_eq!(json_str, expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_condition_catalogue() {
+        let cases = [
+            (Condition::Calories, r#"{"conditionTypeId":4,"conditionTypeKey":"calories","displayOrder":4,"displayable":true}"#),
+            (Condition::HeartRate, r#"{"conditionTypeId":5,"conditionTypeKey":"heart.rate","displayOrder":5,"displayable":true}"#),
+            (Condition::Power, r#"{"conditionTypeId":6,"conditionTypeKey":"power","displayOrder":6,"displayable":true}"#),
+            (Condition::FixedRepetition, r#"{"conditionTypeId":9,"conditionTypeKey":"fixed.repetition","displayOrder":9,"displayable":true}"#),
+            (Condition::Reps, r#"{"conditionTypeId":10,"conditionTypeKey":"reps","displayOrder":10,"displayable":true}"#),
+        ];
+
+        for (condition, expected_json) in cases {
+            assert_eq!(serde_json::to_string(&EndCondition::new(condition.clone())).unwrap(), expected_json);
+
+            let result: EndCondition = serde_json::from_str(expected_json).unwrap();
+            assert_eq!(result.condition_type_key, condition);
+        }
+    }
+
+    #[test]
+    fn test_unknown_condition() {
+        let json_str = r#"{"conditionTypeId":11,"conditionTypeKey":"training.peaks.tss","displayOrder":11,"displayable":true}"#;
+        let json: EndCondition = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.condition_type_key, Condition::Unknown("training.peaks.tss".to_string()));
+        assert_eq!(serde_json::to_string(&json).unwrap(), json_str);
+    }
+
+    #[test]
+    fn test_end_condition_compare() {
+        assert_eq!(serde_json::to_string(&EndConditionCompare::LessThan).unwrap(), r#""lt""#);
+        let compare: Option<EndConditionCompare> = serde_json::from_str(r#""gt""#).unwrap();
+        assert_eq!(compare, Some(EndConditionCompare::GreaterThan));
+
+        assert!(EndCondition::new(Condition::HeartRate).requires_compare());
+        assert!(!EndCondition::new(Condition::FixedRest).requires_compare());
+    }
+}
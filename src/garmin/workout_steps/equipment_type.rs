@@ -0,0 +1,133 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum Equipment {
+    Fins,
+    Kickboard,
+    Paddles,
+    PullBuoy,
+    Snorkel,
+    /// Key added by Garmin after this crate was written
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EquipmentType {
+    pub equipment_type_key: Option<Equipment>,
+    // Garmin Connect's own, no export has confirmed the provisional ones yet
+    #[serde(default, rename = "equipmentTypeId")]
+    received_id: Option<u8>,
+    #[serde(default, rename = "displayOrder")]
+    received_display_order: Option<u8>,
+}
+
+impl EquipmentType {
+
+    pub fn new(equipment_type_key: Option<Equipment>) -> Self {
+        EquipmentType { equipment_type_key, received_id: None, received_display_order: None }
+    }
+
+    /// No exported workout has confirmed an equipment id yet, so equipment keeps the id Garmin
+    /// Connect sent and only falls back to a provisional one when built here
+    pub fn equipment_type_id(&self) -> u8 {
+        match &self.equipment_type_key {
+            Some(Equipment::Unknown(_)) => self.received_id.unwrap_or_default(),
+            Some(equipment) => self.received_id.unwrap_or_else(|| provisional_id(equipment)),
+            None => 0,
+        }
+    }
+
+    pub fn display_order(&self) -> u8 {
+        match self.equipment_type_key {
+            Some(_) => self.received_display_order.unwrap_or(self.equipment_type_id()),
+            None => 0,
+        }
+    }
+
+    /// False for a provisional id, one that no Garmin Connect export has shown yet
+    pub fn id_is_confirmed(&self) -> bool {
+        self.equipment_type_key.is_none() || self.received_id.is_some()
+    }
+}
+
+/// In the order Garmin Connect lists the equipment
+fn provisional_id(equipment: &Equipment) -> u8 {
+    match equipment {
+        Equipment::Fins => 1,
+        Equipment::Kickboard => 2,
+        Equipment::Paddles => 3,
+        Equipment::PullBuoy => 4,
+        Equipment::Snorkel => 5,
+        Equipment::Unknown(_) => 0,
+    }
+}
+
+impl PartialEq for EquipmentType {
+    fn eq(&self, other: &Self) -> bool {
+        self.equipment_type_key == other.equipment_type_key
+            && self.equipment_type_id() == other.equipment_type_id()
+            && self.display_order() == other.display_order()
+    }
+}
+
+impl Serialize for EquipmentType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("EquipmentType", 3)?;
+        state.serialize_field("equipmentTypeId", &self.equipment_type_id())?;
+        state.serialize_field("equipmentTypeKey", &self.equipment_type_key)?;
+        state.serialize_field("displayOrder", &self.display_order())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_equipment() {
+        let json_str = r#"
+          {
+            "equipmentTypeId": 2,
+            "equipmentTypeKey": "kickboard",
+            "displayOrder": 2
+          }
+        "#;
+        let json: EquipmentType = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.equipment_type_id(), 2);
+        assert_eq!(json.equipment_type_key, Some(Equipment::Kickboard));
+        assert_eq!(json.display_order(), 2);
+        assert!(json.id_is_confirmed());
+    }
+
+    #[test]
+    fn test_serialize_equipment() {
+        let equipment = EquipmentType::new(Some(Equipment::PullBuoy));
+        let json_str = serde_json::to_string(&equipment).unwrap();
+        let expected = r#"{"equipmentTypeId":4,"equipmentTypeKey":"pull_buoy","displayOrder":4}"#;
+        assert_eq!(json_str, expected);
+        assert!(!equipment.id_is_confirmed());
+    }
+
+    #[test]
+    fn test_serialize_null() {
+        let json_str = serde_json::to_string(&EquipmentType::default()).unwrap();
+        let expected = r#"{"equipmentTypeId":0,"equipmentTypeKey":null,"displayOrder":0}"#;
+        assert_eq!(json_str, expected);
+    }
+
+    #[test]
+    fn test_unknown_equipment() {
+        let json_str = r#"{"equipmentTypeId":7,"equipmentTypeKey":"swim_fins_x","displayOrder":6}"#;
+        let json: EquipmentType = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.equipment_type_key, Some(Equipment::Unknown("swim_fins_x".to_string())));
+        assert_eq!(serde_json::to_string(&json).unwrap(), json_str);
+    }
+}
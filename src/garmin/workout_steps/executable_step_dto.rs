@@ -1,9 +1,13 @@
-use crate::garmin::workout_steps::end_condition::EndCondition;
-use crate::garmin::workout_steps::preferred_end_condition_unit::PreferredEndConditionUnit;
+use crate::garmin::unit::Unit;
+use crate::garmin::workout_steps::{deserialize_extra, unknown_fields, StepNumbering, UnknownData};
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition, EndConditionCompare};
+use crate::garmin::workout_steps::equipment_type::{Equipment, EquipmentType};
+use crate::garmin::workout_steps::preferred_end_condition_unit::{self, PreferredEndConditionUnit};
 use crate::garmin::workout_steps::step_type::{Step, StepType};
-use crate::garmin::workout_steps::stroke_type::StrokeType;
-use crate::garmin::workout_steps::target_type::TargetType;
+use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
+use crate::garmin::workout_steps::target_type::{StepTarget, Target, TargetFields, TargetType};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::cell::Cell;
 
 #[derive(Serialize, Deserialize, PartialEq)]
@@ -18,15 +22,15 @@ pub struct ExecutableStepDTO{
     end_condition: EndCondition,
     end_condition_value: f32,
     preferred_end_condition_unit: Option<PreferredEndConditionUnit>,
-    end_condition_compare: Option<bool>, // Unsure which type this field have
-    target_type: TargetType,
-    target_value_one: Option<f32>,
-    target_value_two: Option<f32>,
+    end_condition_compare: Option<EndConditionCompare>,
+    target_type: Option<TargetType>,
+    target_value_one: Option<f64>,
+    target_value_two: Option<f64>,
     target_value_unit: Option<String>,
     zone_number: Option<u8>,
     secondary_target_type: Option<TargetType>,
-    secondary_target_value_one: Option<f32>,
-    secondary_target_value_two: Option<f32>,
+    secondary_target_value_one: Option<f64>,
+    secondary_target_value_two: Option<f64>,
     secondary_target_value_unit: Option<String>,
     secondary_zone_number: Option<u8>,
     end_condition_zone: Option<String>, // Unsure which type this field have
@@ -37,10 +41,13 @@ pub struct ExecutableStepDTO{
     workout_provider: Option<String>,
     provider_exercise_source_id: Option<u32>,
     weight_value: Option<f32>,
-    weight_unit: Option<String>,
+    weight_unit: Option<Unit>,
+    #[serde(flatten, deserialize_with = "deserialize_extra")]
+    extra: Map<String, Value>,
 }
 
 impl ExecutableStepDTO {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(step_id: u64,
                step_order: u8,
                step_type: StepType,
@@ -50,12 +57,8 @@ impl ExecutableStepDTO {
                end_condition_value: f32,
                target_type: Option<TargetType>,
                stroke_type: StrokeType,
+               equipment_type: EquipmentType,
                is_rest_step: bool) -> Self {
-        let target_type_defined = match target_type {
-            None => {TargetType::default()},
-            Some(target_type) => { target_type }
-        };
-
         let preferred_end_condition_unit = match is_rest_step {
             true => None,
             false => Some(PreferredEndConditionUnit::default())
@@ -69,9 +72,9 @@ impl ExecutableStepDTO {
             description,
             end_condition,
             end_condition_value,
-            preferred_end_condition_unit: preferred_end_condition_unit,
+            preferred_end_condition_unit,
             end_condition_compare: None,
-            target_type: target_type_defined,
+            target_type: Some(target_type.unwrap_or_default()),
             target_value_one: None,
             target_value_two: None,
             target_value_unit: None,
@@ -83,16 +86,18 @@ impl ExecutableStepDTO {
             secondary_zone_number: None,
             end_condition_zone: None,
             stroke_type,
-            equipment_type: EquipmentType::default(),
+            equipment_type,
             category: None,
             exercise_name: None,
             workout_provider: None,
             provider_exercise_source_id: None,
             weight_value: None,
-            weight_unit: None
+            weight_unit: None,
+            extra: Map::new(),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn active_step(
         step_id: u64,
         step_order: u8,
@@ -103,6 +108,7 @@ impl ExecutableStepDTO {
         end_condition_value: f32,
         target_type: Option<TargetType>,
         stroke_type: StrokeType,
+        equipment_type: EquipmentType,
     ) -> Self {
         Self::new(
             step_id,
@@ -114,10 +120,190 @@ impl ExecutableStepDTO {
             end_condition_value,
             target_type,
             stroke_type,
+            equipment_type,
             false
         )
     }
 
+    pub fn step_id(&self) -> u64 {
+        self.step_id
+    }
+
+    pub fn step_order(&self) -> u8 {
+        self.step_order
+    }
+
+    pub fn step_type(&self) -> &StepType {
+        &self.step_type
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    pub fn set_description(&mut self, description: Option<String>) {
+        self.description = description;
+    }
+
+    pub fn end_condition(&self) -> &EndCondition {
+        &self.end_condition
+    }
+
+    /// Distances are in meters, times in seconds
+    pub fn end_condition_value(&self) -> f32 {
+        self.end_condition_value
+    }
+
+    pub fn stroke_type(&self) -> &StrokeType {
+        &self.stroke_type
+    }
+
+    pub fn set_stroke_type(&mut self, stroke_type: StrokeType) {
+        self.stroke_type = stroke_type;
+    }
+
+    /// Garmin stores distances in meters, the preferred unit only controls how they are displayed
+    pub fn set_distance(&mut self, distance: f32, unit: preferred_end_condition_unit::Unit) {
+        let unit = PreferredEndConditionUnit::new(unit);
+        self.end_condition_value = unit.to_meters(distance);
+        self.preferred_end_condition_unit = Some(unit);
+    }
+
+    pub fn preferred_end_condition_unit(&self) -> Option<&PreferredEndConditionUnit> {
+        self.preferred_end_condition_unit.as_ref()
+    }
+
+    /// Distance in the preferred unit, e.g. 100 for a 100 yard step in a yard pool
+    pub fn distance_in_preferred_unit(&self) -> f32 {
+        match &self.preferred_end_condition_unit {
+            Some(unit) => unit.from_meters(self.end_condition_value),
+            None => self.end_condition_value,
+        }
+    }
+
+    pub fn end_condition_compare(&self) -> Option<EndConditionCompare> {
+        self.end_condition_compare
+    }
+
+    pub fn set_end_condition_compare(&mut self, compare: Option<EndConditionCompare>) {
+        self.end_condition_compare = compare;
+    }
+
+    /// Heart rate and power end conditions need a direction to be executable on the watch
+    pub fn has_valid_end_condition(&self) -> bool {
+        !self.end_condition.requires_compare() || self.end_condition_compare.is_some()
+    }
+
+    pub fn equipment_type(&self) -> &EquipmentType {
+        &self.equipment_type
+    }
+
+    pub fn set_equipment_type(&mut self, equipment_type: EquipmentType) {
+        self.equipment_type = equipment_type;
+    }
+
+    pub fn target(&self) -> Option<StepTarget> {
+        StepTarget::from_values(
+            self.target_type.as_ref(),
+            self.target_value_one,
+            self.target_value_two,
+            self.zone_number,
+        )
+    }
+
+    pub fn set_target(&mut self, target: StepTarget) {
+        let (value_one, value_two, zone_number) = target.values();
+        self.target_type = Some(target.target_type());
+        self.target_value_one = value_one;
+        self.target_value_two = value_two;
+        self.target_value_unit = None;
+        self.zone_number = zone_number;
+    }
+
+    pub(crate) fn target_fields(&self) -> TargetFields {
+        TargetFields {
+            target: self.target_type.as_ref().map(|target_type| target_type.target().clone()),
+            value_one: self.target_value_one,
+            value_two: self.target_value_two,
+            zone_number: self.zone_number,
+        }
+    }
+
+    pub(crate) fn set_target_fields(&mut self, fields: TargetFields) {
+        self.target_type = fields.target.map(TargetType::new);
+        self.target_value_one = fields.value_one;
+        self.target_value_two = fields.value_two;
+        self.zone_number = fields.zone_number;
+    }
+
+    pub(crate) fn secondary_target_fields(&self) -> TargetFields {
+        TargetFields {
+            target: self.secondary_target_type.as_ref().map(|target_type| target_type.target().clone()),
+            value_one: self.secondary_target_value_one,
+            value_two: self.secondary_target_value_two,
+            zone_number: self.secondary_zone_number,
+        }
+    }
+
+    pub(crate) fn set_secondary_target_fields(&mut self, fields: TargetFields) {
+        self.secondary_target_type = fields.target.map(TargetType::new);
+        self.secondary_target_value_one = fields.value_one;
+        self.secondary_target_value_two = fields.value_two;
+        self.secondary_zone_number = fields.zone_number;
+    }
+
+    /// Strength exercise as `(category, exerciseName)`, e.g. `("CURL", "DUMBBELL_CURL")`
+    pub fn exercise(&self) -> (Option<&str>, Option<&str>) {
+        (self.category.as_deref(), self.exercise_name.as_deref())
+    }
+
+    pub(crate) fn set_exercise(&mut self, category: Option<String>, exercise_name: Option<String>) {
+        self.category = category;
+        self.exercise_name = exercise_name;
+    }
+
+    /// Garmin Connect sends -1 when no weight was entered
+    pub fn weight(&self) -> Option<(f32, &Unit)> {
+        match (self.weight_value, &self.weight_unit) {
+            (Some(value), Some(unit)) if value >= 0.0 => Some((value, unit)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn set_weight(&mut self, weight: Option<(f32, Unit)>) {
+        (self.weight_value, self.weight_unit) = weight.unzip();
+    }
+
+    pub(crate) fn renumber(&mut self, numbering: &mut StepNumbering, child_step_id: Option<u8>) {
+        (self.step_id, self.step_order) = numbering.next_step();
+        self.child_step_id.set(child_step_id);
+    }
+
+    pub(crate) fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+
+    pub(crate) fn unknown_data(&self, path: &str, warnings: &mut Vec<UnknownData>) {
+        if let Step::Unknown(key) = &self.step_type.step_type_key {
+            warnings.push(UnknownData::new(path, format!("unknown step type `{}`", key)));
+        }
+        if let Condition::Unknown(key) = &self.end_condition.condition_type_key {
+            warnings.push(UnknownData::new(path, format!("unknown end condition `{}`", key)));
+        }
+        for target_type in [&self.target_type, &self.secondary_target_type].into_iter().flatten() {
+            if let Target::Unknown(key) = target_type.target() {
+                warnings.push(UnknownData::new(path, format!("unknown target type `{}`", key)));
+            }
+        }
+        if let Some(Stroke::Unknown(key)) = &self.stroke_type.stroke_type_key {
+            warnings.push(UnknownData::new(path, format!("unknown stroke type `{}`", key)));
+        }
+        if let Some(Equipment::Unknown(key)) = &self.equipment_type.equipment_type_key {
+            warnings.push(UnknownData::new(path, format!("unknown equipment type `{}`", key)));
+        }
+        unknown_fields(&self.extra, path, warnings);
+    }
+
     pub fn rest_step(step_id: u64,
                      step_order: u8,
                      child_step_id: Cell<Option<u8>>,
@@ -128,44 +314,24 @@ impl ExecutableStepDTO {
         Self::new(
             step_id,
             step_order,
-            StepType{step_type_key: Step::Rest},
+            StepType::new(Step::Rest),
             child_step_id,
             description,
             end_condition, // Todo: Check for correctness
             end_condition_value,
             None,
-            StrokeType{stroke_type_key: None},
+            StrokeType::new(None),
+            EquipmentType::default(),
             true
         )
     }
 }
 
 
-#[derive(Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "camelCase")]
-struct EquipmentType{
-    equipment_type_id: u8,
-    equipment_type_key: Option<String>,
-    display_order: u8,
-}
-
-impl Default for EquipmentType {
-    fn default() -> Self {
-        EquipmentType{
-            equipment_type_id: 0,
-            equipment_type_key: None,
-            display_order: 0,
-        }
-    }
-
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::garmin::workout_steps::end_condition::Condition;
     use crate::garmin::workout_steps::step_type::Step;
-    use crate::garmin::workout_steps::stroke_type::Stroke;
 
     #[test]
     fn test_deserialize() {
@@ -240,24 +406,18 @@ mod tests {
         let object = ExecutableStepDTO::active_step(
             9615001364,
             1,
-            StepType{
-                step_type_key: Step::Warmup,
-            },
+            StepType::new(Step::Warmup),
             Cell::new(None),
             None,
-            EndCondition{
-                condition_type_key: Condition::Distance,
-                displayable: true
-            },
+            EndCondition::new(Condition::Distance),
             400.0,
             None,
-            StrokeType{
-                stroke_type_key: Some(Stroke::Free),
-            }
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::default()
         );
         assert_eq!(object.step_id, 9615001364);
         assert_eq!(object.step_order, 1);
-        assert_eq!(object.target_type.workout_target_type_id(), 1)
+        assert_eq!(object.target_type.unwrap().workout_target_type_id(), 1)
     }
 
     #[test]
@@ -265,20 +425,14 @@ mod tests {
         let object = ExecutableStepDTO::active_step(
             9615001364,
             1,
-            StepType{
-                step_type_key: Step::Warmup,
-            },
+            StepType::new(Step::Warmup),
             Cell::new(None),
             None,
-            EndCondition{
-                condition_type_key: Condition::Distance,
-                displayable: true
-            },
+            EndCondition::new(Condition::Distance),
             400.0,
             None,
-            StrokeType{
-                stroke_type_key: Some(Stroke::Free),
-            }
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::default()
         );
 
         let result = serde_json::to_string(&object).unwrap();
@@ -344,5 +498,104 @@ mod tests {
 
         assert_eq!(result, expected_json);
     }
-}
 
+    #[test]
+    fn test_equipment() {
+        let object = ExecutableStepDTO::active_step(
+            9589910128,
+            22,
+            StepType::new(Step::Main),
+            Cell::new(Some(6)),
+            Some("Single kick".to_string()),
+            EndCondition::new(Condition::Distance),
+            25.0,
+            None,
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::new(Some(Equipment::Kickboard)),
+        );
+
+        let json = serde_json::to_string(&object).unwrap();
+        assert!(json.contains(r#""equipmentType":{"equipmentTypeId":2,"equipmentTypeKey":"kickboard","displayOrder":2}"#));
+
+        let result: ExecutableStepDTO = serde_json::from_str(&json).unwrap();
+        assert_eq!(result.equipment_type().equipment_type_key, Some(Equipment::Kickboard));
+    }
+
+    #[test]
+    fn test_yard_distance() {
+        let mut object = ExecutableStepDTO::active_step(
+            9615001364,
+            1,
+            StepType::new(Step::Main),
+            Cell::new(None),
+            None,
+            EndCondition::new(Condition::Distance),
+            100.0,
+            None,
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::default(),
+        );
+        object.set_distance(100.0, preferred_end_condition_unit::Unit::Yard);
+
+        let json = serde_json::to_value(&object).unwrap();
+        assert!((json["endConditionValue"].as_f64().unwrap() - 91.44).abs() < 1e-4);
+        assert_eq!(json["preferredEndConditionUnit"]["unitKey"], "yard");
+        assert!((object.distance_in_preferred_unit() - 100.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_heart_rate_end_condition() {
+        // Run easy until heart rate drops below 130
+        let mut object = ExecutableStepDTO::active_step(
+            9615001364,
+            1,
+            StepType::new(Step::Main),
+            Cell::new(None),
+            None,
+            EndCondition::new(Condition::HeartRate),
+            130.0,
+            None,
+            StrokeType::new(None),
+            EquipmentType::default(),
+        );
+        assert!(!object.has_valid_end_condition());
+
+        object.set_end_condition_compare(Some(EndConditionCompare::LessThan));
+        assert!(object.has_valid_end_condition());
+
+        let json = serde_json::to_value(&object).unwrap();
+        assert_eq!(json["endCondition"]["conditionTypeKey"], "heart.rate");
+        assert_eq!(json["endConditionValue"], 130.0);
+        assert_eq!(json["endConditionCompare"], "lt");
+    }
+
+    #[test]
+    fn test_set_target() {
+        let mut object = ExecutableStepDTO::active_step(
+            9615001364,
+            1,
+            StepType::new(Step::Main),
+            Cell::new(None),
+            None,
+            EndCondition::new(Condition::Time),
+            300.0,
+            None,
+            StrokeType::new(None),
+            EquipmentType::default()
+        );
+        object.set_target(StepTarget::Power { min_watts: 250, max_watts: 280 });
+
+        let json = serde_json::to_value(&object).unwrap();
+        assert_eq!(json["targetType"]["workoutTargetTypeKey"], "power.zone");
+        assert_eq!(json["targetValueOne"], 250.0);
+        assert_eq!(json["targetValueTwo"], 280.0);
+        assert!(json["zoneNumber"].is_null());
+        assert_eq!(object.target(), Some(StepTarget::Power { min_watts: 250, max_watts: 280 }));
+
+        object.set_target(StepTarget::HeartRateZone(2));
+        let json = serde_json::to_value(&object).unwrap();
+        assert_eq!(json["targetType"]["workoutTargetTypeKey"], "heart.rate.zone");
+        assert!(json["targetValueOne"].is_null());
+        assert_eq!(json["zoneNumber"], 2);
+    }
+}
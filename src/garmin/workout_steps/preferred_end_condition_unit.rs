@@ -1,10 +1,26 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum Unit{
-    Meter
+    Meter,
+    Kilometer,
+    Mile,
+    Yard,
+}
+
+impl Unit {
+    pub const ALL: [Unit; 4] = [Unit::Meter, Unit::Kilometer, Unit::Mile, Unit::Yard];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Unit::Meter => "meter",
+            Unit::Kilometer => "kilometer",
+            Unit::Mile => "mile",
+            Unit::Yard => "yard",
+        }
+    }
 }
 
 
@@ -16,17 +32,40 @@ pub struct PreferredEndConditionUnit {
 
 impl PreferredEndConditionUnit {
 
+    pub fn new(unit_key: Unit) -> Self {
+        PreferredEndConditionUnit{unit_key}
+    }
+
+    pub fn unit(&self) -> Unit {
+        self.unit_key
+    }
+
     pub fn unit_id(&self) -> u8{
         match self.unit_key {
             Unit::Meter => 1,
+            Unit::Kilometer => 2,
+            Unit::Mile => 4,
+            Unit::Yard => 230,
         }
     }
 
+    /// Centimeters per unit
     pub fn factor(&self) -> f32{
         match self.unit_key {
             Unit::Meter => 100.0,
+            Unit::Kilometer => 100000.0,
+            Unit::Mile => 160934.4,
+            Unit::Yard => 91.44,
         }
     }
+
+    pub fn to_meters(&self, value: f32) -> f32 {
+        value * self.factor() / 100.0
+    }
+
+    pub fn from_meters(&self, meters: f32) -> f32 {
+        meters * 100.0 / self.factor()
+    }
 }
 
 
@@ -81,4 +120,18 @@ mod tests {
         let expected = r#"{"unitId":1,"unitKey":"meter","factor":100.0}"#;
         assert_eq!(json_str, expected);
     }
+
+    #[test]
+    fn test_imperial_units() {
+        let yard = PreferredEndConditionUnit::new(Unit::Yard);
+        assert_eq!(serde_json::to_string(&yard).unwrap(), r#"{"unitId":230,"unitKey":"yard","factor":91.44}"#);
+        assert!((yard.to_meters(100.0) - 91.44).abs() < 1e-4);
+        assert!((yard.from_meters(91.44) - 100.0).abs() < 1e-4);
+
+        let mile = PreferredEndConditionUnit::new(Unit::Mile);
+        assert_eq!(serde_json::to_string(&mile).unwrap(), r#"{"unitId":4,"unitKey":"mile","factor":160934.4}"#);
+
+        let kilometer = PreferredEndConditionUnit::new(Unit::Kilometer);
+        assert_eq!(kilometer.to_meters(1.5), 1500.0);
+    }
 }
\ No newline at end of file
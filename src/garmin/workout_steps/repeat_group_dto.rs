@@ -1,9 +1,10 @@
-use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
-use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::{deserialize_extra, unknown_fields, StepNumbering, UnknownData};
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition, EndConditionCompare};
 use crate::garmin::workout_steps::preferred_end_condition_unit::PreferredEndConditionUnit;
 use crate::garmin::workout_steps::step_type::{Step, StepType};
+use crate::garmin::workout_steps::WorkoutStep;
 use serde::{Deserialize, Serialize};
-use std::cell::Cell;
+use serde_json::{Map, Value};
 
 #[derive(Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type")]
@@ -14,13 +15,15 @@ pub struct RepeatGroupDTO {
     step_type: StepType,
     child_step_id: u8,
     number_of_iterations: u8,
-    workout_steps: Vec<ExecutableStepDTO>,
+    workout_steps: Vec<WorkoutStep>,
     end_condition_value: f32,
     preferred_end_condition_unit: Option<PreferredEndConditionUnit>,
-    end_condition_compare: Option<bool>,
+    end_condition_compare: Option<EndConditionCompare>,
     end_condition: EndCondition,
     skip_last_rest_step: Option<bool>,
     smart_repeat: bool,
+    #[serde(flatten, deserialize_with = "deserialize_extra")]
+    extra: Map<String, Value>,
 }
 
 impl RepeatGroupDTO {
@@ -29,19 +32,19 @@ impl RepeatGroupDTO {
         step_order: u8,
         child_step_id: u8,
         number_of_iterations: u8,
-        mut workout_steps: Vec<ExecutableStepDTO>, // Vec<WorkoutStep>
+        workout_steps: Vec<WorkoutStep>,
     ) -> Self {
-        // Set child_step_id in workout steps
-        for i in workout_steps.iter_mut() {
-            i.child_step_id = Cell::new(Some(child_step_id))
+        // Set child_step_id in single steps, nested repeat groups keep their own
+        for step in workout_steps.iter() {
+            if let WorkoutStep::Single(single) = step {
+                single.child_step_id.set(Some(child_step_id))
+            }
         }
 
         RepeatGroupDTO {
             step_id,
             step_order,
-            step_type: StepType {
-                step_type_key: Step::Repeat,
-            },
+            step_type: StepType::new(Step::Repeat),
             child_step_id,
             number_of_iterations,
             workout_steps,
@@ -49,12 +52,67 @@ impl RepeatGroupDTO {
 
             preferred_end_condition_unit: None,
             end_condition_compare: None,
-            end_condition: EndCondition {
-                condition_type_key: Condition::Iterations,
-                displayable: false,
-            },
+            end_condition: EndCondition::with_displayable(Condition::Iterations, false),
             skip_last_rest_step: None,
             smart_repeat: false,
+            extra: Map::new(),
+        }
+    }
+
+    pub fn step_id(&self) -> u64 {
+        self.step_id
+    }
+
+    pub fn step_order(&self) -> u8 {
+        self.step_order
+    }
+
+    pub fn number_of_iterations(&self) -> u8 {
+        self.number_of_iterations
+    }
+
+    pub fn skip_last_rest_step(&self) -> Option<bool> {
+        self.skip_last_rest_step
+    }
+
+    pub(crate) fn set_skip_last_rest_step(&mut self, skip_last_rest_step: Option<bool>) {
+        self.skip_last_rest_step = skip_last_rest_step;
+    }
+
+    pub fn child_step_id(&self) -> u8 {
+        self.child_step_id
+    }
+
+    pub fn workout_steps(&self) -> &[WorkoutStep] {
+        &self.workout_steps
+    }
+
+    pub(crate) fn workout_steps_mut(&mut self) -> &mut [WorkoutStep] {
+        &mut self.workout_steps
+    }
+
+    pub(crate) fn renumber(&mut self, numbering: &mut StepNumbering) {
+        (self.step_id, self.step_order) = numbering.next_step();
+        self.child_step_id = numbering.next_child_step_id();
+        for step in self.workout_steps.iter_mut() {
+            step.renumber(numbering, Some(self.child_step_id));
+        }
+    }
+
+    pub(crate) fn extra(&self) -> &Map<String, Value> {
+        &self.extra
+    }
+
+    pub(crate) fn unknown_data(&self, path: &str, warnings: &mut Vec<UnknownData>) {
+        if let Step::Unknown(key) = &self.step_type.step_type_key {
+            warnings.push(UnknownData::new(path, format!("unknown step type `{}`", key)));
+        }
+        if let Condition::Unknown(key) = &self.end_condition.condition_type_key {
+            warnings.push(UnknownData::new(path, format!("unknown end condition `{}`", key)));
+        }
+        unknown_fields(&self.extra, path, warnings);
+        for (i, step) in self.workout_steps.iter().enumerate() {
+            step.unknown_data(&format!("{}.workoutSteps[{}]", path, i), warnings);
         }
     }
 }
@@ -62,72 +120,67 @@ impl RepeatGroupDTO {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::garmin::workout_steps::equipment_type::EquipmentType;
+    use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+    use std::cell::Cell;
     use crate::garmin::workout_steps::step_type::Step;
     use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
 
+    fn single(step: &WorkoutStep) -> &ExecutableStepDTO {
+        match step {
+            WorkoutStep::Single(single) => single,
+            WorkoutStep::Repeat(_) => panic!("Expected a single step"),
+        }
+    }
+
     #[test]
     fn test_build_repeat_group_dto() {
         let workout_step = ExecutableStepDTO::active_step(
             9615001364,
             3,
-            StepType {
-                step_type_key: Step::Warmup,
-            },
+            StepType::new(Step::Warmup),
             None.into(),
             None,
-            EndCondition {
-                condition_type_key: Condition::Distance,
-                displayable: true,
-            },
+            EndCondition::new(Condition::Distance),
             400.0,
             None,
-            StrokeType {
-                stroke_type_key: Some(Stroke::Free),
-            },
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::default(),
         );
 
-        let step = RepeatGroupDTO::new(9615001366, 3, 1, 8, vec![workout_step]);
+        let step = RepeatGroupDTO::new(9615001366, 3, 1, 8, vec![WorkoutStep::Single(workout_step)]);
         assert_eq!(step.step_id, 9615001366);
         assert_eq!(step.step_order, 3);
         assert_eq!(step.child_step_id, 1);
         assert_eq!(step.number_of_iterations, 8);
 
         //Check that first entry workout_steps have child_step_id set
-        assert_eq!(step.workout_steps[0].child_step_id, Some(1).into());
+        assert_eq!(single(&step.workout_steps[0]).child_step_id, Some(1).into());
     }
 
     #[test]
     fn test_serialize() {
         let workout_steps = vec![
-            ExecutableStepDTO::active_step(
+            WorkoutStep::Single(ExecutableStepDTO::active_step(
                 9615001367,
                 4,
-                StepType {
-                    step_type_key: Step::Main,
-                },
+                StepType::new(Step::Main),
                 None.into(),
                 None,
-                EndCondition {
-                    condition_type_key: Condition::Distance,
-                    displayable: true,
-                },
+                EndCondition::new(Condition::Distance),
                 100.0,
                 None,
-                StrokeType {
-                    stroke_type_key: Some(Stroke::Free),
-                },
-            ),
-            ExecutableStepDTO::rest_step(
+                StrokeType::new(Some(Stroke::Free)),
+                EquipmentType::default(),
+            )),
+            WorkoutStep::Single(ExecutableStepDTO::rest_step(
                 9615001368,
                 5,
                 None.into(),
                 None,
-                EndCondition {
-                    condition_type_key: Condition::FixedRest,
-                    displayable: true,
-                },
+                EndCondition::new(Condition::FixedRest),
                 15.0,
-            ),
+            )),
         ];
 
         let repeat_group = RepeatGroupDTO::new(9615001366, 3, 1, 8, workout_steps);
@@ -411,11 +464,69 @@ mod tests {
         }
         "#;
 
-        let result: RepeatGroupDTO = serde_json::from_str(&json).unwrap();
+        let result: RepeatGroupDTO = serde_json::from_str(json).unwrap();
 
         assert_eq!(result.step_id, 9615001366);
 
         assert_eq!(result.workout_steps.len(), 2);
-        assert_eq!(result.workout_steps[1].child_step_id, Cell::new(Some(1)))
+        assert_eq!(single(&result.workout_steps[1]).child_step_id, Cell::new(Some(1)))
+    }
+
+    #[test]
+    fn test_nested_repeat_group() {
+        // 3 rounds of (4x50 kick + 200 swim)
+        let kick = ExecutableStepDTO::active_step(
+            9615001366,
+            3,
+            StepType::new(Step::Main),
+            None.into(),
+            Some("Kick".to_string()),
+            EndCondition::new(Condition::Distance),
+            50.0,
+            None,
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::default(),
+        );
+        let swim = ExecutableStepDTO::active_step(
+            9615001367,
+            4,
+            StepType::new(Step::Main),
+            None.into(),
+            None,
+            EndCondition::new(Condition::Distance),
+            200.0,
+            None,
+            StrokeType::new(Some(Stroke::Free)),
+            EquipmentType::default(),
+        );
+
+        let inner = RepeatGroupDTO::new(9615001365, 2, 2, 4, vec![WorkoutStep::Single(kick)]);
+        let outer = RepeatGroupDTO::new(
+            9615001364,
+            1,
+            1,
+            3,
+            vec![WorkoutStep::Repeat(inner), WorkoutStep::Single(swim)],
+        );
+
+        let json = serde_json::to_value(&outer).unwrap();
+        assert_eq!(json["childStepId"], 1);
+        assert_eq!(json["workoutSteps"][0]["type"], "RepeatGroupDTO");
+        assert_eq!(json["workoutSteps"][0]["childStepId"], 2);
+        assert_eq!(json["workoutSteps"][0]["workoutSteps"][0]["childStepId"], 2);
+        assert_eq!(json["workoutSteps"][0]["workoutSteps"][0]["stepOrder"], 3);
+        assert_eq!(json["workoutSteps"][1]["type"], "ExecutableStepDTO");
+        assert_eq!(json["workoutSteps"][1]["childStepId"], 1);
+        assert_eq!(json["workoutSteps"][1]["stepOrder"], 4);
+
+        let result: RepeatGroupDTO = serde_json::from_str(&json.to_string()).unwrap();
+        assert!(result == outer);
+        match &result.workout_steps[0] {
+            WorkoutStep::Repeat(nested) => {
+                assert_eq!(nested.number_of_iterations, 4);
+                assert_eq!(single(&nested.workout_steps[0]).child_step_id, Cell::new(Some(2)));
+            }
+            WorkoutStep::Single(_) => panic!("Expected a nested repeat group"),
+        }
     }
 }
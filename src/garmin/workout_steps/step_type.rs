@@ -1,7 +1,7 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Step {
     Warmup,
@@ -9,32 +9,58 @@ pub enum Step {
     Rest,
     Repeat,
     Main,
+    Interval,
+    Recovery,
+    Other,
+    /// Key added by Garmin after this crate was written
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-#[derive(Deserialize, PartialEq)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StepType {
     pub step_type_key: Step,
+    // Only used for unknown keys, known keys derive them
+    #[serde(default, rename = "stepTypeId")]
+    received_id: Option<u8>,
+    #[serde(default, rename = "displayOrder")]
+    received_display_order: Option<u8>,
 }
 
 impl StepType {
 
     pub fn new(step_type_key: Step) -> Self {
-        Self { step_type_key }
+        Self { step_type_key, received_id: None, received_display_order: None }
     }
 
     pub fn step_type_id(&self) -> u8 {
         match self.step_type_key {
             Step::Warmup => 1,
             Step::Cooldown => 2,
+            Step::Interval => 3,
+            Step::Recovery => 4,
             Step::Rest => 5,
             Step::Repeat => 6,
+            Step::Other => 7,
             Step::Main => 8,
+            Step::Unknown(_) => self.received_id.unwrap_or_default(),
         }
     }
 
     pub fn display_order(&self) -> u8 {
-        self.step_type_id()
+        match self.step_type_key {
+            Step::Unknown(_) => self.received_display_order.unwrap_or_default(),
+            _ => self.step_type_id(),
+        }
+    }
+}
+
+impl PartialEq for StepType {
+    fn eq(&self, other: &Self) -> bool {
+        self.step_type_key == other.step_type_key
+            && self.step_type_id() == other.step_type_id()
+            && self.display_order() == other.display_order()
     }
 }
 
@@ -73,11 +99,30 @@ mod tests {
 
     #[test]
     fn test_serialize_step() {
-        let step = StepType {
-            step_type_key: Step::Cooldown,
-        };
+        let step = StepType::new(Step::Cooldown);
         let json_str = serde_json::to_string(&step).unwrap();
         let expected = r#"{"stepTypeId":2,"stepTypeKey":"cooldown","displayOrder":2}"#;
         assert_eq!(json_str, expected);
     }
+
+    #[test]
+    fn test_interval_recovery_and_other() {
+        for (step, json_str) in [
+            (Step::Interval, r#"{"stepTypeId":3,"stepTypeKey":"interval","displayOrder":3}"#),
+            (Step::Recovery, r#"{"stepTypeId":4,"stepTypeKey":"recovery","displayOrder":4}"#),
+            (Step::Other, r#"{"stepTypeId":7,"stepTypeKey":"other","displayOrder":7}"#),
+        ] {
+            let json: StepType = serde_json::from_str(json_str).unwrap();
+            assert_eq!(json.step_type_key, step);
+            assert_eq!(serde_json::to_string(&StepType::new(step)).unwrap(), json_str);
+        }
+    }
+
+    #[test]
+    fn test_unknown_step() {
+        let json_str = r#"{"stepTypeId":9,"stepTypeKey":"tempo","displayOrder":9}"#;
+        let json: StepType = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.step_type_key, Step::Unknown("tempo".to_string()));
+        assert_eq!(serde_json::to_string(&json).unwrap(), json_str);
+    }
 }
\ No newline at end of file
@@ -1,44 +1,91 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 #[serde(rename_all="snake_case")]
+#[allow(clippy::enum_variant_names)]
 pub enum Stroke{
     AnyStroke,
     Free,
+    #[serde(rename = "breaststroke")]
     Breast,
+    #[serde(rename = "backstroke")]
     Back,
+    #[serde(rename = "fly")]
     Butterfly,
-    IndividualMedley
+    IndividualMedley,
+    Drill,
+    Mixed,
+    /// Key added by Garmin after this crate was written
+    #[serde(untagged)]
+    Unknown(String),
 }
 
 
-#[derive(Deserialize, PartialEq)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct StrokeType {
     pub stroke_type_key: Option<Stroke>,
+    // Only used for unknown keys and unconfirmed ids, confirmed keys derive them
+    #[serde(default, rename = "strokeTypeId")]
+    received_id: Option<u8>,
+    #[serde(default, rename = "displayOrder")]
+    received_display_order: Option<u8>,
 }
 
 
 impl StrokeType {
 
     pub fn new(stroke_type_key: Option<Stroke>) -> Self {
-        StrokeType{stroke_type_key}
+        StrokeType{stroke_type_key, received_id: None, received_display_order: None}
     }
+    /// Exported workouts confirm the ids of any_stroke, free and individual_medley. The other
+    /// strokes keep the id Garmin Connect sent, and only fall back to a provisional one that
+    /// follows Garmin's alphabetical numbering when built here, see [`Self::id_is_confirmed`].
     pub fn stroke_type_id(&self) -> u8 {
-        match self.stroke_type_key {
+        match &self.stroke_type_key {
             Some(Stroke::AnyStroke) => 1,
-            Some(Stroke::Breast) => 2,      // Todo: correct
-            Some(Stroke::Back) => 3,        // Todo: correct
-            Some(Stroke::Butterfly) => 4,   // Todo: correct
             Some(Stroke::Free) => 6,
             Some(Stroke::IndividualMedley) => 7,
+            Some(Stroke::Unknown(_)) => self.received_id.unwrap_or_default(),
+            Some(stroke) => self.received_id.unwrap_or_else(|| provisional_id(stroke)),
             None => 0,
         }
     }
 
     pub fn display_order(&self) -> u8 {
-        self.stroke_type_id()
+        match &self.stroke_type_key {
+            Some(Stroke::AnyStroke | Stroke::Free | Stroke::IndividualMedley) | None => self.stroke_type_id(),
+            Some(_) => self.received_display_order.unwrap_or(self.stroke_type_id()),
+        }
+    }
+
+    /// False for a provisional id, one that no Garmin Connect export has shown yet
+    pub fn id_is_confirmed(&self) -> bool {
+        match &self.stroke_type_key {
+            Some(Stroke::AnyStroke | Stroke::Free | Stroke::IndividualMedley) | None => true,
+            Some(_) => self.received_id.is_some(),
+        }
+    }
+}
+
+/// Garmin numbers the strokes in alphabetical order of their keys, which these follow
+fn provisional_id(stroke: &Stroke) -> u8 {
+    match stroke {
+        Stroke::Back => 2,
+        Stroke::Breast => 3,
+        Stroke::Drill => 4,
+        Stroke::Butterfly => 5,
+        Stroke::Mixed => 8,
+        _ => 0,
+    }
+}
+
+impl PartialEq for StrokeType {
+    fn eq(&self, other: &Self) -> bool {
+        self.stroke_type_key == other.stroke_type_key
+            && self.stroke_type_id() == other.stroke_type_id()
+            && self.display_order() == other.display_order()
     }
 }
 
@@ -59,6 +106,21 @@ impl Serialize for StrokeType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::Value;
+    use std::fs;
+
+    fn collect_stroke_types<'a>(value: &'a Value, found: &mut Vec<&'a Value>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(stroke_type) = map.get("strokeType") {
+                    found.push(stroke_type);
+                }
+                map.values().for_each(|v| collect_stroke_types(v, found));
+            }
+            Value::Array(values) => values.iter().for_each(|v| collect_stroke_types(v, found)),
+            _ => {}
+        }
+    }
 
     #[test]
     fn test_deserialize_stroke() {
@@ -77,9 +139,7 @@ mod tests {
 
     #[test]
     fn test_serialize_stroke() {
-        let stroke = StrokeType {
-            stroke_type_key: Some(Stroke::IndividualMedley),
-        };
+        let stroke = StrokeType::new(Some(Stroke::IndividualMedley));
         let json_str = serde_json::to_string(&stroke).unwrap();
         let expected = r#"{"strokeTypeId":7,"strokeTypeKey":"individual_medley","displayOrder":7}"#;
         assert_eq!(json_str, expected);
@@ -102,11 +162,94 @@ mod tests {
 
     #[test]
     fn test_serialize_null() {
-        let resting_stroke = StrokeType {
-            stroke_type_key: None,
-        };
+        let resting_stroke = StrokeType::new(None);
         let json_str = serde_json::to_string(&resting_stroke).unwrap();
         let expected = r#"{"strokeTypeId":0,"strokeTypeKey":null,"displayOrder":0}"#;
         assert_eq!(json_str, expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_stroke_catalogue() {
+        let cases = [
+            (Stroke::AnyStroke, r#"{"strokeTypeId":1,"strokeTypeKey":"any_stroke","displayOrder":1}"#),
+            (Stroke::Free, r#"{"strokeTypeId":6,"strokeTypeKey":"free","displayOrder":6}"#),
+            (Stroke::IndividualMedley, r#"{"strokeTypeId":7,"strokeTypeKey":"individual_medley","displayOrder":7}"#),
+        ];
+
+        for (stroke, expected_json) in cases {
+            let stroke_type = StrokeType::new(Some(stroke.clone()));
+            assert!(stroke_type.id_is_confirmed());
+            assert_eq!(serde_json::to_string(&stroke_type).unwrap(), expected_json);
+
+            let result: StrokeType = serde_json::from_str(expected_json).unwrap();
+            assert_eq!(result.stroke_type_key, Some(stroke));
+        }
+    }
+
+    #[test]
+    fn test_provisional_ids() {
+        let built = StrokeType::new(Some(Stroke::Butterfly));
+        assert!(!built.id_is_confirmed());
+        assert_eq!(serde_json::to_string(&built).unwrap(), r#"{"strokeTypeId":5,"strokeTypeKey":"fly","displayOrder":5}"#);
+
+        // What Garmin Connect sends wins over the provisional id
+        let json_str = r#"{"strokeTypeId":3,"strokeTypeKey":"fly","displayOrder":4}"#;
+        let received: StrokeType = serde_json::from_str(json_str).unwrap();
+        assert!(received.id_is_confirmed());
+        assert_eq!(serde_json::to_string(&received).unwrap(), json_str);
+    }
+
+    #[test]
+    fn test_unknown_stroke() {
+        let json_str = r#"{"strokeTypeId":9,"strokeTypeKey":"rimo","displayOrder":9}"#;
+        let json: StrokeType = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.stroke_type_key, Some(Stroke::Unknown("rimo".to_string())));
+        assert_eq!(serde_json::to_string(&json).unwrap(), json_str);
+    }
+
+    #[test]
+    fn test_ids_match_exported_workouts() {
+        let mut checked = 0;
+        for entry in fs::read_dir("src/workouts_json").expect("Missing workouts_json directory") {
+            let path = entry.unwrap().path();
+            let json: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+            let mut stroke_types = Vec::new();
+            collect_stroke_types(&json, &mut stroke_types);
+            for stroke_type in stroke_types {
+                let parsed: StrokeType = serde_json::from_value(stroke_type.clone()).unwrap();
+                assert_eq!(Value::from(parsed.stroke_type_id()), stroke_type["strokeTypeId"], "{}", path.display());
+                assert_eq!(Value::from(parsed.display_order()), stroke_type["displayOrder"], "{}", path.display());
+                checked += 1;
+            }
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    #[ignore = "needs a Garmin Connect export with backstroke, breaststroke, drill, fly and mixed steps in src/workouts_json"]
+    fn test_every_stroke_is_in_an_export() {
+        let mut seen = Vec::new();
+        for entry in fs::read_dir("src/workouts_json").expect("Missing workouts_json directory") {
+            let json: Value = serde_json::from_str(&fs::read_to_string(entry.unwrap().path()).unwrap()).unwrap();
+            let mut stroke_types = Vec::new();
+            collect_stroke_types(&json, &mut stroke_types);
+            seen.extend(stroke_types.into_iter().filter_map(|stroke_type| stroke_type["strokeTypeKey"].as_str().map(str::to_string)));
+        }
+
+        let catalogue = [
+            Stroke::AnyStroke,
+            Stroke::Back,
+            Stroke::Breast,
+            Stroke::Drill,
+            Stroke::Butterfly,
+            Stroke::Free,
+            Stroke::IndividualMedley,
+            Stroke::Mixed,
+        ];
+        for stroke in catalogue {
+            let key = serde_json::to_value(&stroke).unwrap();
+            assert!(seen.iter().any(|seen| Some(seen.as_str()) == key.as_str()), "no export has {}", key);
+        }
+    }
+}
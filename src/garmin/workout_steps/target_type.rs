@@ -1,40 +1,174 @@
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 #[serde(rename_all= "snake_case")]
 pub enum Target{
     #[serde(rename = "no.target")]
     NoTarget,
+    #[serde(rename = "power.zone")]
+    PowerZone,
+    Cadence,
+    #[serde(rename = "heart.rate.zone")]
+    HeartRateZone,
+    #[serde(rename = "speed.zone")]
+    SpeedZone,
+    #[serde(rename = "pace.zone")]
+    PaceZone,
     #[serde(rename = "swim.instruction")]
     SwimInstruction,
-
+    /// Key added by Garmin after this crate was written
+    #[serde(untagged)]
+    Unknown(String),
 }
 
-#[derive(Deserialize, PartialEq)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TargetType {
     workout_target_type_key: Target,
+    // Only used for unknown keys, known keys derive them
+    #[serde(default, rename = "workoutTargetTypeId")]
+    received_id: Option<u8>,
+    #[serde(default, rename = "displayOrder")]
+    received_display_order: Option<u8>,
 }
 
 impl TargetType {
 
+    pub fn new(workout_target_type_key: Target) -> Self {
+        TargetType{workout_target_type_key, received_id: None, received_display_order: None}
+    }
+
+    pub fn target(&self) -> &Target {
+        &self.workout_target_type_key
+    }
+
     pub fn workout_target_type_id(&self) -> u8{
         match self.workout_target_type_key {
             Target::NoTarget => 1,
+            Target::PowerZone => 2,
+            Target::Cadence => 3,
+            Target::HeartRateZone => 4,
+            Target::SpeedZone => 5,
+            Target::PaceZone => 6,
             Target::SwimInstruction => 18,
+            Target::Unknown(_) => self.received_id.unwrap_or_default(),
         }
     }
 
     pub fn display_order(&self) -> u8{
-        self.workout_target_type_id()
+        match self.workout_target_type_key {
+            Target::Unknown(_) => self.received_display_order.unwrap_or_default(),
+            _ => self.workout_target_type_id(),
+        }
+    }
+}
+
+impl PartialEq for TargetType {
+    fn eq(&self, other: &Self) -> bool {
+        self.workout_target_type_key == other.workout_target_type_key
+            && self.workout_target_type_id() == other.workout_target_type_id()
+            && self.display_order() == other.display_order()
+    }
+}
+
+/// Typed view of a step target and the `targetValueOne`/`targetValueTwo`/`zoneNumber`
+/// fields it is stored in. Garmin stores pace and speed as meters per second, with the
+/// slower bound in `targetValueOne`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StepTarget {
+    NoTarget,
+    /// Pace range in seconds per kilometer
+    Pace { slowest_secs_per_km: f64, fastest_secs_per_km: f64 },
+    /// Speed range in kilometers per hour
+    Speed { min_kph: f64, max_kph: f64 },
+    HeartRateZone(u8),
+    HeartRate { min_bpm: u16, max_bpm: u16 },
+    /// Cadence range in rpm (cycling) or steps per minute (running)
+    Cadence { min: u16, max: u16 },
+    PowerZone(u8),
+    Power { min_watts: u16, max_watts: u16 },
+}
+
+impl StepTarget {
+    pub fn target_type(&self) -> TargetType {
+        let target = match self {
+            StepTarget::NoTarget => Target::NoTarget,
+            StepTarget::Pace { .. } => Target::PaceZone,
+            StepTarget::Speed { .. } => Target::SpeedZone,
+            StepTarget::HeartRateZone(_) | StepTarget::HeartRate { .. } => Target::HeartRateZone,
+            StepTarget::Cadence { .. } => Target::Cadence,
+            StepTarget::PowerZone(_) | StepTarget::Power { .. } => Target::PowerZone,
+        };
+        TargetType::new(target)
+    }
+
+    /// Returns `(target_value_one, target_value_two, zone_number)`
+    pub fn values(&self) -> (Option<f64>, Option<f64>, Option<u8>) {
+        match *self {
+            StepTarget::NoTarget => (None, None, None),
+            StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km } => (
+                Some(1000.0 / slowest_secs_per_km),
+                Some(1000.0 / fastest_secs_per_km),
+                None,
+            ),
+            StepTarget::Speed { min_kph, max_kph } => (Some(min_kph / 3.6), Some(max_kph / 3.6), None),
+            StepTarget::HeartRateZone(zone) | StepTarget::PowerZone(zone) => (None, None, Some(zone)),
+            StepTarget::HeartRate { min_bpm: min, max_bpm: max }
+            | StepTarget::Cadence { min, max }
+            | StepTarget::Power { min_watts: min, max_watts: max } => (Some(min as f64), Some(max as f64), None),
+        }
+    }
+
+    /// Rebuilds the typed target from the stored fields, `None` if they don't describe one
+    pub fn from_values(
+        target_type: Option<&TargetType>,
+        value_one: Option<f64>,
+        value_two: Option<f64>,
+        zone_number: Option<u8>,
+    ) -> Option<StepTarget> {
+        let target = target_type.map_or(&Target::NoTarget, TargetType::target);
+        match (target, value_one, value_two, zone_number) {
+            (Target::NoTarget, _, _, _) => Some(StepTarget::NoTarget),
+            (Target::PaceZone, Some(one), Some(two), _) => Some(StepTarget::Pace {
+                slowest_secs_per_km: 1000.0 / one,
+                fastest_secs_per_km: 1000.0 / two,
+            }),
+            (Target::SpeedZone, Some(one), Some(two), _) => Some(StepTarget::Speed {
+                min_kph: one * 3.6,
+                max_kph: two * 3.6,
+            }),
+            (Target::HeartRateZone, _, _, Some(zone)) => Some(StepTarget::HeartRateZone(zone)),
+            (Target::HeartRateZone, Some(one), Some(two), None) => Some(StepTarget::HeartRate {
+                min_bpm: one.round() as u16,
+                max_bpm: two.round() as u16,
+            }),
+            (Target::Cadence, Some(one), Some(two), _) => Some(StepTarget::Cadence {
+                min: one.round() as u16,
+                max: two.round() as u16,
+            }),
+            (Target::PowerZone, _, _, Some(zone)) => Some(StepTarget::PowerZone(zone)),
+            (Target::PowerZone, Some(one), Some(two), None) => Some(StepTarget::Power {
+                min_watts: one.round() as u16,
+                max_watts: two.round() as u16,
+            }),
+            _ => None,
+        }
     }
 }
+
+/// Target fields as stored, for converters that carry more than `StepTarget` covers
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct TargetFields {
+    pub target: Option<Target>,
+    pub value_one: Option<f64>,
+    pub value_two: Option<f64>,
+    pub zone_number: Option<u8>,
+}
+
 impl Default for TargetType {
     fn default() -> Self {
-        TargetType {
-            workout_target_type_key: Target::NoTarget,
-        }
+        TargetType::new(Target::NoTarget)
     }
 }
 
@@ -73,11 +207,64 @@ mod tests {
 
     #[test]
     fn test_serialize_step() {
-        let step = TargetType {
-            workout_target_type_key: Target::NoTarget,
-        };
+        let step = TargetType::new(Target::NoTarget);
         let json_str = serde_json::to_string(&step).unwrap();
         let expected = r#"{"workoutTargetTypeId":1,"workoutTargetTypeKey":"no.target","displayOrder":1}"#;
         assert_eq!(json_str, expected);
     }
+
+    #[test]
+    fn test_pace_target_values() {
+        // 5:00 - 4:30 min/km
+        let target = StepTarget::Pace { slowest_secs_per_km: 300.0, fastest_secs_per_km: 270.0 };
+        let (one, two, zone) = target.values();
+
+        assert_eq!(target.target_type().workout_target_type_id(), 6);
+        assert!((one.unwrap() - 3.3333).abs() < 1e-3);
+        assert!((two.unwrap() - 3.7037).abs() < 1e-3);
+        assert_eq!(zone, None);
+
+        let round_trip = StepTarget::from_values(Some(&target.target_type()), one, two, zone).unwrap();
+        match round_trip {
+            StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km } => {
+                assert!((slowest_secs_per_km - 300.0).abs() < 1e-9);
+                assert!((fastest_secs_per_km - 270.0).abs() < 1e-9);
+            }
+            other => panic!("Expected pace target, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_zone_and_range_targets() {
+        let cases = [
+            (StepTarget::HeartRateZone(3), 4, (None, None, Some(3))),
+            (StepTarget::HeartRate { min_bpm: 140, max_bpm: 150 }, 4, (Some(140.0), Some(150.0), None)),
+            (StepTarget::Cadence { min: 85, max: 95 }, 3, (Some(85.0), Some(95.0), None)),
+            (StepTarget::PowerZone(4), 2, (None, None, Some(4))),
+            (StepTarget::Power { min_watts: 250, max_watts: 280 }, 2, (Some(250.0), Some(280.0), None)),
+        ];
+
+        for (target, type_id, values) in cases {
+            assert_eq!(target.target_type().workout_target_type_id(), type_id);
+            assert_eq!(target.values(), values);
+            let (one, two, zone) = values;
+            assert_eq!(StepTarget::from_values(Some(&target.target_type()), one, two, zone), Some(target));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_pace_zone() {
+        let json_str = r#"{"workoutTargetTypeId":6,"workoutTargetTypeKey":"pace.zone","displayOrder":6}"#;
+        let json: TargetType = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.target(), &Target::PaceZone);
+    }
+
+    #[test]
+    fn test_unknown_target() {
+        let json_str = r#"{"workoutTargetTypeId":17,"workoutTargetTypeKey":"swim.css.offset","displayOrder":17}"#;
+        let json: TargetType = serde_json::from_str(json_str).unwrap();
+        assert_eq!(json.target(), &Target::Unknown("swim.css.offset".to_string()));
+        assert_eq!(serde_json::to_string(&json).unwrap(), json_str);
+        assert_eq!(StepTarget::from_values(Some(&json), None, None, None), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,591 @@
+//! Intervals.icu workout text, the way our cyclists write workouts:
+//!
+//! ```text
+//! Warmup
+//! - 10m ramp 50-75%
+//!
+//! Main set 4x
+//! - 5m 105% 95rpm
+//! - Rest 3m 55%
+//!
+//! Cooldown
+//! - 10m Z1
+//! ```
+//!
+//! Steps start with `-`. Any other line starts a section that runs to the next blank line,
+//! repeated when it says `4x`; sections named warmup or cooldown hold those step types. A step
+//! is a time (`1h`, `5m`, `1m30s`) or distance (`2km`, `400mtr`, `1mi`) followed by
+//! targets: `75%` or `70-80%` of FTP, `250w`, `Z3` (power zone), `Z2 HR`, `75% HR` (of max),
+//! `95% LTHR`, `4:30/km Pace` or `90% Pace` (of threshold pace), `140-150bpm` and `90rpm`.
+//! Other words are the step's cue, `Rest` or `Recovery` making it a rest step.
+use crate::athlete::Athlete;
+use crate::builder::{self, StepsBuilder, WorkoutBuilder};
+use crate::convert::{self, Loss};
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::{PreferredEndConditionUnit, Unit as DistanceUnit};
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::stroke_type::Stroke;
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+use crate::notation::ParseError;
+use std::cell::Cell;
+
+/// Reads the workout text into a builder for `sport`, resolving relative targets against
+/// the athlete
+pub fn import(text: &str, sport: Sport, athlete: &Athlete) -> Result<(WorkoutBuilder, Vec<Loss>), ParseError> {
+    let mut importer = Importer { athlete, sport: sport.clone(), losses: Vec::new() };
+    let mut builder = builder::workout(sport);
+    let mut section: Option<Section> = None;
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if let Some(section) = section.take() {
+                section.close(&mut builder);
+            }
+        } else if let Some(step) = trimmed.strip_prefix('-') {
+            let column = line.len() - step.len() + 1;
+            let step_type = section.as_ref().map_or(Step::Main, |section| section.step_type.clone());
+            let single = importer.step(step, step_type, line_number, column)?;
+            match &mut section {
+                Some(section) => section.steps.push(WorkoutStep::Single(single)),
+                None => builder.steps().push(WorkoutStep::Single(single)),
+            }
+        } else {
+            if let Some(section) = section.take() {
+                section.close(&mut builder);
+            }
+            let column = line.len() - line.trim_start().len() + 1;
+            section = Some(Section::new(trimmed, line_number, column)?);
+        }
+    }
+    if let Some(section) = section {
+        section.close(&mut builder);
+    }
+    Ok((builder, importer.losses))
+}
+
+struct Section {
+    iterations: Option<u8>,
+    step_type: Step,
+    steps: Vec<WorkoutStep>,
+}
+
+impl Section {
+    fn new(header: &str, line: usize, column: usize) -> Result<Self, ParseError> {
+        let mut iterations = None;
+        for (word, word_column) in words(header, column) {
+            let digits = |count: &&str| !count.is_empty() && count.bytes().all(|byte| byte.is_ascii_digit());
+            let Some(count) = word.strip_suffix(['x', 'X']).filter(digits) else {
+                continue;
+            };
+            iterations = match count.parse::<u8>() {
+                Ok(0) => return Err(ParseError::new(line, word_column, format!("\"{}\" repeats nothing", word))),
+                Ok(count) => Some(count),
+                Err(_) => {
+                    let message = format!("\"{}\" is more than {} repeats", word, u8::MAX);
+                    return Err(ParseError::new(line, word_column, message));
+                }
+            };
+            break;
+        }
+        let lower = header.to_lowercase();
+        let step_type = if lower.contains("warm") {
+            Step::Warmup
+        } else if lower.contains("cool") {
+            Step::Cooldown
+        } else {
+            Step::Main
+        };
+        Ok(Section { iterations, step_type, steps: Vec::new() })
+    }
+
+    fn close(self, builder: &mut WorkoutBuilder) {
+        match self.iterations {
+            Some(iterations) if !self.steps.is_empty() => {
+                let group = RepeatGroupDTO::new(0, 0, 0, iterations, self.steps);
+                builder.steps().push(WorkoutStep::Repeat(group));
+            }
+            _ => builder.steps().extend(self.steps),
+        }
+    }
+}
+
+struct Importer<'a> {
+    athlete: &'a Athlete,
+    sport: Sport,
+    losses: Vec<Loss>,
+}
+
+impl Importer<'_> {
+    fn step(&mut self, text: &str, step_type: Step, line: usize, column: usize) -> Result<ExecutableStepDTO, ParseError> {
+        let path = format!("line {}", line);
+        let words = words(text, column);
+        let mut length = None;
+        let mut targets = Vec::new();
+        let mut cadence = None;
+        let mut ramp = false;
+        let mut cue = Vec::new();
+
+        let mut i = 0;
+        while i < words.len() {
+            let (word, word_column) = words[i];
+            let next = words.get(i + 1).map(|(next, _)| next.to_ascii_lowercase());
+            let mut consume_next = |suffix: &[&str]| {
+                let matched = next.as_deref().is_some_and(|next| suffix.contains(&next));
+                if matched {
+                    i += 1;
+                }
+                matched
+            };
+            let error = |message: &str| ParseError::new(line, word_column, format!("{} in \"{}\"", message, word));
+            let lower = word.to_ascii_lowercase();
+
+            if length.is_none()
+                && let Some(parsed) = parse_length(&lower)
+            {
+                length = Some(parsed);
+            } else if lower == "ramp" {
+                ramp = true;
+            } else if let Some(percent) = lower.strip_suffix('%') {
+                let (low, high) = range(percent, |value| value.parse::<f64>().ok()).ok_or_else(|| error("bad percentage"))?;
+                let (low, high) = (low / 100.0, high / 100.0);
+                let athlete = self.athlete;
+                targets.push(if consume_next(&["hr"]) {
+                    StepTarget::HeartRate { min_bpm: athlete.heart_rate_of_max(low), max_bpm: athlete.heart_rate_of_max(high) }
+                } else if consume_next(&["lthr"]) {
+                    StepTarget::HeartRate { min_bpm: athlete.heart_rate(low), max_bpm: athlete.heart_rate(high) }
+                } else if consume_next(&["pace"]) {
+                    StepTarget::Pace {
+                        slowest_secs_per_km: athlete.pace(low).round(),
+                        fastest_secs_per_km: athlete.pace(high).round(),
+                    }
+                } else {
+                    consume_next(&["ftp", "power"]);
+                    StepTarget::Power { min_watts: athlete.watts(low), max_watts: athlete.watts(high) }
+                });
+            } else if let Some(zone) = lower.strip_prefix('z').and_then(|zone| zone.parse::<u8>().ok()) {
+                if consume_next(&["hr"]) {
+                    targets.push(StepTarget::HeartRateZone(zone));
+                } else if consume_next(&["pace"]) {
+                    self.losses.push(Loss::new(&path, format!("pace zone {} left out, Garmin has no pace zones", zone)));
+                } else {
+                    consume_next(&["power"]);
+                    targets.push(StepTarget::PowerZone(zone));
+                }
+            } else if let Some((min, max)) = lower.strip_suffix('w').and_then(|watts| range(watts, |value| value.parse::<u16>().ok())) {
+                // Not `slow` or `now`, those are the cue's
+                targets.push(StepTarget::Power { min_watts: min, max_watts: max });
+            } else if let Some(bpm) = lower.strip_suffix("bpm") {
+                let (min, max) = range(bpm, |value| value.parse::<u16>().ok()).ok_or_else(|| error("bad heart rate"))?;
+                targets.push(StepTarget::HeartRate { min_bpm: min, max_bpm: max });
+            } else if let Some(rpm) = lower.strip_suffix("rpm") {
+                cadence = Some(range(rpm, |value| value.parse::<u16>().ok()).ok_or_else(|| error("bad cadence"))?);
+            } else if let Some((pace, per)) = lower.split_once('/')
+                && (per == "km" || per == "mi")
+                && let Some((from, to)) = range(pace, clock)
+            {
+                let meters = if per == "km" { 1000.0 } else { 1609.344 };
+                consume_next(&["pace"]);
+                targets.push(StepTarget::Pace {
+                    slowest_secs_per_km: (from.max(to) * 1000.0 / meters).round(),
+                    fastest_secs_per_km: (from.min(to) * 1000.0 / meters).round(),
+                });
+            } else {
+                cue.push(word);
+            }
+            i += 1;
+        }
+
+        let (condition, value, unit) = length.ok_or_else(|| ParseError::new(line, column - 1, "step has no time or distance"))?;
+        let cue = cue.join(" ");
+        let rest = matches!(cue.to_lowercase().as_str(), "rest" | "recovery");
+        let end_condition = EndCondition::new(condition.clone());
+        let mut single = if rest {
+            ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, end_condition, value)
+        } else {
+            let stroke = (self.sport == Sport::Swimming).then_some(Stroke::AnyStroke);
+            let mut single = convert::active_step(step_type, end_condition, value, stroke);
+            single.set_description(Some(cue).filter(|cue| !cue.is_empty()));
+            single
+        };
+        if let Some(unit) = unit {
+            single.set_distance(PreferredEndConditionUnit::new(unit).from_meters(value), unit);
+        }
+
+        if ramp {
+            let message = match (targets.first().copied(), cadence) {
+                (Some(StepTarget::Power { min_watts, max_watts }), _) => {
+                    format!("ramp from {}W to {}W imported as a range", min_watts, max_watts)
+                }
+                (Some(StepTarget::HeartRate { min_bpm, max_bpm }), _) => {
+                    format!("ramp from {}bpm to {}bpm imported as a range", min_bpm, max_bpm)
+                }
+                (Some(StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km }), _) => format!(
+                    "ramp from {}/km to {}/km imported as a range",
+                    format_clock(slowest_secs_per_km),
+                    format_clock(fastest_secs_per_km)
+                ),
+                (Some(StepTarget::Speed { min_kph, max_kph }), _) => {
+                    format!("ramp from {} to {} km/h imported as a range", format_number(min_kph), format_number(max_kph))
+                }
+                (Some(StepTarget::Cadence { min, max }), _) | (None, Some((min, max))) => {
+                    format!("ramp from {}rpm to {}rpm imported as a range", min, max)
+                }
+                (Some(StepTarget::PowerZone(zone)), _) => format!("ramp in power zone {} imported as a steady step", zone),
+                (Some(StepTarget::HeartRateZone(zone)), _) => format!("ramp in heart rate zone {} imported as a steady step", zone),
+                (Some(StepTarget::NoTarget), _) | (None, None) => "ramp without a target imported as a steady step".to_string(),
+            };
+            self.losses.push(Loss::new(&path, message));
+        }
+        if targets.len() > 1 {
+            self.losses.push(Loss::new(&path, "only the first target kept, a Garmin step has one"));
+        }
+        match (targets.first(), cadence) {
+            (Some(target), Some((min, max))) => {
+                single.set_target(*target);
+                self.losses.push(Loss::new(&path, format!("cadence {}rpm left out, a Garmin step has one target", format_range(min as f64, max as f64))));
+            }
+            (Some(target), None) => single.set_target(*target),
+            (None, Some((min, max))) => single.set_target(StepTarget::Cadence { min, max }),
+            (None, None) => {}
+        }
+        Ok(single)
+    }
+}
+
+/// Whitespace separated words with their 1-based columns
+fn words(text: &str, column: usize) -> Vec<(&str, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (c.is_whitespace(), start) {
+            (true, Some(begin)) => {
+                words.push((&text[begin..i], column + text[..begin].chars().count()));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    words
+}
+
+/// `a` or `a-b`
+fn range<T: Copy>(text: &str, parse: impl Fn(&str) -> Option<T>) -> Option<(T, T)> {
+    match text.split_once('-') {
+        Some((low, high)) => Some((parse(low)?, parse(high)?)),
+        None => parse(text).map(|value| (value, value)),
+    }
+}
+
+/// `m:ss` in seconds
+fn clock(text: &str) -> Option<f64> {
+    let (minutes, seconds) = text.split_once(':')?;
+    Some(minutes.parse::<f64>().ok()? * 60.0 + seconds.parse::<f64>().ok()?)
+}
+
+/// `1h30m`, `5m`, `30s`, or `2km`, `400mtr`, `1mi`. A bare `m` is minutes, like Intervals.icu.
+fn parse_length(word: &str) -> Option<(Condition, f32, Option<DistanceUnit>)> {
+    for (suffix, unit) in [("km", DistanceUnit::Kilometer), ("mtr", DistanceUnit::Meter), ("mi", DistanceUnit::Mile)] {
+        if let Some(value) = word.strip_suffix(suffix).and_then(|value| value.parse::<f32>().ok()) {
+            return Some((Condition::Distance, PreferredEndConditionUnit::new(unit).to_meters(value), Some(unit)));
+        }
+    }
+
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    for c in word.chars() {
+        if c.is_ascii_digit() || c == '.' {
+            number.push(c);
+            continue;
+        }
+        let factor = match c {
+            'h' => 3600.0,
+            'm' => 60.0,
+            's' => 1.0,
+            _ => return None,
+        };
+        seconds += number.parse::<f32>().ok()? * factor;
+        number.clear();
+    }
+    (number.is_empty() && seconds > 0.0).then_some((Condition::Time, seconds, None))
+}
+
+/// Writes the workout as Intervals.icu text. Repeats inside repeats are unrolled, lap button
+/// and heart rate or calorie end conditions are left out.
+pub fn export(workout: &Workout, athlete: &Athlete) -> (String, Vec<Loss>) {
+    let mut exporter = Exporter { athlete, losses: Vec::new() };
+    let mut sections: Vec<(Option<String>, Vec<String>)> = Vec::new();
+
+    for (i, segment) in workout.workout_segments().iter().enumerate() {
+        for (j, step) in segment.workout_steps().iter().enumerate() {
+            let path = format!("workoutSegments[{}].workoutSteps[{}]", i, j);
+            match step {
+                WorkoutStep::Single(single) => {
+                    let header = match single.step_type().step_type_key {
+                        Step::Warmup => Some("Warmup".to_string()),
+                        Step::Cooldown => Some("Cooldown".to_string()),
+                        _ => None,
+                    };
+                    let line = exporter.step(single, &path);
+                    match sections.last_mut() {
+                        Some((last, lines)) if *last == header && last.as_deref().is_none_or(|last| !last.ends_with('x')) => {
+                            lines.extend(line)
+                        }
+                        _ => sections.push((header, line.into_iter().collect())),
+                    }
+                }
+                WorkoutStep::Repeat(repeat) => {
+                    let lines = exporter.steps(repeat.workout_steps(), &path);
+                    sections.push((Some(format!("{}x", repeat.number_of_iterations())), lines));
+                }
+            }
+        }
+    }
+
+    let text = sections
+        .into_iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .map(|(header, lines)| header.into_iter().chain(lines).collect::<Vec<String>>().join("\n"))
+        .collect::<Vec<String>>()
+        .join("\n\n");
+    (text + "\n", exporter.losses)
+}
+
+struct Exporter<'a> {
+    athlete: &'a Athlete,
+    losses: Vec<Loss>,
+}
+
+impl Exporter<'_> {
+    /// Lines of a repeat, with any repeats inside it unrolled
+    fn steps(&mut self, steps: &[WorkoutStep], parent_path: &str) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let path = format!("{}.workoutSteps[{}]", parent_path, i);
+            match step {
+                WorkoutStep::Single(single) => lines.extend(self.step(single, &path)),
+                WorkoutStep::Repeat(repeat) => {
+                    let iterations = repeat.number_of_iterations();
+                    self.losses.push(Loss::new(&path, format!("nested repeat unrolled into {} copies", iterations)));
+                    let children = self.steps(repeat.workout_steps(), &path);
+                    lines.extend((0..iterations).flat_map(|_| children.clone()));
+                }
+            }
+        }
+        lines
+    }
+
+    fn step(&mut self, step: &ExecutableStepDTO, path: &str) -> Option<String> {
+        let value = step.end_condition_value();
+        let length = match step.end_condition().condition_type_key {
+            Condition::Time | Condition::FixedRest => format_time(value),
+            Condition::FixedRepetition => {
+                self.losses.push(Loss::new(path, format!("send-off of {} written as a rest that long", format_time(value))));
+                format_time(value)
+            }
+            Condition::Distance => format_distance(step),
+            ref condition => {
+                let key = serde_json::to_value(condition).unwrap_or_default();
+                self.losses.push(Loss::new(path, format!("step ending on {} left out", key)));
+                return None;
+            }
+        };
+
+        let mut words = vec!["-".to_string()];
+        if step.step_type().step_type_key == Step::Rest {
+            words.push("Rest".to_string());
+        } else if let Some(description) = step.description().filter(|description| !description.is_empty()) {
+            words.push(description.to_string());
+        }
+        words.push(length);
+        if let Some(target) = self.target(step, path) {
+            words.push(target);
+        }
+        if let Some(stroke) = &step.stroke_type().stroke_type_key
+            && *stroke != Stroke::AnyStroke
+        {
+            let key = serde_json::to_value(stroke).unwrap_or_default();
+            self.losses.push(Loss::new(path, format!("stroke {} left out", key)));
+        }
+        if let Some(equipment) = &step.equipment_type().equipment_type_key {
+            let key = serde_json::to_value(equipment).unwrap_or_default();
+            self.losses.push(Loss::new(path, format!("equipment {} left out", key)));
+        }
+        Some(words.join(" "))
+    }
+
+    fn target(&mut self, step: &ExecutableStepDTO, path: &str) -> Option<String> {
+        let athlete = self.athlete;
+        let percent = |low: f64, high: f64| format_range((low * 100.0).round(), (high * 100.0).round());
+        Some(match step.target()? {
+            StepTarget::NoTarget => return None,
+            StepTarget::Power { min_watts, max_watts } => {
+                format!("{}%", percent(athlete.fraction_of_ftp(min_watts), athlete.fraction_of_ftp(max_watts)))
+            }
+            StepTarget::PowerZone(zone) => format!("Z{}", zone),
+            StepTarget::HeartRateZone(zone) => format!("Z{} HR", zone),
+            StepTarget::HeartRate { min_bpm, max_bpm } => format!("{}bpm", format_range(min_bpm as f64, max_bpm as f64)),
+            StepTarget::Cadence { min, max } => format!("{}rpm", format_range(min as f64, max as f64)),
+            StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km } => {
+                format_pace(slowest_secs_per_km, fastest_secs_per_km)
+            }
+            StepTarget::Speed { min_kph, max_kph } => format_pace(3600.0 / min_kph, 3600.0 / max_kph),
+        })
+        .or_else(|| {
+            self.losses.push(Loss::new(path, "target left out"));
+            None
+        })
+    }
+}
+
+fn format_number(value: f64) -> String {
+    format!("{}", (value * 10.0).round() / 10.0)
+}
+
+fn format_range(low: f64, high: f64) -> String {
+    let (low, high) = (format_number(low), format_number(high));
+    if low == high { low } else { format!("{}-{}", low, high) }
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds.round() as u32;
+    let parts = [(seconds / 3600, "h"), (seconds / 60 % 60, "m"), (seconds % 60, "s")];
+    let text: String = parts.iter().filter(|(value, _)| *value > 0).map(|(value, unit)| format!("{}{}", value, unit)).collect();
+    if text.is_empty() { "0s".to_string() } else { text }
+}
+
+/// In the step's own unit, Intervals.icu has no yards so those are meters
+fn format_distance(step: &ExecutableStepDTO) -> String {
+    let (value, suffix) = match step.preferred_end_condition_unit().map(PreferredEndConditionUnit::unit) {
+        Some(DistanceUnit::Kilometer) => (step.distance_in_preferred_unit(), "km"),
+        Some(DistanceUnit::Mile) => (step.distance_in_preferred_unit(), "mi"),
+        _ => (step.end_condition_value(), "mtr"),
+    };
+    format!("{}{}", format_number(value as f64), suffix)
+}
+
+fn format_clock(secs: f64) -> String {
+    let secs = secs.round() as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Slowest first, `5:00-4:30/km Pace`
+fn format_pace(slowest_secs_per_km: f64, fastest_secs_per_km: f64) -> String {
+    let (slowest, fastest) = (format_clock(slowest_secs_per_km), format_clock(fastest_secs_per_km));
+    if slowest == fastest {
+        format!("{}/km Pace", slowest)
+    } else {
+        format!("{}-{}/km Pace", slowest, fastest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Units;
+    use crate::garmin::timestamp::{self, FixedClock};
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_import_cyclist_text() {
+        let text = "Warmup\n- 10m ramp 50-70%\n- 3x 1m 100rpm\n\nMain set 4x\n- 5m 105%\n- Rest 3m 55% 85rpm\n\n- Tempo 20m Z3\n- 10m Z2 HR\n\nCooldown\n- 10m ramp 80-95% LTHR\n";
+        let (builder, losses) = import(text, Sport::Cycling, &Athlete::default()).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "line 2: ramp from 125W to 175W imported as a range",
+                "line 7: cadence 85rpm left out, a Garmin step has one target",
+                "line 13: ramp from 136bpm to 162bpm imported as a range",
+            ]
+        );
+
+        let workout = builder.build(&clock()).unwrap();
+        assert_eq!(workout.validate(), vec![]);
+        let steps = workout.workout_segments()[0].workout_steps();
+        assert_eq!(steps.len(), 6);
+        let WorkoutStep::Single(ramp) = &steps[0] else { panic!() };
+        assert_eq!(ramp.step_type().step_type_key, Step::Warmup);
+        assert_eq!(ramp.target(), Some(StepTarget::Power { min_watts: 125, max_watts: 175 }));
+        let WorkoutStep::Single(spin) = &steps[1] else { panic!() };
+        assert_eq!(spin.description(), Some("3x"));
+        assert_eq!(spin.target(), Some(StepTarget::Cadence { min: 100, max: 100 }));
+        let WorkoutStep::Repeat(repeat) = &steps[2] else { panic!() };
+        assert_eq!(repeat.number_of_iterations(), 4);
+        let WorkoutStep::Single(rest) = &repeat.workout_steps()[1] else { panic!() };
+        assert_eq!(rest.step_type().step_type_key, Step::Rest);
+        let WorkoutStep::Single(tempo) = &steps[3] else { panic!() };
+        assert_eq!((tempo.description(), tempo.target()), (Some("Tempo"), Some(StepTarget::PowerZone(3))));
+        let WorkoutStep::Single(cooldown) = &steps[5] else { panic!() };
+        assert_eq!(cooldown.target(), Some(StepTarget::HeartRate { min_bpm: 136, max_bpm: 162 }));
+
+        assert_eq!(
+            import("- 5m 105%\n- hard 1x", Sport::Cycling, &Athlete::default()).err().map(|e| e.to_string()),
+            Some("2:1: step has no time or distance".to_string())
+        );
+        assert_eq!(
+            import("- 5m 1o5%", Sport::Cycling, &Athlete::default()).err().map(|e| e.to_string()),
+            Some("1:6: bad percentage in \"1o5%\"".to_string())
+        );
+        let error = |text: &str| import(text, Sport::Cycling, &Athlete::default()).err().map(|e| e.to_string());
+        assert_eq!(error("Main 0x\n- 5m"), Some("1:6: \"0x\" repeats nothing".to_string()));
+        assert_eq!(error("  Main 300x\n- 5m"), Some("1:8: \"300x\" is more than 255 repeats".to_string()));
+    }
+
+    #[test]
+    fn test_plain_english_cue() {
+        let text = "- 5m slow now, low flow easy/km 200w";
+        let (builder, losses) = import(text, Sport::Cycling, &Athlete::default()).unwrap();
+        assert_eq!(losses, vec![]);
+        let workout = builder.build(&clock()).unwrap();
+        let WorkoutStep::Single(step) = &workout.workout_segments()[0].workout_steps()[0] else { panic!() };
+        assert_eq!(step.description(), Some("slow now, low flow easy/km"));
+        assert_eq!(step.target(), Some(StepTarget::Power { min_watts: 200, max_watts: 200 }));
+    }
+
+    #[test]
+    fn test_export_and_import_back() {
+        let text = "Warmup\n- 2km 5:30-5:00/km Pace\n\n5x\n- 1km 4:10/km Pace\n- Rest 90s\n\n- Steady 20m Z2 HR\n- 1h30s 140-150bpm\n\nCooldown\n- 10m\n";
+        let (builder, losses) = import(text, Sport::Running, &Athlete::default()).unwrap();
+        assert_eq!(losses, vec![]);
+        let workout = builder.build(&clock()).unwrap();
+
+        let (exported, losses) = export(&workout, &Athlete::default());
+        assert_eq!(losses, vec![]);
+        assert_eq!(exported, text.replace("90s", "1m30s"));
+    }
+
+    #[test]
+    fn test_export_reports_losses() {
+        let workout = crate::builder::swim()
+            .repeat(2, |r| {
+                r.repeat(2, |r| r.main(50.m(), crate::builder::Free).send_off(1.mins()))
+                    .rest(crate::builder::lap_button())
+            })
+            .build(&clock())
+            .unwrap();
+
+        let (text, losses) = export(&workout, &Athlete::default());
+        assert_eq!(text, "2x\n- 50mtr\n- Rest 1m\n- 50mtr\n- Rest 1m\n");
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "workoutSegments[0].workoutSteps[0].workoutSteps[0]: nested repeat unrolled into 2 copies",
+                "workoutSegments[0].workoutSteps[0].workoutSteps[0].workoutSteps[0]: stroke \"free\" left out",
+                "workoutSegments[0].workoutSteps[0].workoutSteps[0].workoutSteps[1]: send-off of 1m written as a rest that long",
+                "workoutSegments[0].workoutSteps[0].workoutSteps[1]: step ending on \"lap.button\" left out",
+            ]
+        );
+    }
+}
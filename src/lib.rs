@@ -0,0 +1,16 @@
+pub mod athlete;
+pub mod builder;
+pub mod card;
+pub mod convert;
+pub mod definition;
+pub mod fit;
+pub mod garmin;
+pub mod intervals_icu;
+pub mod notation;
+pub mod tcx;
+pub mod training_peaks;
+pub mod workout_builder;
+pub mod workouts_skillsnt;
+mod xml;
+mod yaml;
+pub mod zwo;
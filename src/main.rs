@@ -1,11 +1,270 @@
-mod garmin;
-mod workout_builder;
-mod workouts_skillsnt;
+use garmin_json_structure::garmin::timestamp::SystemClock;
+use garmin_json_structure::garmin::validation::Severity;
+use garmin_json_structure::garmin::workout::Workout;
+use garmin_json_structure::garmin::estimate::SwimPace;
+use garmin_json_structure::athlete::Athlete;
+use garmin_json_structure::builder::WorkoutBuilder;
+use garmin_json_structure::convert::Loss;
+use garmin_json_structure::garmin::sport_type::Sport;
+use garmin_json_structure::garmin::training_api;
+use garmin_json_structure::{card, definition, fit, intervals_icu, notation, tcx, training_peaks, workouts_skillsnt, zwo};
+use std::path::Path;
+use std::{env, fs, process};
+
+const USAGE: &str = "\
+usage: garmin-json-structure <command> <args>
+
+  Without a command, prints Core Workout 4 as workout JSON.
+
+  validate <workout.json>
+  to-text <workout.json>                  from-text <sets.txt>
+  card <workout.json>
+  to-fit <workout.json> <output.fit>      from-fit <workout.fit>
+  to-zwo <workout.json>                   from-zwo <workout.zwo>
+  to-tcx <workout.json>                   from-tcx <workout.tcx>
+  to-trainingpeaks <workout.json>         from-trainingpeaks <workout.json> <sport>
+  to-training-api <workout.json>          from-training-api <workout.json>
+  to-intervals <workout.json>             from-intervals <workout.txt> <sport>
+  from-yaml <workout.yaml>";
 
 fn main() {
-    println!("Hello, world!");
-    
-    workouts_skillsnt::core_workout4::main();
+    let args: Vec<String> = env::args().collect();
+    let args: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+
+    match args.as_slice() {
+        [] => workouts_skillsnt::core_workout4::main(),
+        ["validate", path] => validate(path),
+        ["to-text", path] => println!("{}", notation::format(&read_workout(path))),
+        ["from-text", path] => from_text(path),
+        ["card", path] => print!("{}", card::render(&read_workout(path), SwimPace::default())),
+        ["to-fit", path, output] => fs::write(output, fit::encode(&read_workout(path))).unwrap_or_else(|e| {
+            eprintln!("Could not write {}: {}", output, e);
+            process::exit(2);
+        }),
+        ["from-fit", path] => from_fit(path),
+        ["to-zwo", path] => to_zwo(path),
+        ["from-zwo", path] => from_zwo(path),
+        ["to-tcx", path] => {
+            let (tcx, losses) = tcx::export(&read_workout(path));
+            print!("{}", tcx);
+            warn(&losses);
+        }
+        ["from-tcx", path] => from_tcx(path),
+        ["to-trainingpeaks", path] => {
+            let (json, losses) = training_peaks::export(&read_workout(path), &Athlete::default());
+            println!("{}", json);
+            warn(&losses);
+        }
+        ["from-trainingpeaks", path, sport] => from_training_peaks(path, sport),
+        ["to-training-api", path] => match training_api::export(&read_workout(path)) {
+            Ok((json, losses)) => {
+                println!("{}", json);
+                warn(&losses);
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        ["from-training-api", path] => {
+            let (workout, losses) = training_api::import(&read_text(path), &SystemClock).unwrap_or_else(|e| {
+                eprintln!("{}: {}", path, e);
+                process::exit(2);
+            });
+            warn(&losses);
+            serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap();
+        }
+        ["to-intervals", path] => {
+            let (text, losses) = intervals_icu::export(&read_workout(path), &Athlete::default());
+            print!("{}", text);
+            warn(&losses);
+        }
+        ["from-intervals", path, sport] => from_intervals(path, sport),
+        ["from-yaml", path] => {
+            let builders = definition::compile(&read_text(path)).unwrap_or_else(|e| {
+                eprintln!("{}:{}", path, e);
+                process::exit(2);
+            });
+            print_workouts(builders.into_iter().map(build).collect());
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn read_workout(path: &str) -> Workout {
+    serde_json::from_str(&read_text(path)).unwrap_or_else(|e| {
+        eprintln!("Invalid workout JSON in {}: {}", path, e);
+        process::exit(2);
+    })
+}
+
+fn validate(path: &str) {
+    let workout = read_workout(path);
+
+    let diagnostics = workout.validate();
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        process::exit(1);
+    }
+}
+
+/// Turns a file of coach notation into workout JSON, named after the file
+fn from_text(path: &str) {
+    let builder = notation::parse(&read_text(path)).unwrap_or_else(|e| {
+        eprintln!("{}:{}", path, e);
+        process::exit(2);
+    });
 
+    let name = Path::new(path).file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    match builder.name(&name).build(&SystemClock) {
+        Ok(workout) => serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap(),
+        Err(diagnostics) => {
+            for diagnostic in &diagnostics {
+                eprintln!("{}", diagnostic);
+            }
+            process::exit(1);
+        }
+    }
+}
+
+/// Turns a FIT workout file into workout JSON
+fn from_fit(path: &str) {
+    match fit::decode(&read_bytes(path)) {
+        Ok(workout) => serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap(),
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(2);
+        }
+    }
+}
+
+/// Prints the workout as a Zwift file, with what it couldn't carry on stderr
+fn to_zwo(path: &str) {
+    match zwo::export(&read_workout(path), &Athlete::default()) {
+        Ok((zwo, losses)) => {
+            print!("{}", zwo);
+            warn(&losses);
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Turns a Zwift file into workout JSON
+fn from_zwo(path: &str) {
+    let (builder, losses) = zwo::import(&read_text(path), &Athlete::default()).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(2);
+    });
+    warn(&losses);
+    let workout = build(builder);
+    serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap();
+}
+
+/// Turns a TCX file into workout JSON, an array if it holds more than one workout
+fn from_tcx(path: &str) {
+    let (builders, losses) = tcx::import(&read_text(path), &Athlete::default()).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(2);
+    });
+    warn(&losses);
+    print_workouts(builders.into_iter().map(build).collect());
+}
+
+/// Turns TrainingPeaks structure JSON into workout JSON, `sport` being a Garmin sport key
+/// like `running`
+fn from_training_peaks(path: &str, sport: &str) {
+    let sport = parse_sport(sport);
+    let (builder, losses) = training_peaks::import(&read_text(path), sport, &Athlete::default()).unwrap_or_else(|e| {
+        eprintln!("{}: {}", path, e);
+        process::exit(2);
+    });
+    warn(&losses);
+    let name = Path::new(path).file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let workout = build(builder.name(&name));
+    serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap();
+}
+
+/// Turns Intervals.icu workout text into workout JSON, named after the file
+fn from_intervals(path: &str, sport: &str) {
+    let sport = parse_sport(sport);
+    let (builder, losses) = intervals_icu::import(&read_text(path), sport, &Athlete::default()).unwrap_or_else(|e| {
+        eprintln!("{}:{}", path, e);
+        process::exit(2);
+    });
+    warn(&losses);
+    let name = Path::new(path).file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let workout = build(builder.name(&name));
+    serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap();
+}
+
+const SPORTS: [Sport; 11] = [
+    Sport::Running,
+    Sport::Cycling,
+    Sport::Other,
+    Sport::Swimming,
+    Sport::StrengthTraining,
+    Sport::CardioTraining,
+    Sport::Yoga,
+    Sport::Pilates,
+    Sport::Hiit,
+    Sport::MultiSport,
+    Sport::Mobility,
+];
+
+/// A Garmin sport key like `running`, one this crate knows
+fn parse_sport(sport: &str) -> Sport {
+    let keys: Vec<String> = SPORTS.iter().map(|known| serde_json::to_value(known).unwrap().as_str().unwrap().to_string()).collect();
+    match keys.iter().position(|key| key == sport) {
+        Some(index) => SPORTS[index].clone(),
+        None => {
+            eprintln!("Unknown sport {}, expected one of {}", sport, keys.join(", "));
+            process::exit(2);
+        }
+    }
+}
+
+fn read_bytes(path: &str) -> Vec<u8> {
+    fs::read(path).unwrap_or_else(|e| {
+        eprintln!("Could not read {}: {}", path, e);
+        process::exit(2);
+    })
+}
+
+fn read_text(path: &str) -> String {
+    String::from_utf8(read_bytes(path)).unwrap_or_else(|e| {
+        eprintln!("Could not read {}: {}", path, e);
+        process::exit(2);
+    })
+}
+
+fn build(builder: WorkoutBuilder) -> Workout {
+    builder.build(&SystemClock).unwrap_or_else(|diagnostics| {
+        for diagnostic in &diagnostics {
+            eprintln!("{}", diagnostic);
+        }
+        process::exit(1);
+    })
+}
+
+/// As workout JSON, an array if there is more than one
+fn print_workouts(mut workouts: Vec<Workout>) {
+    if workouts.len() == 1 {
+        serde_json::to_writer_pretty(std::io::stdout(), &workouts.remove(0)).unwrap();
+    } else {
+        serde_json::to_writer_pretty(std::io::stdout(), &workouts).unwrap();
+    }
+}
 
+fn warn(losses: &[Loss]) {
+    for loss in losses {
+        eprintln!("warning: {}", loss);
+    }
 }
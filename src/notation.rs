@@ -0,0 +1,803 @@
+//! Plain-text swim sets the way coaches write them on a whiteboard, `parse` reads them and
+//! `format` writes them:
+//!
+//! ```text
+//! 400 free warmup / 8x50 kick w/ board r20 / 4x100 IM @1:50 / 200 easy
+//! ```
+//!
+//! Sets are separated by `/`, `,` or new lines. A set is a distance (`100`, `100m`, `50yd`, `1mi`),
+//! a time (`2:00`) or `lap` for "until the lap button", optionally prefixed by a repeat count (`4x`), and followed by any of
+//! - a stroke: `free`, `back`, `breast`, `fly`, `IM`, `drill`, `mixed`, `choice`
+//! - `kick` (a drill) or `pull` (free with a pull buoy)
+//! - a step type: `warmup`/`wu`, `cooldown`/`cd`, `main`
+//! - equipment: `w/ board`, `w/ fins`, `w/ paddles`, `w/ buoy`, `w/ snorkel`
+//! - rest after the swim: `r20`, `rest 1:00`, `r lap`, or a send-off: `@1:45`. `timed-rest 20`
+//!   is the same rest with a `time` end condition instead of `fixed.rest`
+//! - anything else, bare or `"quoted"`, becomes the step description
+//!
+//! Several sets are repeated together with parentheses, `3x(4x50 kick r15, 200 pull)`, and
+//! `r30` on its own is a rest step.
+
+use crate::builder::{lap_button, swim, Length, StepsBuilder, Units, WorkoutBuilder};
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::Condition;
+use crate::garmin::workout_steps::equipment_type::Equipment;
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::Unit as DistanceUnit;
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::stroke_type::Stroke;
+use crate::garmin::workout_steps::WorkoutStep;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    /// 1-based, like editors count
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError { line, column, message: message.into() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the sets into a swim workout builder, name and pool length are left to the caller
+pub fn parse(text: &str) -> Result<WorkoutBuilder, ParseError> {
+    Ok(emit(swim(), parse_sets(text, DistanceUnit::Meter)?))
+}
+
+/// Distances without a unit are in `default_unit`
+pub(crate) fn parse_sets(text: &str, default_unit: DistanceUnit) -> Result<Vec<Set>, ParseError> {
+    let tokens = Lexer { chars: text.chars().collect(), pos: 0, line: 1, column: 1 }.tokens()?;
+    let mut parser = Parser { tokens, pos: 0, default_unit };
+    parser.sets(None)
+}
+
+#[derive(Debug, PartialEq, Clone)]
+enum Token {
+    Number(f32),
+    /// `m:ss`, in seconds
+    Time(f32),
+    Word(String),
+    Quoted(String),
+    Separator,
+    Open,
+    Close,
+    At,
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct Lexed {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Lexer {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn tokens(mut self) -> Result<Vec<Lexed>, ParseError> {
+        let mut tokens = Vec::new();
+        while let Some(c) = self.peek() {
+            let (line, column) = (self.line, self.column);
+            let token = match c {
+                '\n' | '/' | ',' | ';' => {
+                    self.bump();
+                    Token::Separator
+                }
+                c if c.is_whitespace() => {
+                    self.bump();
+                    continue;
+                }
+                '(' | ')' | '@' => {
+                    self.bump();
+                    match c {
+                        '(' => Token::Open,
+                        ')' => Token::Close,
+                        _ => Token::At,
+                    }
+                }
+                '"' => {
+                    self.bump();
+                    let mut text = String::new();
+                    loop {
+                        match self.bump() {
+                            Some('"') => break,
+                            Some(c) => text.push(c),
+                            None => return Err(ParseError::new(line, column, "unterminated description")),
+                        }
+                    }
+                    Token::Quoted(text)
+                }
+                c if c.is_ascii_digit() => self.number(line, column)?,
+                c if c.is_alphabetic() => {
+                    let mut word = String::new();
+                    while let Some(c) = self.peek().filter(|c| c.is_alphabetic() || *c == '-') {
+                        word.push(c);
+                        self.bump();
+                    }
+                    if word.eq_ignore_ascii_case("w") && self.peek() == Some('/') {
+                        word.push('/');
+                        self.bump();
+                    }
+                    Token::Word(word)
+                }
+                c => return Err(ParseError::new(line, column, format!("unexpected character '{}'", c))),
+            };
+            tokens.push(Lexed { token, line, column });
+        }
+        tokens.push(Lexed { token: Token::End, line: self.line, column: self.column });
+        Ok(tokens)
+    }
+
+    fn number(&mut self, line: usize, column: usize) -> Result<Token, ParseError> {
+        let mut text = String::new();
+        while let Some(c) = self.peek().filter(|c| c.is_ascii_digit() || *c == '.' || *c == ':') {
+            text.push(c);
+            self.bump();
+        }
+
+        match text.split_once(':') {
+            Some((minutes, seconds)) => match (minutes.parse::<u32>(), seconds.parse::<u32>()) {
+                (Ok(minutes), Ok(secs)) if seconds.len() == 2 && secs < 60 => minutes
+                    .checked_mul(60)
+                    .and_then(|minutes| minutes.checked_add(secs))
+                    .map(|secs| Token::Time(secs as f32))
+                    .ok_or_else(|| ParseError::new(line, column, format!("time '{}' is too long", text))),
+                _ => Err(ParseError::new(line, column, format!("invalid time '{}', expected m:ss", text))),
+            },
+            None => text
+                .parse()
+                .map(Token::Number)
+                .map_err(|_| ParseError::new(line, column, format!("invalid number '{}'", text))),
+        }
+    }
+}
+
+pub(crate) enum Set {
+    Swim(Swim),
+    Rest(Length),
+    TimedRest(Length),
+    SendOff(Length),
+    Repeat(u8, Vec<Set>),
+}
+
+pub(crate) struct Swim {
+    pub step: Step,
+    pub length: Length,
+    pub stroke: Option<Stroke>,
+    pub equipment: Option<Equipment>,
+    pub description: Vec<String>,
+}
+
+struct Parser {
+    tokens: Vec<Lexed>,
+    pos: usize,
+    default_unit: DistanceUnit,
+}
+
+impl Parser {
+    fn peek(&self) -> &Lexed {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Lexed {
+        let lexed = self.tokens[self.pos].clone();
+        if lexed.token != Token::End {
+            self.pos += 1;
+        }
+        lexed
+    }
+
+    fn error(lexed: &Lexed, message: impl Into<String>) -> ParseError {
+        ParseError::new(lexed.line, lexed.column, message)
+    }
+
+    /// Sets up to the end of the text, or up to the `)` matching `open`
+    fn sets(&mut self, open: Option<&Lexed>) -> Result<Vec<Set>, ParseError> {
+        let mut sets = Vec::new();
+        loop {
+            let lexed = self.peek().clone();
+            match (&lexed.token, open) {
+                (Token::Separator, _) => {
+                    self.next();
+                }
+                (Token::End, None) | (Token::Close, Some(_)) => return Ok(sets),
+                (Token::End, Some(open)) => return Err(Self::error(open, "'(' is never closed")),
+                (Token::Close, None) => return Err(Self::error(&lexed, "')' without a matching '('")),
+                _ => sets.extend(self.set()?),
+            }
+        }
+    }
+
+    fn set(&mut self) -> Result<Vec<Set>, ParseError> {
+        let start = self.next();
+        match &start.token {
+            Token::Word(word) if let Some(rest) = rest(word) => {
+                let rest = rest(self.duration(&start)?);
+                self.end_of_set()?;
+                Ok(vec![rest])
+            }
+            Token::Number(count) if matches!(&self.peek().token, Token::Word(x) if x.eq_ignore_ascii_case("x")) => {
+                self.next();
+                let iterations = match *count {
+                    count if count.fract() == 0.0 && (1.0..=255.0).contains(&count) => count as u8,
+                    _ => return Err(Self::error(&start, "repeat count must be a whole number from 1 to 255")),
+                };
+
+                let sets = if self.peek().token == Token::Open {
+                    let open = self.next();
+                    let mut sets = self.sets(Some(&open))?;
+                    self.next();
+                    if sets.is_empty() {
+                        return Err(Self::error(&open, "empty repeat"));
+                    }
+                    self.rests(&mut sets)?;
+                    self.end_of_set()?;
+                    sets
+                } else {
+                    let first = self.next();
+                    self.swim(first)?
+                };
+                Ok(vec![Set::Repeat(iterations, sets)])
+            }
+            _ => self.swim(start),
+        }
+    }
+
+    /// A distance or time and its modifiers, followed by the rests that come after it
+    fn swim(&mut self, start: Lexed) -> Result<Vec<Set>, ParseError> {
+        let length = match start.token {
+            Token::Number(distance) => {
+                let unit = match &self.peek().token {
+                    Token::Word(word) => distance_unit(word),
+                    _ => None,
+                };
+                if unit.is_some() {
+                    self.next();
+                }
+                if distance <= 0.0 {
+                    return Err(Self::error(&start, "distance must be more than zero"));
+                }
+                Length::Distance(distance, unit.unwrap_or(self.default_unit))
+            }
+            Token::Time(secs) => secs.s(),
+            Token::Word(word) if word.eq_ignore_ascii_case("lap") => lap_button(),
+            _ => return Err(Self::error(&start, "expected a distance, a time, a repeat count or a rest")),
+        };
+
+        let mut swim = Swim { step: Step::Main, length, stroke: None, equipment: None, description: Vec::new() };
+        let mut rests = Vec::new();
+        loop {
+            let lexed = self.peek().clone();
+            match &lexed.token {
+                Token::Separator | Token::Close | Token::End => break,
+                Token::Word(word) if let Some(rest) = rest(word) => {
+                    self.next();
+                    rests.push(rest(self.duration(&lexed)?));
+                }
+                Token::At => {
+                    self.next();
+                    rests.push(Set::SendOff(self.duration(&lexed)?));
+                }
+                Token::Word(word) => {
+                    self.next();
+                    self.word(&mut swim, word, &lexed)?;
+                }
+                Token::Quoted(text) => {
+                    self.next();
+                    swim.description.push(text.clone());
+                }
+                _ => return Err(Self::error(&lexed, "expected a stroke, equipment, rest or description")),
+            }
+        }
+
+        let mut sets = vec![Set::Swim(swim)];
+        sets.extend(rests);
+        Ok(sets)
+    }
+
+    fn word(&mut self, swim: &mut Swim, word: &str, lexed: &Lexed) -> Result<(), ParseError> {
+        let lowercase = word.to_lowercase();
+        if let Some(stroke) = stroke(&lowercase) {
+            set_once(&mut swim.stroke, stroke, lexed, "stroke")
+        } else if let Some(step) = step_type(&lowercase) {
+            swim.step = step;
+            Ok(())
+        } else if lowercase == "kick" {
+            swim.description.push(word.to_string());
+            set_once(&mut swim.stroke, Stroke::Drill, lexed, "stroke")
+        } else if lowercase == "pull" {
+            swim.stroke.get_or_insert(Stroke::Free);
+            set_once(&mut swim.equipment, Equipment::PullBuoy, lexed, "equipment")
+        } else if lowercase == "w/" || lowercase == "with" {
+            let lexed = self.next();
+            let equipment = match &lexed.token {
+                Token::Word(word) => equipment(&word.to_lowercase()),
+                _ => None,
+            };
+            let Some(equipment) = equipment else {
+                return Err(Self::error(&lexed, "expected board, fins, paddles, buoy or snorkel"));
+            };
+            if equipment == Equipment::PullBuoy
+                && matches!(&self.peek().token, Token::Word(word) if word.eq_ignore_ascii_case("buoy"))
+            {
+                self.next();
+            }
+            set_once(&mut swim.equipment, equipment, &lexed, "equipment")
+        } else {
+            swim.description.push(word.to_string());
+            Ok(())
+        }
+    }
+
+    /// The time after `r`, `rest` or `@`, or `lap` to wait for the lap button
+    fn duration(&mut self, after: &Lexed) -> Result<Length, ParseError> {
+        let lexed = self.next();
+        match lexed.token {
+            Token::Number(secs) | Token::Time(secs) => Ok(secs.s()),
+            Token::Word(word) if word.eq_ignore_ascii_case("lap") => Ok(lap_button()),
+            _ => Err(Self::error(after, "expected a time like 20 or 1:30, or lap")),
+        }
+    }
+
+    /// Rests after a parenthesised group belong to every repeat
+    fn rests(&mut self, sets: &mut Vec<Set>) -> Result<(), ParseError> {
+        loop {
+            let lexed = self.peek().clone();
+            match &lexed.token {
+                Token::Word(word) if let Some(rest) = rest(word) => {
+                    self.next();
+                    sets.push(rest(self.duration(&lexed)?));
+                }
+                Token::At => {
+                    self.next();
+                    sets.push(Set::SendOff(self.duration(&lexed)?));
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn end_of_set(&mut self) -> Result<(), ParseError> {
+        let lexed = self.peek();
+        match lexed.token {
+            Token::Separator | Token::Close | Token::End => Ok(()),
+            _ => Err(Self::error(lexed, "expected '/', ',' or a new line")),
+        }
+    }
+}
+
+fn set_once<T: PartialEq>(slot: &mut Option<T>, value: T, lexed: &Lexed, what: &str) -> Result<(), ParseError> {
+    match slot {
+        Some(existing) if *existing != value => Err(Parser::error(lexed, format!("{} given twice", what))),
+        _ => {
+            *slot = Some(value);
+            Ok(())
+        }
+    }
+}
+
+/// `r` and `rest` are a fixed rest, `timed-rest` a rest with a time end condition
+fn rest(word: &str) -> Option<fn(Length) -> Set> {
+    match word.to_lowercase().as_str() {
+        "r" | "rest" => Some(Set::Rest),
+        "timed-rest" => Some(Set::TimedRest),
+        _ => None,
+    }
+}
+
+pub(crate) fn distance_unit(word: &str) -> Option<DistanceUnit> {
+    match word.to_lowercase().as_str() {
+        "m" => Some(DistanceUnit::Meter),
+        "km" => Some(DistanceUnit::Kilometer),
+        "y" | "yd" | "yds" => Some(DistanceUnit::Yard),
+        "mi" => Some(DistanceUnit::Mile),
+        _ => None,
+    }
+}
+
+pub(crate) fn stroke(word: &str) -> Option<Stroke> {
+    match word {
+        "free" | "freestyle" | "fr" => Some(Stroke::Free),
+        "back" | "backstroke" | "bk" => Some(Stroke::Back),
+        "breast" | "breaststroke" | "br" => Some(Stroke::Breast),
+        "fly" | "butterfly" => Some(Stroke::Butterfly),
+        "im" => Some(Stroke::IndividualMedley),
+        "drill" => Some(Stroke::Drill),
+        "mixed" => Some(Stroke::Mixed),
+        "choice" | "any" => Some(Stroke::AnyStroke),
+        _ => None,
+    }
+}
+
+pub(crate) fn step_type(word: &str) -> Option<Step> {
+    match word {
+        "warmup" | "warm-up" | "wu" => Some(Step::Warmup),
+        "cooldown" | "cool-down" | "warmdown" | "warm-down" | "cd" | "wd" => Some(Step::Cooldown),
+        "main" => Some(Step::Main),
+        _ => None,
+    }
+}
+
+pub(crate) fn equipment(word: &str) -> Option<Equipment> {
+    match word {
+        "board" | "kickboard" => Some(Equipment::Kickboard),
+        "fins" => Some(Equipment::Fins),
+        "paddles" | "paddle" => Some(Equipment::Paddles),
+        "buoy" | "pull" => Some(Equipment::PullBuoy),
+        "snorkel" => Some(Equipment::Snorkel),
+        _ => None,
+    }
+}
+
+pub(crate) fn emit<B: StepsBuilder>(mut builder: B, sets: Vec<Set>) -> B {
+    for set in sets {
+        builder = match set {
+            Set::Swim(swim) => {
+                let mut builder = builder.active(swim.step, swim.length, swim.stroke.unwrap_or(Stroke::AnyStroke));
+                if let Some(equipment) = swim.equipment {
+                    builder = builder.equipment(equipment);
+                }
+                if !swim.description.is_empty() {
+                    builder = builder.note(&swim.description.join(" "));
+                }
+                builder
+            }
+            Set::Rest(length) => builder.rest(length),
+            Set::TimedRest(length) => builder.timed_rest(length),
+            Set::SendOff(length) => builder.send_off(length),
+            Set::Repeat(iterations, sets) => builder.repeat(iterations, |repeat| emit(repeat, sets)),
+        };
+    }
+    builder
+}
+
+/// Renders a workout as coach notation, one line per segment.
+///
+/// The result parses back into the same steps, except for end conditions the notation has
+/// no words for, which are written as a quoted `"calories 300"` that `parse` rejects.
+pub fn format(workout: &Workout) -> String {
+    workout
+        .workout_segments()
+        .iter()
+        .map(|segment| format_steps(segment.workout_steps(), " / "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_steps(steps: &[WorkoutStep], separator: &str) -> String {
+    let mut sets: Vec<String> = Vec::new();
+    // Rests read best right after the swim they follow, `100 free r20`
+    let mut after_swim = false;
+    for step in steps {
+        match step {
+            WorkoutStep::Single(single) if is_rest_step(single) => match sets.last_mut() {
+                Some(last) if after_swim => {
+                    last.push(' ');
+                    last.push_str(&format_rest(single));
+                }
+                _ => sets.push(format_rest(single)),
+            },
+            WorkoutStep::Single(single) => {
+                sets.push(format_swim(single));
+                after_swim = true;
+            }
+            WorkoutStep::Repeat(repeat) => {
+                sets.push(format_repeat(repeat));
+                after_swim = false;
+            }
+        }
+    }
+    sets.join(separator)
+}
+
+fn format_repeat(repeat: &RepeatGroupDTO) -> String {
+    let steps = repeat.workout_steps();
+    let single_swim = match steps.split_first() {
+        Some((WorkoutStep::Single(first), rests)) => {
+            !is_rest_step(first) && rests.iter().all(|step| matches!(step, WorkoutStep::Single(rest) if is_rest_step(rest)))
+        }
+        _ => false,
+    };
+
+    if single_swim {
+        format!("{}x{}", repeat.number_of_iterations(), format_steps(steps, ", "))
+    } else {
+        format!("{}x({})", repeat.number_of_iterations(), format_steps(steps, ", "))
+    }
+}
+
+pub(crate) fn is_rest_step(step: &ExecutableStepDTO) -> bool {
+    step.step_type().step_type_key == Step::Rest
+}
+
+pub(crate) fn format_rest(step: &ExecutableStepDTO) -> String {
+    let value = step.end_condition_value();
+    match &step.end_condition().condition_type_key {
+        Condition::LapButton => "r lap".to_string(),
+        Condition::FixedRest => format!("r{}", format_secs(value)),
+        Condition::Time => format!("timed-rest {}", format_secs(value)),
+        Condition::FixedRepetition => format!("@{}", format_clock(value)),
+        condition => format!("r \"{} {}\"", key(condition), value),
+    }
+}
+
+pub(crate) fn format_swim(step: &ExecutableStepDTO) -> String {
+    let mut words = vec![match &step.end_condition().condition_type_key {
+        Condition::Distance => format_distance(step),
+        Condition::Time => format_clock(step.end_condition_value()),
+        Condition::LapButton => "lap".to_string(),
+        condition => format!("\"{} {}\"", key(condition), step.end_condition_value()),
+    }];
+
+    let equipment = step.equipment_type().equipment_type_key.clone();
+    let stroke = step.stroke_type().stroke_type_key.clone();
+    let mut description = step.description().unwrap_or_default();
+    match (&stroke, &equipment) {
+        (Some(Stroke::Drill), _) if description.split(' ').next() == Some("kick") => {
+            words.push("kick".to_string());
+            description = description["kick".len()..].trim_start();
+        }
+        (Some(Stroke::Free), Some(Equipment::PullBuoy)) => words.push("pull".to_string()),
+        (Some(Stroke::AnyStroke) | None, _) => {}
+        (Some(stroke), _) => words.push(format_stroke(stroke)),
+    }
+
+    match step.step_type().step_type_key {
+        Step::Warmup => words.push("wu".to_string()),
+        Step::Cooldown => words.push("cd".to_string()),
+        _ => {}
+    }
+
+    match (&stroke, &equipment) {
+        (Some(Stroke::Free), Some(Equipment::PullBuoy)) | (_, None) => {}
+        (_, Some(Equipment::Unknown(key))) => words.push(format_description(key)),
+        (_, Some(equipment)) => words.push(format!("w/ {}", format_equipment(equipment))),
+    }
+
+    if !description.is_empty() {
+        words.push(format_description(description));
+    }
+    words.join(" ")
+}
+
+fn format_distance(step: &ExecutableStepDTO) -> String {
+    // Yard conversions are not exact in f32
+    let distance = (step.distance_in_preferred_unit() * 100.0).round() / 100.0;
+    match step.preferred_end_condition_unit().map(|unit| unit.unit()) {
+        Some(DistanceUnit::Yard) => format!("{}yd", distance),
+        Some(DistanceUnit::Kilometer) => format!("{}km", distance),
+        Some(DistanceUnit::Mile) => format!("{}mi", distance),
+        _ => format!("{}", distance),
+    }
+}
+
+fn format_stroke(stroke: &Stroke) -> String {
+    match stroke {
+        Stroke::Free => "free".to_string(),
+        Stroke::Back => "back".to_string(),
+        Stroke::Breast => "breast".to_string(),
+        Stroke::Butterfly => "fly".to_string(),
+        Stroke::IndividualMedley => "IM".to_string(),
+        Stroke::Drill => "drill".to_string(),
+        Stroke::Mixed => "mixed".to_string(),
+        Stroke::AnyStroke => "choice".to_string(),
+        Stroke::Unknown(key) => format_description(key),
+    }
+}
+
+fn format_equipment(equipment: &Equipment) -> &'static str {
+    match equipment {
+        Equipment::Fins => "fins",
+        Equipment::Kickboard => "board",
+        Equipment::Paddles => "paddles",
+        Equipment::PullBuoy => "buoy",
+        Equipment::Snorkel => "snorkel",
+        Equipment::Unknown(_) => unreachable!("written as a note"),
+    }
+}
+
+/// Single plain words are written bare, like `easy`, anything that could be misread is quoted
+fn format_description(description: &str) -> String {
+    let lowercase = description.to_lowercase();
+    let plain = description.chars().all(char::is_alphabetic)
+        && stroke(&lowercase).is_none()
+        && step_type(&lowercase).is_none()
+        && equipment(&lowercase).is_none()
+        && distance_unit(&lowercase).is_none()
+        && rest(&lowercase).is_none()
+        && !["kick", "pull", "lap", "x", "w", "with"].contains(&lowercase.as_str());
+    if plain {
+        description.to_string()
+    } else {
+        // Quotes can't be escaped in the notation
+        format!("\"{}\"", description.replace('"', "'"))
+    }
+}
+
+/// Whole seconds below a minute are written bare, `r20`, longer rests as `r1:30`
+fn format_secs(secs: f32) -> String {
+    if secs < 60.0 && secs.fract() == 0.0 {
+        format!("{}", secs)
+    } else {
+        format_clock(secs)
+    }
+}
+
+pub(crate) fn format_clock(secs: f32) -> String {
+    let secs = secs.round() as u32;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+fn key<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(key)) => key,
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garmin::estimate::SwimPace;
+    use crate::garmin::timestamp::{self, FixedClock};
+    use serde_json::Value;
+
+    fn steps(text: &str) -> Value {
+        let clock = FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap());
+        let workout = parse(text).unwrap().build(&clock).unwrap();
+        serde_json::to_value(workout).unwrap()["workoutSegments"][0]["workoutSteps"].clone()
+    }
+
+    fn error(text: &str) -> (usize, usize, String) {
+        let error = parse(text).err().unwrap();
+        (error.line, error.column, error.message)
+    }
+
+    #[test]
+    fn test_coach_notation() {
+        let steps = steps("400 free warmup / 8x50 kick w/ board r20 / 4x100 IM @1:50 / 200 easy");
+
+        assert_eq!(steps[0]["stepType"]["stepTypeKey"], "warmup");
+        assert_eq!(steps[0]["strokeType"]["strokeTypeKey"], "free");
+        assert_eq!(steps[0]["endConditionValue"], 400.0);
+
+        let kick = &steps[1];
+        assert_eq!(kick["numberOfIterations"], 8);
+        assert_eq!(kick["workoutSteps"][0]["strokeType"]["strokeTypeKey"], "drill");
+        assert_eq!(kick["workoutSteps"][0]["equipmentType"]["equipmentTypeKey"], "kickboard");
+        assert_eq!(kick["workoutSteps"][0]["description"], "kick");
+        assert_eq!(kick["workoutSteps"][1]["endCondition"]["conditionTypeKey"], "fixed.rest");
+        assert_eq!(kick["workoutSteps"][1]["endConditionValue"], 20.0);
+
+        let im = &steps[2];
+        assert_eq!(im["workoutSteps"][0]["strokeType"]["strokeTypeKey"], "individual_medley");
+        assert_eq!(im["workoutSteps"][1]["endCondition"]["conditionTypeKey"], "fixed.repetition");
+        assert_eq!(im["workoutSteps"][1]["endConditionValue"], 110.0);
+
+        assert_eq!(steps[3]["strokeType"]["strokeTypeKey"], "any_stroke");
+        assert_eq!(steps[3]["description"], "easy");
+    }
+
+    #[test]
+    fn test_nested_groups() {
+        let steps = steps("3x(4x50 kick r15, 200 pull) r30\n100yd back");
+
+        let group = &steps[0];
+        assert_eq!(group["numberOfIterations"], 3);
+        assert_eq!(group["workoutSteps"][0]["numberOfIterations"], 4);
+        assert_eq!(group["workoutSteps"][1]["equipmentType"]["equipmentTypeKey"], "pull_buoy");
+        assert_eq!(group["workoutSteps"][1]["strokeType"]["strokeTypeKey"], "free");
+        assert_eq!(group["workoutSteps"][2]["endConditionValue"], 30.0);
+        assert_eq!(steps[1]["preferredEndConditionUnit"]["unitKey"], "yard");
+    }
+
+    #[test]
+    fn test_distance() {
+        let workout = parse("400 wu, 4x100 r10, 2:00 kick, 200 cd").unwrap().build(&timestamp::SystemClock).unwrap();
+        // The timed kick counts as 100m at the default pace
+        assert_eq!(workout.estimate(SwimPace::default()).total.distance_in_meters, 1100.0);
+    }
+
+    #[test]
+    fn test_rest_keywords() {
+        let condition = |text: &str| {
+            let workout = parse(text).unwrap().build(&timestamp::SystemClock).unwrap();
+            serde_json::to_value(&workout).unwrap()["workoutSegments"][0]["workoutSteps"][1]["endCondition"]["conditionTypeKey"].clone()
+        };
+        assert_eq!(condition("100 free r20"), "fixed.rest");
+        assert_eq!(condition("100 free rest 20"), "fixed.rest");
+        assert_eq!(condition("100 free timed-rest 20"), "time");
+    }
+
+    #[test]
+    fn test_send_off_rest() {
+        let workout = parse("4x100 @2:30").unwrap().build(&timestamp::SystemClock).unwrap();
+        // 100m at 2:00/100m leaves 30s before the next one
+        assert_eq!(workout.estimate(SwimPace::default()).total.rest_in_secs, 120.0);
+    }
+
+    #[test]
+    fn test_errors_point_at_the_problem() {
+        assert_eq!(error("400 free\n4x(100 fly"), (2, 3, "'(' is never closed".to_string()));
+        assert_eq!(error("100 free r"), (1, 10, "expected a time like 20 or 1:30, or lap".to_string()));
+        assert_eq!(error("100 free @1:5"), (1, 11, "invalid time '1:5', expected m:ss".to_string()));
+        assert_eq!(error("100 @99999999:00"), (1, 6, "time '99999999:00' is too long".to_string()));
+        assert_eq!(error("0x100"), (1, 1, "repeat count must be a whole number from 1 to 255".to_string()));
+        assert_eq!(error("100 free back"), (1, 10, "stroke given twice".to_string()));
+        assert_eq!(error("100 w/ snorkle"), (1, 8, "expected board, fins, paddles, buoy or snorkel".to_string()));
+        assert_eq!(error("free 100"), (1, 1, "expected a distance, a time, a repeat count or a rest".to_string()));
+        assert_eq!(error("100 free $"), (1, 10, "unexpected character '$'".to_string()));
+    }
+
+    fn load(name: &str) -> Workout {
+        serde_json::from_str(&std::fs::read_to_string(format!("src/workouts_json/{}", name)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_format_exported_workout() {
+        assert_eq!(format(&load("expected_workout.json")), "400 free wu r lap / 8x100 free r15 / r lap / 200 cd");
+    }
+
+    #[test]
+    fn test_format_parses_back() {
+        for entry in std::fs::read_dir("src/workouts_json").unwrap() {
+            let path = entry.unwrap().path();
+            let workout: Workout = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let text = format(&workout);
+
+            let reparsed = parse(&text).unwrap().build(&timestamp::SystemClock).unwrap();
+            assert_eq!(format(&reparsed), text, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_format_notation() {
+        for (text, expected) in [
+            ("3x(4x50 kick r15, 200 pull)", "3x(4x50 kick r15, 200 pull)"),
+            // Group rests are written inside the group they belong to
+            ("2x(100 back, 2x(50 fly \"fast\" @1:00)) r30", "2x(100 back, 2x50 fly fast @1:00, r30)"),
+            ("timed-rest 1:30 / lap choice / r lap", "timed-rest 1:30 / lap r lap"),
+            ("500yd w/ fins / 2:00 kick w/ board / 100 IM cd", "500yd w/ fins / 2:00 kick w/ board / 100 IM cd"),
+            ("1mi free / 1.5km free", "1mi free / 1.5km free"),
+        ] {
+            let workout = parse(text).unwrap().pool(25.yd()).build(&timestamp::SystemClock).unwrap();
+            assert_eq!(format(&workout), expected);
+        }
+    }
+}
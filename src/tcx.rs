@@ -0,0 +1,596 @@
+//! Training Center XML (`.tcx`) workouts, for older devices and tools that predate FIT.
+//!
+//! TCX knows running, biking and "other", active and resting steps, and heart rate, speed
+//! and cadence targets. Warmup and cooldown steps are written as active steps. Those, strokes,
+//! equipment, pool lengths, send-offs and power are lost, and reported as a [`Loss`], as are
+//! repetitions and step counts outside what the schema allows.
+use crate::athlete::Athlete;
+use crate::builder::{self, StepsBuilder, WorkoutBuilder};
+use crate::convert::{self, Loss};
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition, EndConditionCompare};
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::stroke_type::Stroke;
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+use crate::xml::{self, Element, XmlError};
+use std::cell::Cell;
+use std::fmt;
+
+const NAMESPACE: &str = "http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2";
+const XSI_NAMESPACE: &str = "http://www.w3.org/2001/XMLSchema-instance";
+/// Workout and step names are `RestrictedToken_t`, devices reject longer ones
+const MAX_NAME_LENGTH: usize = 15;
+/// `Repetitions_t` bounds
+const REPETITIONS: std::ops::RangeInclusive<u8> = 2..=99;
+/// `StepId_t` is at most 20, counting repeats
+const MAX_STEP_ID: u32 = 20;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TcxError {
+    Xml(XmlError),
+    /// The root element isn't `TrainingCenterDatabase`
+    NotTcx(String),
+    NoWorkouts,
+    MissingElement { path: String, element: String },
+    InvalidNumber { path: String, element: String, value: String },
+}
+
+impl fmt::Display for TcxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TcxError::Xml(error) => write!(f, "{}", error),
+            TcxError::NotTcx(root) => write!(f, "root element is <{}>, not <TrainingCenterDatabase>", root),
+            TcxError::NoWorkouts => write!(f, "no <Workouts> in the file"),
+            TcxError::MissingElement { path, element } => write!(f, "{}: missing <{}>", path, element),
+            TcxError::InvalidNumber { path, element, value } => {
+                write!(f, "{}: <{}>{}</{}> is not a number", path, element, value, element)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TcxError {}
+
+/// Writes the workout as a TCX file holding one workout
+pub fn export(workout: &Workout) -> (String, Vec<Loss>) {
+    let mut exporter = Exporter { step_id: 0, losses: Vec::new() };
+    let sport = match workout.sport_type().sport() {
+        Sport::Running => "Running",
+        Sport::Cycling => "Biking",
+        sport => {
+            exporter.losses.push(Loss::new("workout", format!("{:?} written as sport Other", sport)));
+            "Other"
+        }
+    };
+    if workout.sport_type().sport() == Sport::Swimming {
+        let unit = workout.pool_length_unit().unit_key.as_deref().unwrap_or_default();
+        let message = format!("{} {} pool length left out", workout.pool_length(), unit);
+        exporter.losses.push(Loss::new("workout", message));
+    }
+
+    let name = exporter.name(workout.workout_name(), "workout");
+    let mut element = Element::new("Workout").attribute("Sport", sport).child(Element::with_text("Name", name));
+    for (i, segment) in workout.workout_segments().iter().enumerate() {
+        let path = format!("workoutSegments[{}]", i);
+        element.children.extend(exporter.steps(segment.workout_steps(), &path, "Step"));
+    }
+    if let Some(description) = workout.description().filter(|description| !description.is_empty()) {
+        element = element.child(Element::with_text("Notes", description));
+    }
+
+    let database = Element::new("TrainingCenterDatabase")
+        .attribute("xmlns", NAMESPACE)
+        .attribute("xmlns:xsi", XSI_NAMESPACE)
+        .child(Element::new("Workouts").child(element));
+    (database.to_document(), exporter.losses)
+}
+
+struct Exporter {
+    step_id: u32,
+    losses: Vec<Loss>,
+}
+
+impl Exporter {
+    fn name(&mut self, name: &str, path: &str) -> String {
+        if name.chars().count() <= MAX_NAME_LENGTH {
+            return name.to_string();
+        }
+        let shortened: String = name.chars().take(MAX_NAME_LENGTH).collect();
+        self.losses.push(Loss::new(path, format!("\"{}\" shortened to \"{}\"", name, shortened)));
+        shortened
+    }
+
+    /// Reported once, at the first step past the limit
+    fn next_step_id(&mut self, path: &str) {
+        self.step_id += 1;
+        if self.step_id == MAX_STEP_ID + 1 {
+            let message = format!("more than {} steps, devices may refuse the workout", MAX_STEP_ID);
+            self.losses.push(Loss::new(path, message));
+        }
+    }
+
+    /// Steps are `Step` at the top level and `Child` inside repeats
+    fn steps(&mut self, steps: &[WorkoutStep], parent_path: &str, tag: &str) -> Vec<Element> {
+        let mut elements = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let path = format!("{}.workoutSteps[{}]", parent_path, i);
+            elements.push(match step {
+                WorkoutStep::Single(single) => self.single(single, &path, tag),
+                WorkoutStep::Repeat(repeat) => self.repeat(repeat, &path, tag),
+            });
+        }
+        elements
+    }
+
+    /// Like FIT, a repeat's id comes after its children's
+    fn repeat(&mut self, repeat: &RepeatGroupDTO, path: &str, tag: &str) -> Element {
+        let children = self.steps(repeat.workout_steps(), path, "Child");
+        self.next_step_id(path);
+        let iterations = repeat.number_of_iterations();
+        if !REPETITIONS.contains(&iterations) {
+            let message = format!("{} repetitions written, outside TCX's {}-{}", iterations, REPETITIONS.start(), REPETITIONS.end());
+            self.losses.push(Loss::new(path, message));
+        }
+        let mut element = Element::new(tag)
+            .attribute("xsi:type", "Repeat_t")
+            .child(Element::with_text("StepId", self.step_id))
+            .child(Element::with_text("Repetitions", iterations));
+        element.children.extend(children);
+        element
+    }
+
+    fn single(&mut self, step: &ExecutableStepDTO, path: &str, tag: &str) -> Element {
+        self.next_step_id(path);
+        let mut element = Element::new(tag)
+            .attribute("xsi:type", "Step_t")
+            .child(Element::with_text("StepId", self.step_id));
+        if let Some(description) = step.description().filter(|description| !description.is_empty()) {
+            element = element.child(Element::with_text("Name", self.name(description, path)));
+        }
+
+        let rest = match &step.step_type().step_type_key {
+            Step::Rest => true,
+            Step::Main | Step::Repeat => false,
+            kind => {
+                let key = serde_json::to_value(kind).unwrap_or_default();
+                self.losses.push(Loss::new(path, format!("{} step written as active", key)));
+                false
+            }
+        };
+        element = element
+            .child(self.duration(step, path))
+            .child(Element::with_text("Intensity", if rest { "Resting" } else { "Active" }))
+            .child(self.target(step, path));
+
+        if let Some(stroke) = &step.stroke_type().stroke_type_key
+            && *stroke != Stroke::AnyStroke
+        {
+            let key = serde_json::to_value(stroke).unwrap_or_default();
+            self.losses.push(Loss::new(path, format!("stroke {} left out", key)));
+        }
+        if let Some(equipment) = &step.equipment_type().equipment_type_key {
+            let key = serde_json::to_value(equipment).unwrap_or_default();
+            self.losses.push(Loss::new(path, format!("equipment {} left out", key)));
+        }
+        element
+    }
+
+    fn duration(&mut self, step: &ExecutableStepDTO, path: &str) -> Element {
+        let value = step.end_condition_value();
+        let duration = |duration_type: &str| Element::new("Duration").attribute("xsi:type", duration_type);
+        match step.end_condition().condition_type_key {
+            Condition::Time | Condition::FixedRest => {
+                duration("Time_t").child(Element::with_text("Seconds", value.round()))
+            }
+            Condition::FixedRepetition => {
+                let message = format!("send-off of {}s written as a rest that long", value.round());
+                self.losses.push(Loss::new(path, message));
+                duration("Time_t").child(Element::with_text("Seconds", value.round()))
+            }
+            // Always meters, yards included
+            Condition::Distance => duration("Distance_t").child(Element::with_text("Meters", value.round())),
+            Condition::Calories => duration("CaloriesBurned_t").child(Element::with_text("Calories", value.round())),
+            Condition::HeartRate => {
+                let duration_type = match step.end_condition_compare() {
+                    Some(EndConditionCompare::LessThan) => "HeartRateBelow_t",
+                    _ => "HeartRateAbove_t",
+                };
+                duration(duration_type).child(bpm("HeartRate", value.round() as u16))
+            }
+            Condition::LapButton => duration("UserInitiated_t"),
+            ref condition => {
+                let key = serde_json::to_value(condition).unwrap_or_default();
+                self.losses.push(Loss::new(path, format!("{} end condition written as lap button", key)));
+                duration("UserInitiated_t")
+            }
+        }
+    }
+
+    fn target(&mut self, step: &ExecutableStepDTO, path: &str) -> Element {
+        let target = |target_type: &str| Element::new("Target").attribute("xsi:type", target_type);
+        let speed = |view_as: &str, low: f64, high: f64| {
+            target("Speed_t").child(
+                Element::new("SpeedZone")
+                    .attribute("xsi:type", "CustomSpeedZone_t")
+                    .child(Element::with_text("ViewAs", view_as))
+                    .child(Element::with_text("LowInMetersPerSecond", round(low)))
+                    .child(Element::with_text("HighInMetersPerSecond", round(high))),
+            )
+        };
+
+        match step.target().unwrap_or(StepTarget::NoTarget) {
+            StepTarget::NoTarget => target("None_t"),
+            StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km } => {
+                speed("Pace", 1000.0 / slowest_secs_per_km, 1000.0 / fastest_secs_per_km)
+            }
+            StepTarget::Speed { min_kph, max_kph } => speed("Speed", min_kph / 3.6, max_kph / 3.6),
+            StepTarget::HeartRateZone(zone) => target("HeartRate_t").child(
+                Element::new("HeartRateZone")
+                    .attribute("xsi:type", "PredefinedHeartRateZone_t")
+                    .child(Element::with_text("Number", zone)),
+            ),
+            StepTarget::HeartRate { min_bpm, max_bpm } => target("HeartRate_t").child(
+                Element::new("HeartRateZone")
+                    .attribute("xsi:type", "CustomHeartRateZone_t")
+                    .child(bpm("Low", min_bpm))
+                    .child(bpm("High", max_bpm)),
+            ),
+            StepTarget::Cadence { min, max } => target("Cadence_t")
+                .child(Element::with_text("Low", min))
+                .child(Element::with_text("High", max)),
+            StepTarget::PowerZone(zone) => {
+                self.losses.push(Loss::new(path, format!("power zone {} target left out", zone)));
+                target("None_t")
+            }
+            StepTarget::Power { min_watts, max_watts } => {
+                self.losses.push(Loss::new(path, format!("power {}-{} W target left out", min_watts, max_watts)));
+                target("None_t")
+            }
+        }
+    }
+}
+
+fn bpm(name: &str, value: u16) -> Element {
+    Element::new(name)
+        .attribute("xsi:type", "HeartRateInBeatsPerMinute_t")
+        .child(Element::with_text("Value", value))
+}
+
+fn round(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Reads every workout in a TCX file into a builder, heart rates as % of max resolved
+/// against the athlete's max heart rate
+pub fn import(text: &str, athlete: &Athlete) -> Result<(Vec<WorkoutBuilder>, Vec<Loss>), TcxError> {
+    let root = xml::parse(text).map_err(TcxError::Xml)?;
+    if local_name(&root.name) != "TrainingCenterDatabase" {
+        return Err(TcxError::NotTcx(root.name));
+    }
+    let workouts = child(&root, "Workouts").ok_or(TcxError::NoWorkouts)?;
+
+    let mut losses = Vec::new();
+    let mut builders = Vec::new();
+    for (i, workout) in children(workouts, "Workout").enumerate() {
+        let path = format!("Workout[{}]", i);
+        let sport = match workout.get("Sport") {
+            Some("Running") => Sport::Running,
+            Some("Biking") => Sport::Cycling,
+            _ => Sport::Other,
+        };
+        let mut builder = builder::workout(sport).name(text_of(workout, "Name").unwrap_or_default());
+        if let Some(notes) = text_of(workout, "Notes").filter(|notes| !notes.is_empty()) {
+            builder = builder.description(notes);
+        }
+        for (j, step) in children(workout, "Step").enumerate() {
+            let step = import_step(step, &format!("{}.Step[{}]", path, j), athlete, &mut losses)?;
+            builder.steps().push(step);
+        }
+        builders.push(builder);
+    }
+    if builders.is_empty() {
+        return Err(TcxError::NoWorkouts);
+    }
+    Ok((builders, losses))
+}
+
+fn import_step(element: &Element, path: &str, athlete: &Athlete, losses: &mut Vec<Loss>) -> Result<WorkoutStep, TcxError> {
+    if xsi_type(element) == Some("Repeat_t") {
+        let iterations = number(element, path, "Repetitions")?.clamp(1.0, u8::MAX as f64) as u8;
+        let mut steps = Vec::new();
+        for (i, child) in children(element, "Child").enumerate() {
+            steps.push(import_step(child, &format!("{}.Child[{}]", path, i), athlete, losses)?);
+        }
+        return Ok(WorkoutStep::Repeat(RepeatGroupDTO::new(0, 0, 0, iterations, steps)));
+    }
+
+    let duration = child(element, "Duration").ok_or_else(|| missing(path, "Duration"))?;
+    let (condition, value, compare) = match xsi_type(duration) {
+        Some("Time_t") => (Condition::Time, number(duration, path, "Seconds")?, None),
+        Some("Distance_t") => (Condition::Distance, number(duration, path, "Meters")?, None),
+        Some("CaloriesBurned_t") => (Condition::Calories, number(duration, path, "Calories")?, None),
+        Some(duration_type @ ("HeartRateAbove_t" | "HeartRateBelow_t")) => {
+            let heart_rate = child(duration, "HeartRate").ok_or_else(|| missing(path, "HeartRate"))?;
+            let compare =
+                if duration_type == "HeartRateBelow_t" { EndConditionCompare::LessThan } else { EndConditionCompare::GreaterThan };
+            (Condition::HeartRate, read_bpm(heart_rate, path, athlete)? as f64, Some(compare))
+        }
+        Some("UserInitiated_t") => (Condition::LapButton, 0.0, None),
+        duration_type => {
+            let duration_type = duration_type.unwrap_or("untyped");
+            losses.push(Loss::new(path, format!("{} duration read as lap button", duration_type)));
+            (Condition::LapButton, 0.0, None)
+        }
+    };
+
+    let end_condition = EndCondition::new(condition.clone());
+    let mut single = if text_of(element, "Intensity") == Some("Resting") {
+        ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, end_condition, value as f32)
+    } else {
+        convert::active_step(Step::Main, end_condition, value as f32, None)
+    };
+    single.set_end_condition_compare(compare);
+    if let Some(name) = text_of(element, "Name").filter(|name| !name.is_empty()) {
+        single.set_description(Some(name.to_string()));
+    }
+    if let Some(target) = child(element, "Target") {
+        let target = import_target(target, path, athlete, losses)?;
+        if target != StepTarget::NoTarget {
+            single.set_target(target);
+        }
+    }
+    Ok(WorkoutStep::Single(single))
+}
+
+fn import_target(target: &Element, path: &str, athlete: &Athlete, losses: &mut Vec<Loss>) -> Result<StepTarget, TcxError> {
+    Ok(match xsi_type(target) {
+        Some("Speed_t") => {
+            let zone = child(target, "SpeedZone").ok_or_else(|| missing(path, "SpeedZone"))?;
+            if xsi_type(zone) == Some("PredefinedSpeedZone_t") {
+                losses.push(Loss::new(path, "speed zone target left out, Garmin Connect has no speed zones"));
+                return Ok(StepTarget::NoTarget);
+            }
+            let low = number(zone, path, "LowInMetersPerSecond")?;
+            let high = number(zone, path, "HighInMetersPerSecond")?;
+            if text_of(zone, "ViewAs") == Some("Pace") {
+                StepTarget::Pace {
+                    slowest_secs_per_km: (1000.0 / low).round(),
+                    fastest_secs_per_km: (1000.0 / high).round(),
+                }
+            } else {
+                StepTarget::Speed { min_kph: (low * 36.0).round() / 10.0, max_kph: (high * 36.0).round() / 10.0 }
+            }
+        }
+        Some("HeartRate_t") => {
+            let zone = child(target, "HeartRateZone").ok_or_else(|| missing(path, "HeartRateZone"))?;
+            if xsi_type(zone) == Some("PredefinedHeartRateZone_t") {
+                StepTarget::HeartRateZone(number(zone, path, "Number")? as u8)
+            } else {
+                let value = |name: &str| read_bpm(child(zone, name).ok_or_else(|| missing(path, name))?, path, athlete);
+                StepTarget::HeartRate { min_bpm: value("Low")?, max_bpm: value("High")? }
+            }
+        }
+        Some("Cadence_t") => StepTarget::Cadence {
+            min: number(target, path, "Low")? as u16,
+            max: number(target, path, "High")? as u16,
+        },
+        _ => StepTarget::NoTarget,
+    })
+}
+
+/// A `HeartRateValue_t`, in beats per minute or as a percentage of max heart rate
+fn read_bpm(heart_rate: &Element, path: &str, athlete: &Athlete) -> Result<u16, TcxError> {
+    let value = number(heart_rate, path, "Value")?;
+    Ok(match xsi_type(heart_rate) {
+        Some("HeartRateAsPercentOfMax_t") => athlete.heart_rate_of_max(value / 100.0),
+        _ => value.round() as u16,
+    })
+}
+
+/// TCX files written with a namespace prefix say `tcx:Workout`
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn child<'a>(element: &'a Element, name: &str) -> Option<&'a Element> {
+    children(element, name).next()
+}
+
+fn children<'a>(element: &'a Element, name: &str) -> impl Iterator<Item = &'a Element> {
+    element.children.iter().filter(move |child| local_name(&child.name) == name)
+}
+
+fn text_of<'a>(element: &'a Element, name: &str) -> Option<&'a str> {
+    child(element, name).map(|child| child.text.trim())
+}
+
+fn xsi_type(element: &Element) -> Option<&str> {
+    element
+        .attributes
+        .iter()
+        .find(|(key, _)| local_name(key) == "type")
+        .map(|(_, value)| local_name(value))
+}
+
+fn missing(path: &str, element: &str) -> TcxError {
+    TcxError::MissingElement { path: path.to_string(), element: element.to_string() }
+}
+
+fn number(element: &Element, path: &str, name: &str) -> Result<f64, TcxError> {
+    let text = text_of(element, name).ok_or_else(|| missing(path, name))?;
+    text.parse().map_err(|_| TcxError::InvalidNumber {
+        path: path.to_string(),
+        element: name.to_string(),
+        value: text.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::garmin::timestamp::{self, FixedClock};
+    use std::fs;
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_run_round_trip() {
+        let workout = workout(Sport::Running)
+            .name("Tempo")
+            .description("Easy first")
+            .main(2.km(), AnyStroke)
+            .target(StepTarget::HeartRateZone(2))
+            .repeat(4, |r| {
+                r.main(5.mins(), AnyStroke)
+                    .target(StepTarget::Pace { slowest_secs_per_km: 255.0, fastest_secs_per_km: 245.0 })
+                    .note("tempo")
+                    .timed_rest(90.s())
+            })
+            .main(lap_button(), AnyStroke)
+            .target(StepTarget::Cadence { min: 170, max: 180 })
+            .build(&clock())
+            .unwrap();
+
+        let (tcx, losses) = export(&workout);
+        assert_eq!(losses, vec![]);
+        assert!(tcx.contains(
+            r#"    <Child xsi:type="Step_t">
+          <StepId>2</StepId>
+          <Name>tempo</Name>
+          <Duration xsi:type="Time_t">
+            <Seconds>300</Seconds>
+          </Duration>
+          <Intensity>Active</Intensity>
+          <Target xsi:type="Speed_t">
+            <SpeedZone xsi:type="CustomSpeedZone_t">
+              <ViewAs>Pace</ViewAs>
+              <LowInMetersPerSecond>3.922</LowInMetersPerSecond>
+              <HighInMetersPerSecond>4.082</HighInMetersPerSecond>
+            </SpeedZone>
+          </Target>
+        </Child>"#
+        ));
+        assert!(tcx.contains("<StepId>4</StepId>\n        <Repetitions>4</Repetitions>"));
+
+        let (builders, losses) = import(&tcx, &Athlete::default()).unwrap();
+        assert_eq!(losses, vec![]);
+        let imported = builders.into_iter().next().unwrap().build(&clock()).unwrap();
+        assert_eq!(export(&imported).0, tcx);
+        assert_eq!(imported.description(), Some("Easy first"));
+    }
+
+    #[test]
+    fn test_swim_losses() {
+        let json_string = fs::read_to_string("src/workouts_json/expected_workout.json").unwrap();
+        let workout: Workout = serde_json::from_str(&json_string).unwrap();
+
+        let (tcx, losses) = export(&workout);
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(messages[..2], ["workout: Swimming written as sport Other", "workout: 25 meter pool length left out"]);
+        assert_eq!(messages[2], "workout: \"Svømmeøkt i basseng (2)\" shortened to \"Svømmeøkt i bas\"");
+        assert_eq!(messages[3], "workoutSegments[0].workoutSteps[0]: \"warmup\" step written as active");
+        assert_eq!(messages[4], "workoutSegments[0].workoutSteps[0]: stroke \"free\" left out");
+        assert!(tcx.contains("<Workout Sport=\"Other\">"));
+        assert!(tcx.contains("<Duration xsi:type=\"UserInitiated_t\"/>"));
+    }
+
+    #[test]
+    fn test_schema_limits() {
+        let workout = workout(Sport::Running)
+            .warmup(10.mins(), AnyStroke)
+            .repeat(1, |r| r.main(1.km(), AnyStroke))
+            .repeat(100, |r| r.main(100.m(), AnyStroke).timed_rest(30.s()))
+            .repeat(8, |r| r.main(200.m(), AnyStroke).timed_rest(30.s()))
+            .repeat(8, |r| r.main(400.m(), AnyStroke).timed_rest(60.s()))
+            .repeat(4, |r| r.main(800.m(), AnyStroke).timed_rest(90.s()))
+            .repeat(2, |r| r.main(1.km(), AnyStroke).timed_rest(2.mins()))
+            .main(1.km(), AnyStroke)
+            .main(1.km(), AnyStroke)
+            .cooldown(10.mins(), AnyStroke)
+            .build(&clock())
+            .unwrap();
+
+        let (tcx, losses) = export(&workout);
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            [
+                "workoutSegments[0].workoutSteps[0]: \"warmup\" step written as active",
+                "workoutSegments[0].workoutSteps[1]: 1 repetitions written, outside TCX's 2-99",
+                "workoutSegments[0].workoutSteps[2]: 100 repetitions written, outside TCX's 2-99",
+                "workoutSegments[0].workoutSteps[9]: more than 20 steps, devices may refuse the workout",
+                "workoutSegments[0].workoutSteps[9]: \"cooldown\" step written as active",
+            ]
+        );
+        assert!(tcx.contains("<StepId>21</StepId>"));
+    }
+
+    #[test]
+    fn test_import_device_file() {
+        let tcx = r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>
+<TrainingCenterDatabase xmlns="http://www.garmin.com/xmlschemas/TrainingCenterDatabase/v2"
+    xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Workouts>
+    <Workout Sport="Biking">
+      <Name>HR build</Name>
+      <Step xsi:type="Step_t">
+        <StepId>1</StepId>
+        <Duration xsi:type="HeartRateAbove_t">
+          <HeartRate xsi:type="HeartRateInBeatsPerMinute_t"><Value>140</Value></HeartRate>
+        </Duration>
+        <Intensity>Active</Intensity>
+        <Target xsi:type="Speed_t">
+          <SpeedZone xsi:type="PredefinedSpeedZone_t"><Number>3</Number></SpeedZone>
+        </Target>
+      </Step>
+      <Step xsi:type="Step_t">
+        <StepId>2</StepId>
+        <Duration xsi:type="CaloriesBurned_t"><Calories>150</Calories></Duration>
+        <Intensity>Active</Intensity>
+        <Target xsi:type="HeartRate_t">
+          <HeartRateZone xsi:type="CustomHeartRateZone_t">
+            <Low xsi:type="HeartRateInBeatsPerMinute_t"><Value>150</Value></Low>
+            <High xsi:type="HeartRateAsPercentOfMax_t"><Value>85</Value></High>
+          </HeartRateZone>
+        </Target>
+      </Step>
+      <Step xsi:type="Step_t">
+        <StepId>3</StepId>
+        <Duration xsi:type="Laps_t"><Laps>2</Laps></Duration>
+        <Intensity>Resting</Intensity>
+      </Step>
+    </Workout>
+  </Workouts>
+</TrainingCenterDatabase>"#;
+        let (builders, losses) = import(tcx, &Athlete::default()).unwrap();
+        assert_eq!(
+            losses,
+            vec![
+                Loss::new("Workout[0].Step[0]", "speed zone target left out, Garmin Connect has no speed zones"),
+                Loss::new("Workout[0].Step[2]", "Laps_t duration read as lap button"),
+            ]
+        );
+        let workout = builders.into_iter().next().unwrap().build(&clock()).unwrap();
+        assert_eq!(workout.sport_type().sport(), Sport::Cycling);
+        let steps = workout.workout_segments()[0].workout_steps();
+        let WorkoutStep::Single(first) = &steps[0] else { panic!() };
+        assert_eq!(first.end_condition().condition_type_key, Condition::HeartRate);
+        assert_eq!(first.end_condition_compare(), Some(EndConditionCompare::GreaterThan));
+        assert_eq!(first.end_condition_value(), 140.0);
+        let WorkoutStep::Single(second) = &steps[1] else { panic!() };
+        // 85% of 190 bpm
+        assert_eq!(second.target(), Some(StepTarget::HeartRate { min_bpm: 150, max_bpm: 162 }));
+        let WorkoutStep::Single(third) = &steps[2] else { panic!() };
+        assert_eq!(third.end_condition().condition_type_key, Condition::LapButton);
+
+        assert_eq!(import("<TrainingCenterDatabase/>", &Athlete::default()).err(), Some(TcxError::NoWorkouts));
+        assert_eq!(import("<gpx/>", &Athlete::default()).err(), Some(TcxError::NotTcx("gpx".to_string())));
+    }
+}
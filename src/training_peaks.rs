@@ -0,0 +1,558 @@
+//! TrainingPeaks structured workouts, the `structure` JSON coaches' plans come with.
+//!
+//! Intensities are percentages of one threshold for the whole workout (FTP, threshold heart
+//! rate, max heart rate or threshold pace) unless a target names another in its `unit`,
+//! resolved against an [`Athlete`] into absolute Garmin targets. Threshold pace percentages
+//! are of threshold speed, so 105% is faster.
+//! TrainingPeaks repeats don't nest, inner repeats are unrolled.
+use crate::athlete::Athlete;
+use crate::builder::{self, StepsBuilder, WorkoutBuilder};
+use crate::convert::{self, Loss};
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::{PreferredEndConditionUnit, Unit as DistanceUnit};
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::stroke_type::Stroke;
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Structure {
+    structure: Vec<Block>,
+    primary_length_metric: LengthMetric,
+    primary_intensity_metric: IntensityMetric,
+    #[serde(default = "range")]
+    primary_intensity_target_or_range: String,
+}
+
+fn range() -> String {
+    "range".to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum LengthMetric {
+    Duration,
+    Distance,
+}
+
+/// The one absolute target unit, steps or strokes a minute
+const CADENCE: &str = "roundOrStridePerMinute";
+
+/// What the target percentages are of
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+enum IntensityMetric {
+    #[serde(rename = "percentOfFtp")]
+    Ftp,
+    #[serde(rename = "percentOfThresholdHr")]
+    ThresholdHr,
+    #[serde(rename = "percentOfMaxHr")]
+    MaxHr,
+    #[serde(rename = "percentOfThresholdPace")]
+    ThresholdPace,
+}
+
+/// A single step is a block of one repetition holding it
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Block {
+    #[serde(rename = "type")]
+    kind: BlockType,
+    length: Length,
+    steps: Vec<TrainingPeaksStep>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum BlockType {
+    Step,
+    Repetition,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TrainingPeaksStep {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    length: Length,
+    #[serde(default)]
+    targets: Vec<Target>,
+    intensity_class: IntensityClass,
+    /// Ends on the lap button
+    #[serde(default)]
+    open_duration: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+struct Length {
+    value: f64,
+    unit: LengthUnit,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum LengthUnit {
+    Second,
+    Minute,
+    Hour,
+    Meter,
+    Kilometer,
+    Mile,
+    Yard,
+    Repetition,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum IntensityClass {
+    WarmUp,
+    Active,
+    Rest,
+    Recovery,
+    CoolDown,
+}
+
+/// A percentage range of the workout's intensity metric, or of `unit` when it's set
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Target {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    unit: Option<String>,
+}
+
+impl Target {
+    /// Fractions rather than percentages
+    fn range(&self) -> Option<(f64, f64)> {
+        match (self.min_value.or(self.value), self.max_value.or(self.value)) {
+            (Some(min), Some(max)) => Some((min.min(max) / 100.0, min.max(max) / 100.0)),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the workout as TrainingPeaks structure JSON. The intensity metric follows the
+/// first target that has one, targets of other kinds name theirs in `unit`.
+pub fn export(workout: &Workout, athlete: &Athlete) -> (String, Vec<Loss>) {
+    let sport = workout.sport_type().sport();
+    let singles = convert::singles(workout);
+    let metric = singles
+        .iter()
+        .find_map(|single| match single.target() {
+            Some(StepTarget::Power { .. }) => Some(IntensityMetric::Ftp),
+            Some(StepTarget::Pace { .. } | StepTarget::Speed { .. }) => Some(IntensityMetric::ThresholdPace),
+            Some(StepTarget::HeartRate { .. }) => Some(IntensityMetric::ThresholdHr),
+            _ => None,
+        })
+        .unwrap_or(match sport {
+            Sport::Cycling => IntensityMetric::Ftp,
+            Sport::Running => IntensityMetric::ThresholdPace,
+            _ => IntensityMetric::ThresholdHr,
+        });
+    let is = |condition: Condition| {
+        singles.iter().filter(|single| single.end_condition().condition_type_key == condition).count()
+    };
+    let length_metric = if is(Condition::Distance) > is(Condition::Time) {
+        LengthMetric::Distance
+    } else {
+        LengthMetric::Duration
+    };
+
+    let mut exporter = Exporter { athlete, metric, losses: Vec::new() };
+    let mut blocks = Vec::new();
+    for (i, segment) in workout.workout_segments().iter().enumerate() {
+        for (j, step) in segment.workout_steps().iter().enumerate() {
+            let path = format!("workoutSegments[{}].workoutSteps[{}]", i, j);
+            blocks.push(match step {
+                WorkoutStep::Single(single) => Block {
+                    kind: BlockType::Step,
+                    length: Length { value: 1.0, unit: LengthUnit::Repetition },
+                    steps: vec![exporter.step(single, &path)],
+                },
+                WorkoutStep::Repeat(repeat) => Block {
+                    kind: BlockType::Repetition,
+                    length: Length { value: repeat.number_of_iterations() as f64, unit: LengthUnit::Repetition },
+                    steps: exporter.steps(repeat.workout_steps(), &path),
+                },
+            });
+        }
+    }
+
+    let structure = Structure {
+        structure: blocks,
+        primary_length_metric: length_metric,
+        primary_intensity_metric: metric,
+        primary_intensity_target_or_range: range(),
+    };
+    (serde_json::to_string_pretty(&structure).unwrap(), exporter.losses)
+}
+
+struct Exporter<'a> {
+    athlete: &'a Athlete,
+    metric: IntensityMetric,
+    losses: Vec<Loss>,
+}
+
+impl Exporter<'_> {
+    /// The steps of a repeat, with any repeats inside it unrolled
+    fn steps(&mut self, steps: &[WorkoutStep], parent_path: &str) -> Vec<TrainingPeaksStep> {
+        let mut exported = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let path = format!("{}.workoutSteps[{}]", parent_path, i);
+            match step {
+                WorkoutStep::Single(single) => exported.push(self.step(single, &path)),
+                WorkoutStep::Repeat(repeat) => {
+                    let iterations = repeat.number_of_iterations();
+                    let message = format!("nested repeat unrolled into {} copies", iterations);
+                    self.losses.push(Loss::new(&path, message));
+                    let children = self.steps(repeat.workout_steps(), &path);
+                    exported.extend((0..iterations).flat_map(|_| children.clone()));
+                }
+            }
+        }
+        exported
+    }
+
+    fn step(&mut self, step: &ExecutableStepDTO, path: &str) -> TrainingPeaksStep {
+        let value = step.end_condition_value() as f64;
+        let seconds = |value: f64| Length { value, unit: LengthUnit::Second };
+        let (length, open_duration) = match step.end_condition().condition_type_key {
+            Condition::Time | Condition::FixedRest => (seconds(value), false),
+            Condition::FixedRepetition => {
+                self.losses.push(Loss::new(path, format!("send-off of {}s written as a rest that long", value)));
+                (seconds(value), false)
+            }
+            Condition::Distance => (distance(step), false),
+            Condition::LapButton => (seconds(0.0), true),
+            ref condition => {
+                let key = serde_json::to_value(condition).unwrap_or_default();
+                self.losses.push(Loss::new(path, format!("{} end condition written as lap button", key)));
+                (seconds(0.0), true)
+            }
+        };
+
+        let intensity_class = match step.step_type().step_type_key {
+            Step::Warmup => IntensityClass::WarmUp,
+            Step::Cooldown => IntensityClass::CoolDown,
+            Step::Rest => IntensityClass::Rest,
+            Step::Recovery => IntensityClass::Recovery,
+            _ => IntensityClass::Active,
+        };
+
+        TrainingPeaksStep {
+            name: step.description().filter(|description| !description.is_empty()).map(str::to_string),
+            length,
+            targets: self.targets(step, path),
+            intensity_class,
+            open_duration,
+        }
+    }
+
+    /// Targets of another kind than the workout's intensities name what they're a percentage of
+    fn targets(&mut self, step: &ExecutableStepDTO, path: &str) -> Vec<Target> {
+        let athlete = self.athlete;
+        let (metric, range) = match step.target().unwrap_or(StepTarget::NoTarget) {
+            StepTarget::NoTarget => return Vec::new(),
+            StepTarget::Power { min_watts, max_watts } => {
+                (IntensityMetric::Ftp, (athlete.fraction_of_ftp(min_watts), athlete.fraction_of_ftp(max_watts)))
+            }
+            StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km } => (
+                IntensityMetric::ThresholdPace,
+                (athlete.fraction_of_threshold(slowest_secs_per_km), athlete.fraction_of_threshold(fastest_secs_per_km)),
+            ),
+            StepTarget::Speed { min_kph, max_kph } => (
+                IntensityMetric::ThresholdPace,
+                (athlete.fraction_of_threshold(3600.0 / min_kph), athlete.fraction_of_threshold(3600.0 / max_kph)),
+            ),
+            StepTarget::HeartRate { min_bpm, max_bpm } => (
+                IntensityMetric::ThresholdHr,
+                (athlete.fraction_of_threshold_heart_rate(min_bpm), athlete.fraction_of_threshold_heart_rate(max_bpm)),
+            ),
+            StepTarget::Cadence { min, max } => {
+                let unit = Some(CADENCE.to_string());
+                return vec![Target { min_value: Some(min as f64), max_value: Some(max as f64), value: None, unit }];
+            }
+            target => {
+                self.losses.push(Loss::new(path, format!("{:?} target left out, TrainingPeaks has no zones", target)));
+                return Vec::new();
+            }
+        };
+        let unit = match serde_json::to_value(metric) {
+            Ok(serde_json::Value::String(key)) if metric != self.metric => Some(key),
+            _ => None,
+        };
+        let percent = |fraction: f64| (fraction * 1000.0).round() / 10.0;
+        vec![Target { min_value: Some(percent(range.0)), max_value: Some(percent(range.1)), value: None, unit }]
+    }
+}
+
+/// In the unit the step shows its distance in
+fn distance(step: &ExecutableStepDTO) -> Length {
+    let unit = match step.preferred_end_condition_unit().map(PreferredEndConditionUnit::unit) {
+        Some(DistanceUnit::Kilometer) => LengthUnit::Kilometer,
+        Some(DistanceUnit::Mile) => LengthUnit::Mile,
+        Some(DistanceUnit::Yard) => LengthUnit::Yard,
+        _ => LengthUnit::Meter,
+    };
+    let value = step.distance_in_preferred_unit() as f64;
+    Length { value: (value * 1000.0).round() / 1000.0, unit }
+}
+
+/// Reads TrainingPeaks structure JSON, also when it comes double encoded as a string the way
+/// the workout API returns it, into a builder for `sport`
+pub fn import(json: &str, sport: Sport, athlete: &Athlete) -> Result<(WorkoutBuilder, Vec<Loss>), serde_json::Error> {
+    let structure: Structure = match serde_json::from_str::<String>(json) {
+        Ok(inner) => serde_json::from_str(&inner)?,
+        Err(_) => serde_json::from_str(json)?,
+    };
+
+    let mut importer = Importer { athlete, sport: sport.clone(), metric: structure.primary_intensity_metric, losses: Vec::new() };
+    let mut builder = builder::workout(sport);
+    for (i, block) in structure.structure.iter().enumerate() {
+        let path = format!("structure[{}]", i);
+        let mut steps = Vec::new();
+        for (j, step) in block.steps.iter().enumerate() {
+            steps.push(WorkoutStep::Single(importer.step(step, &format!("{}.steps[{}]", path, j))));
+        }
+        match block.kind {
+            BlockType::Repetition => {
+                let iterations = block.length.value.round().clamp(1.0, u8::MAX as f64) as u8;
+                builder.steps().push(WorkoutStep::Repeat(RepeatGroupDTO::new(0, 0, 0, iterations, steps)));
+            }
+            BlockType::Step => builder.steps().extend(steps),
+        }
+    }
+    Ok((builder, importer.losses))
+}
+
+struct Importer<'a> {
+    athlete: &'a Athlete,
+    sport: Sport,
+    metric: IntensityMetric,
+    losses: Vec<Loss>,
+}
+
+impl Importer<'_> {
+    fn step(&mut self, step: &TrainingPeaksStep, path: &str) -> ExecutableStepDTO {
+        let value = step.length.value as f32;
+        let distance = |unit| (Condition::Distance, PreferredEndConditionUnit::new(unit).to_meters(value), Some(unit));
+        let (condition, end_value, distance_unit) = match step.length.unit {
+            _ if step.open_duration => (Condition::LapButton, 0.0, None),
+            LengthUnit::Second => (Condition::Time, value, None),
+            LengthUnit::Minute => (Condition::Time, value * 60.0, None),
+            LengthUnit::Hour => (Condition::Time, value * 3600.0, None),
+            LengthUnit::Repetition => {
+                self.losses.push(Loss::new(path, "step lasting repetitions read as lap button"));
+                (Condition::LapButton, 0.0, None)
+            }
+            LengthUnit::Meter => distance(DistanceUnit::Meter),
+            LengthUnit::Kilometer => distance(DistanceUnit::Kilometer),
+            LengthUnit::Mile => distance(DistanceUnit::Mile),
+            LengthUnit::Yard => distance(DistanceUnit::Yard),
+        };
+
+        let swimming = self.sport == Sport::Swimming;
+        let rest = matches!(step.intensity_class, IntensityClass::Rest | IntensityClass::Recovery);
+        let mut single = if rest {
+            // Swim rests are `fixed.rest` like the ones Garmin Connect makes
+            let condition = if swimming && condition == Condition::Time { Condition::FixedRest } else { condition.clone() };
+            ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, EndCondition::new(condition), end_value)
+        } else {
+            let step_type = match step.intensity_class {
+                IntensityClass::WarmUp => Step::Warmup,
+                IntensityClass::CoolDown => Step::Cooldown,
+                _ => Step::Main,
+            };
+            let stroke = swimming.then_some(Stroke::AnyStroke);
+            convert::active_step(step_type, EndCondition::new(condition.clone()), end_value, stroke)
+        };
+        if let Some(unit) = distance_unit {
+            single.set_distance(value, unit);
+        }
+        single.set_description(step.name.clone().filter(|name| !name.is_empty()));
+
+        let kept = step.targets.iter().enumerate().find_map(|(i, target)| self.target(target, path).map(|target| (i, target)));
+        if let Some((i, target)) = kept {
+            single.set_target(target);
+            if step.targets.len() > 1 {
+                self.losses.push(Loss::new(path, format!("only targets[{}] kept, a Garmin step has one", i)));
+            }
+        }
+        single
+    }
+
+    fn target(&mut self, target: &Target, path: &str) -> Option<StepTarget> {
+        let (low, high) = target.range()?;
+        let metric = match target.unit.as_deref() {
+            None => self.metric,
+            Some(CADENCE) => {
+                let (min, max) = ((low * 100.0).round() as u16, (high * 100.0).round() as u16);
+                return Some(StepTarget::Cadence { min, max });
+            }
+            Some(unit) => match serde_json::from_value(serde_json::Value::from(unit)) {
+                Ok(metric) => metric,
+                Err(_) => {
+                    self.losses.push(Loss::new(path, format!("target in {} left out", unit)));
+                    return None;
+                }
+            },
+        };
+
+        let athlete = self.athlete;
+        match metric {
+            IntensityMetric::Ftp => {
+                Some(StepTarget::Power { min_watts: athlete.watts(low), max_watts: athlete.watts(high) })
+            }
+            IntensityMetric::ThresholdHr => {
+                Some(StepTarget::HeartRate { min_bpm: athlete.heart_rate(low), max_bpm: athlete.heart_rate(high) })
+            }
+            IntensityMetric::MaxHr => {
+                Some(StepTarget::HeartRate { min_bpm: athlete.heart_rate_of_max(low), max_bpm: athlete.heart_rate_of_max(high) })
+            }
+            IntensityMetric::ThresholdPace if self.sport == Sport::Running => Some(StepTarget::Pace {
+                slowest_secs_per_km: athlete.pace(low).round(),
+                fastest_secs_per_km: athlete.pace(high).round(),
+            }),
+            IntensityMetric::ThresholdPace => {
+                let message = format!("{:?} has no threshold pace to resolve the target against", self.sport);
+                self.losses.push(Loss::new(path, message));
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::garmin::timestamp::{self, FixedClock};
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_import_coach_plan() {
+        // The way the workout API hands it over, as a string
+        let json = serde_json::to_string(
+            r#"{
+                "structure": [
+                    {"type": "step", "length": {"value": 1, "unit": "repetition"}, "steps": [
+                        {"name": "Warm up", "length": {"value": 10, "unit": "minute"},
+                         "targets": [{"minValue": 50, "maxValue": 65}], "intensityClass": "warmUp"}
+                    ], "begin": 0, "end": 600},
+                    {"type": "repetition", "length": {"value": 4, "unit": "repetition"}, "steps": [
+                        {"length": {"value": 300, "unit": "second"},
+                         "targets": [{"minValue": 95, "maxValue": 105}, {"minValue": 85, "maxValue": 95, "unit": "roundOrStridePerMinute"}],
+                         "intensityClass": "active"},
+                        {"length": {"value": 180, "unit": "second"},
+                         "targets": [{"value": 20, "unit": "percentOfLactateThreshold"}, {"value": 50}], "intensityClass": "rest"}
+                    ]},
+                    {"type": "step", "length": {"value": 1, "unit": "repetition"}, "steps": [
+                        {"length": {"value": 0, "unit": "second"}, "targets": [], "intensityClass": "coolDown", "openDuration": true}
+                    ]}
+                ],
+                "primaryLengthMetric": "duration",
+                "primaryIntensityMetric": "percentOfFtp",
+                "primaryIntensityTargetOrRange": "range"
+            }"#,
+        )
+        .unwrap();
+        let athlete = Athlete { ftp_watts: 300, ..Athlete::default() };
+
+        let (builder, losses) = import(&json, Sport::Cycling, &athlete).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "structure[1].steps[0]: only targets[0] kept, a Garmin step has one",
+                "structure[1].steps[1]: target in percentOfLactateThreshold left out",
+                "structure[1].steps[1]: only targets[1] kept, a Garmin step has one",
+            ]
+        );
+        let workout = builder.name("Sweet spot").build(&clock()).unwrap();
+        assert_eq!(workout.validate(), vec![]);
+
+        let steps = workout.workout_segments()[0].workout_steps();
+        let WorkoutStep::Single(warmup) = &steps[0] else { panic!() };
+        assert_eq!(warmup.description(), Some("Warm up"));
+        assert_eq!(warmup.end_condition_value(), 600.0);
+        assert_eq!(warmup.target(), Some(StepTarget::Power { min_watts: 150, max_watts: 195 }));
+        let WorkoutStep::Repeat(repeat) = &steps[1] else { panic!() };
+        assert_eq!(repeat.number_of_iterations(), 4);
+        let WorkoutStep::Single(rest) = &repeat.workout_steps()[1] else { panic!() };
+        assert_eq!(rest.step_type().step_type_key, Step::Rest);
+        assert_eq!(rest.target(), Some(StepTarget::Power { min_watts: 150, max_watts: 150 }));
+        let WorkoutStep::Single(cooldown) = &steps[2] else { panic!() };
+        assert_eq!(cooldown.end_condition().condition_type_key, Condition::LapButton);
+    }
+
+    #[test]
+    fn test_export_and_import_back() {
+        let workout = workout(Sport::Running)
+            .warmup(2.km(), AnyStroke)
+            .target(StepTarget::HeartRate { min_bpm: 130, max_bpm: 150 })
+            .repeat(3, |r| {
+                r.main(1.km(), AnyStroke)
+                    .target(StepTarget::Pace { slowest_secs_per_km: 250.0, fastest_secs_per_km: 240.0 })
+                    .timed_rest(2.mins())
+                    .repeat(2, |r| {
+                        r.main(100.m(), AnyStroke).target(StepTarget::Cadence { min: 180, max: 190 }).timed_rest(30.s())
+                    })
+            })
+            .cooldown(lap_button(), AnyStroke)
+            .target(StepTarget::HeartRateZone(1))
+            .build(&clock())
+            .unwrap();
+
+        let (json, losses) = export(&workout, &Athlete::default());
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "workoutSegments[0].workoutSteps[1].workoutSteps[2]: nested repeat unrolled into 2 copies",
+                "workoutSegments[0].workoutSteps[2]: HeartRateZone(1) target left out, TrainingPeaks has no zones",
+            ]
+        );
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["primaryLengthMetric"], "distance");
+        assert_eq!(value["primaryIntensityMetric"], "percentOfThresholdHr");
+        assert_eq!(value["structure"][0]["steps"][0]["length"], serde_json::json!({"value": 2.0, "unit": "kilometer"}));
+        assert_eq!(
+            value["structure"][0]["steps"][0]["targets"],
+            serde_json::json!([{"minValue": 76.5, "maxValue": 88.2}])
+        );
+        assert_eq!(value["structure"][1]["steps"].as_array().unwrap().len(), 6);
+        assert_eq!(
+            value["structure"][1]["steps"][2]["targets"],
+            serde_json::json!([{"minValue": 180.0, "maxValue": 190.0, "unit": "roundOrStridePerMinute"}])
+        );
+        assert_eq!(
+            value["structure"][1]["steps"][0]["targets"],
+            serde_json::json!([{"minValue": 108.0, "maxValue": 112.5, "unit": "percentOfThresholdPace"}])
+        );
+
+        let (builder, losses) = import(&json, Sport::Running, &Athlete::default()).unwrap();
+        assert_eq!(losses, vec![]);
+        let imported = builder.build(&clock()).unwrap();
+        assert_eq!(export(&imported, &Athlete::default()).0, json);
+        let WorkoutStep::Repeat(repeat) = &imported.workout_segments()[0].workout_steps()[1] else { panic!() };
+        let WorkoutStep::Single(interval) = &repeat.workout_steps()[0] else { panic!() };
+        let Some(StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km }) = interval.target() else { panic!() };
+        assert!((slowest_secs_per_km - 250.0).abs() < 1e-9 && (fastest_secs_per_km - 240.0).abs() < 1e-9);
+
+        assert!(import("{\"structure\": []}", Sport::Running, &Athlete::default()).is_err());
+    }
+}
@@ -2,12 +2,13 @@
 Still work in progress.
 
 Todo:
-    - propagating step_order in RepeatGroupDTO
     - proper logic around rest steps
  */
 
+use crate::garmin::timestamp::Clock;
 use crate::garmin::workout::Workout;
 use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
+use crate::garmin::workout_steps::equipment_type::{Equipment, EquipmentType};
 use crate::garmin::workout_steps::step_type::{Step, StepType};
 use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
 use crate::garmin::workout_steps::WorkoutStep;
@@ -16,13 +17,14 @@ use std::cell::Cell;
 use crate::garmin::unit::Unit;
 use crate::garmin::workout_segments::WorkoutSegment;
 use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::Unit as DistanceUnit;
 use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
 use crate::garmin::workout_steps::target_type::TargetType;
 use std::io;
 use std::io::Write;
 use std::str::FromStr;
 
-const STEP_OFFSET: u64 = 9615001364;
+const FIRST_STEP_ID: u64 = 9615001364;
 
 fn get_input<T: FromStr>(prompt: &str) -> T
 where
@@ -48,34 +50,32 @@ where
 pub struct WorkoutBuilder {}
 
 impl WorkoutBuilder {
-    pub fn new_workout(&self) -> Workout {
+    pub fn new_workout(&self, clock: &impl Clock) -> Workout {
         let workout_name = self.get_workout_name();
 
-        let pool_length: f32 = self.get_pool_length();
+        let (pool_length, pool_unit) = self.get_pool_length();
 
         let mut workout_steps: Vec<WorkoutStep> = Vec::new();
 
-        let step_counter: u64 = 1;
+        // Step ids and orders are placeholders, renumber_steps assigns them
         loop {
             let repeat = self.is_repeat();
 
             let step_type = self.get_step_type();
             let stroke_type = self.get_stroke_type();
+            let equipment_type = self.get_equipment_type();
             let end_condition = self.get_end_condition();
             let end_value: f32 = get_input("End value: "); // + &end_condition;
-
-            // I think if end_condition is Distance, PreferredEndConditionUnit must be set
-            // Todo: Verify the combinations, and create a getter corresponding to End condition
-            // let end_unit = ...
+            let is_distance = end_condition.condition_type_key == Condition::Distance;
 
             let target_type = match end_condition.condition_type_key {
                 Condition::LapButton => Some(TargetType::default()),
                 _ => None,
             };
 
-            let executable_step = ExecutableStepDTO::active_step(
-                step_counter + STEP_OFFSET,
-                step_counter as u8,
+            let mut executable_step = ExecutableStepDTO::active_step(
+                0,
+                0,
                 step_type,
                 Cell::new(None),
                 None, //Todo: get description
@@ -83,25 +83,28 @@ impl WorkoutBuilder {
                 end_value,
                 target_type,
                 stroke_type,
+                equipment_type,
             );
+            if is_distance {
+                // Distances are entered in the pool's unit
+                executable_step.set_distance(end_value, pool_unit);
+            }
 
             if repeat {
                 let number_of_iterations: u8 = get_input("Number of iterations: ");
                 let repeat_group = RepeatGroupDTO::new(
-                    step_counter + STEP_OFFSET,
-                    step_counter as u8,
-                    1,
+                    0,
+                    0,
+                    0,
                     number_of_iterations,
                     vec![
-                        executable_step,
-                        self.get_rest_step(
-                            step_counter + STEP_OFFSET,
-                            step_counter as u8,
-                            Cell::new(Some(2)),
-                        ),
+                        WorkoutStep::Single(executable_step),
+                        WorkoutStep::Single(self.get_rest_step(0, 0, Cell::new(None))),
                     ],
                 );
                 workout_steps.push(WorkoutStep::Repeat(repeat_group))
+            } else {
+                workout_steps.push(WorkoutStep::Single(executable_step))
             }
 
             let choice = loop {
@@ -123,17 +126,19 @@ impl WorkoutBuilder {
             }
         }
 
-        Workout::new_swimming_workout(
+        let mut workout = Workout::new_swimming_workout(
             1180301830,
             100441918,
             workout_name,
             None,
-            "123".to_string(),
-            "456".to_string(),
+            clock.now(),
+            clock.now(),
             vec![WorkoutSegment::new(workout_steps)],
             pool_length,
-            Unit::default(),
-        )
+            Unit::from(pool_unit),
+        );
+        workout.renumber_steps(FIRST_STEP_ID);
+        workout
     }
 
     fn get_workout_name(&self) -> String {
@@ -141,17 +146,19 @@ impl WorkoutBuilder {
         get_input("Workout name: ")
     }
 
-    fn get_pool_length(&self) -> f32 {
+    fn get_pool_length(&self) -> (f32, DistanceUnit) {
         println!("Select pool length:");
         println!("1) 25m pool");
         println!("2) 17m pool");
-        println!("3) Custom length");
+        println!("3) 25yd pool");
+        println!("4) Custom length");
         loop {
-            let input: String = get_input("Enter choice (1, 2 or 3):");
+            let input: String = get_input("Enter choice (1, 2, 3 or 4):");
             match input.trim() {
-                "1" => return 25.0,
-                "2" => return 17.0,
-                "3" => return self.get_custom_pool_length(),
+                "1" => return (25.0, DistanceUnit::Meter),
+                "2" => return (17.0, DistanceUnit::Meter),
+                "3" => return (25.0, DistanceUnit::Yard),
+                "4" => return (self.get_custom_pool_length(), DistanceUnit::Meter),
                 _ => println!("Invalid choice, please try again"),
             }
         }
@@ -195,7 +202,7 @@ impl WorkoutBuilder {
                     continue;
                 }
             };
-            return StepType{step_type_key: step_type_key };
+            return StepType::new(step_type_key);
         }
     }
 
@@ -207,6 +214,9 @@ impl WorkoutBuilder {
             println!("3) Back");
             println!("4) Butterfly");
             println!("5) IndividualMedley");
+            println!("6) Drill");
+            println!("7) Mixed");
+            println!("8) AnyStroke");
 
             let input: String = get_input("");
             let stroke = match input.trim() {
@@ -215,12 +225,38 @@ impl WorkoutBuilder {
                 "3" => Stroke::Back,
                 "4" => Stroke::Butterfly,
                 "5" => Stroke::IndividualMedley,
+                "6" => Stroke::Drill,
+                "7" => Stroke::Mixed,
+                "8" => Stroke::AnyStroke,
                 _ => continue,
             };
 
-            break StrokeType {
-                stroke_type_key: Some(stroke),
+            break StrokeType::new(Some(stroke));
+        }
+    }
+
+    fn get_equipment_type(&self) -> EquipmentType {
+        loop {
+            println!("Enter equipment:");
+            println!("0) None");
+            println!("1) Fins");
+            println!("2) Kickboard");
+            println!("3) Paddles");
+            println!("4) Pull buoy");
+            println!("5) Snorkel");
+
+            let input: String = get_input("");
+            let equipment = match input.trim() {
+                "0" => None,
+                "1" => Some(Equipment::Fins),
+                "2" => Some(Equipment::Kickboard),
+                "3" => Some(Equipment::Paddles),
+                "4" => Some(Equipment::PullBuoy),
+                "5" => Some(Equipment::Snorkel),
+                _ => continue,
             };
+
+            break EquipmentType::new(equipment);
         }
     }
 
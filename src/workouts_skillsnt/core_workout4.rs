@@ -1,69 +1,50 @@
-use crate::garmin::unit::Unit;
+use crate::builder::*;
+use crate::garmin::timestamp::{Clock, SystemClock};
 use crate::garmin::workout::Workout;
-use crate::garmin::workout_segments::WorkoutSegment;
-use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
-use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
-use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
-use crate::garmin::workout_steps::step_type::{Step, StepType};
-use crate::garmin::workout_steps::stroke_type::{Stroke, StrokeType};
-use crate::garmin::workout_steps::WorkoutStep;
-use chrono::{NaiveDateTime, Utc};
-use std::cell::Cell;
 
-const STEP_OFFSET: u64 = 9615001364;
+pub fn main(){
+    let workout = workout(&SystemClock);
 
-pub(crate) fn main(){
-    // Set timestamps
+    // Write workout to json file
+    serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap();
+
+}
+
+pub fn workout(clock: &impl Clock) -> Workout {
     // Todo: Figure out if created date can be updated
+    swim()
+        .workout_id(1180301830) //Update
+        .name("Core Workout 4")
+        .pool(25.m())
+        .repeat(2, |r| r.warmup(50.m(), AnyStroke).timed_rest(20.s()))
+        .build(clock)
+        .expect("Core Workout 4 is valid")
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::garmin::timestamp::{self, FixedClock};
 
-    let utc: NaiveDateTime = Utc::now().naive_utc();
+    #[test]
+    fn test_reproducible_workout() {
+        let clock = FixedClock(timestamp::parse("2025-04-06T19:55:57.0").unwrap());
 
-    let workout = Workout::new_swimming_workout(
-        1180301830, //Update
-        100441918,
-        "Core Workout 4".to_string(),
-        None,
-        utc.to_string(),
-        utc.to_string(),
-        vec![
-            WorkoutSegment::new(
-                vec![
-                    WorkoutStep::Repeat(RepeatGroupDTO::new(
-                        1 + STEP_OFFSET,
-                        1,
-                        1,
-                        2,
-                        vec![
-                            ExecutableStepDTO::active_step(
-                                2 + STEP_OFFSET,
-                                2,
-                                StepType::new(Step::Warmup),
-                                Cell::new(Some(1)),
-                                None,
-                                EndCondition::new(Condition::Distance),
-                                50.0,
-                                None,
-                                StrokeType::new(Some(Stroke::AnyStroke))
-                            ),
-                            ExecutableStepDTO::rest_step(
-                                3 + STEP_OFFSET,
-                                3,
-                                Cell::new(Some(1)),
-                                None,
-                                EndCondition::new(Condition::Time),
-                                20.0
-                            )
-                        ]
-                    ))
-                ]
-            )
-        ],
-        25.0,
-        Unit::default()
+        let first = serde_json::to_value(workout(&clock)).unwrap();
+        let second = serde_json::to_value(workout(&clock)).unwrap();
 
-    );
+        assert_eq!(first, second);
+        assert_eq!(first["createdDate"], "2025-04-06T19:55:57.0");
+        assert_eq!(first["updatedDate"], "2025-04-06T19:55:57.0");
 
-    // Write workout to json file
-    serde_json::to_writer_pretty(std::io::stdout(), &workout).unwrap();
+        let repeat = &first["workoutSegments"][0]["workoutSteps"][0];
+        assert_eq!(repeat["stepId"], FIRST_STEP_ID);
+        assert_eq!(repeat["childStepId"], 1);
+        assert_eq!(repeat["workoutSteps"][1]["stepOrder"], 3);
+        assert_eq!(repeat["workoutSteps"][1]["childStepId"], 1);
+        assert_eq!(repeat["workoutSteps"][1]["endCondition"]["conditionTypeKey"], "time");
 
-}
\ No newline at end of file
+        // 2x(50m at 2:00/100m + 20s rest)
+        assert_eq!(first["estimatedDistanceInMeters"], 100.0);
+        assert_eq!(first["estimatedDurationInSecs"], 160);
+    }
+}
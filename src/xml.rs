@@ -0,0 +1,301 @@
+//! Just enough XML for workout files: elements, attributes and text. Namespaces are kept as
+//! part of the names, DOCTYPEs and processing instructions are skipped.
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    pub text: String,
+}
+
+impl Element {
+    pub fn new(name: &str) -> Self {
+        Element { name: name.to_string(), ..Default::default() }
+    }
+
+    pub fn attribute(mut self, name: &str, value: impl ToString) -> Self {
+        self.attributes.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn child(mut self, child: Element) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn text(mut self, text: impl ToString) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// Shorthand for an element holding only text, like `<Name>Intervals</Name>`
+    pub fn with_text(name: &str, text: impl ToString) -> Self {
+        Element::new(name).text(text)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    pub fn child_text(&self, name: &str) -> Option<&str> {
+        self.find(name).map(|child| child.text.trim())
+    }
+
+    /// The document with an XML declaration, indented by two spaces
+    pub fn to_document(&self) -> String {
+        format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}", self)
+    }
+
+    fn write(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        write!(f, "{}<{}", indent, self.name)?;
+        for (key, value) in &self.attributes {
+            write!(f, " {}=\"{}\"", key, escape(value))?;
+        }
+        if self.children.is_empty() && self.text.is_empty() {
+            return writeln!(f, "/>");
+        }
+        write!(f, ">{}", escape(&self.text))?;
+        if !self.children.is_empty() {
+            writeln!(f)?;
+            for child in &self.children {
+                child.write(f, depth + 1)?;
+            }
+            write!(f, "{}", indent)?;
+        }
+        writeln!(f, "</{}>", self.name)
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct XmlError {
+    /// Byte offset into the document
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for XmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid XML at byte {}: {}", self.offset, self.message)
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+/// Parses the document's root element
+pub(crate) fn parse(text: &str) -> Result<Element, XmlError> {
+    let mut parser = Parser { text, pos: 0 };
+    parser.skip_misc()?;
+    let root = parser.element()?;
+    parser.skip_misc()?;
+    if parser.pos < text.len() {
+        return Err(parser.error("content after the root element"));
+    }
+    Ok(root)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> XmlError {
+        XmlError { offset: self.pos, message: message.to_string() }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), XmlError> {
+        match self.rest().find(end) {
+            Some(index) => {
+                self.pos += index + end.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("missing {}", end))),
+        }
+    }
+
+    /// Whitespace, comments, the declaration and DOCTYPEs
+    fn skip_misc(&mut self) -> Result<(), XmlError> {
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if self.rest().starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if self.rest().starts_with("<!") {
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> Result<String, XmlError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '='))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += end;
+        Ok(rest[..end].to_string())
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), XmlError> {
+        if !self.rest().starts_with(token) {
+            return Err(self.error(&format!("expected {}", token)));
+        }
+        self.pos += token.len();
+        Ok(())
+    }
+
+    fn element(&mut self) -> Result<Element, XmlError> {
+        self.expect("<")?;
+        let mut element = Element::new(&self.name()?);
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(element);
+            }
+            if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = self.rest().chars().next().filter(|c| *c == '"' || *c == '\'');
+            let quote = quote.ok_or_else(|| self.error("expected a quoted attribute value"))?;
+            self.pos += 1;
+            let end = self.rest().find(quote).ok_or_else(|| self.error("unterminated attribute value"))?;
+            let value = unescape(&self.rest()[..end]).map_err(|message| self.error(&message))?;
+            self.pos += end + 1;
+            element.attributes.push((key, value));
+        }
+
+        loop {
+            let end = self.rest().find('<').ok_or_else(|| self.error(&format!("missing </{}>", element.name)))?;
+            let text = unescape(&self.rest()[..end]).map_err(|message| self.error(&message))?;
+            element.text.push_str(&text);
+            self.pos += end;
+
+            if self.rest().starts_with("</") {
+                self.pos += 2;
+                let name = self.name()?;
+                if name != element.name {
+                    return Err(self.error(&format!("expected </{}>, found </{}>", element.name, name)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                // Indentation between children isn't content
+                if !element.children.is_empty() && element.text.trim().is_empty() {
+                    element.text.clear();
+                }
+                return Ok(element);
+            } else if self.rest().starts_with("<![CDATA[") {
+                self.pos += 9;
+                let end = self.rest().find("]]>").ok_or_else(|| self.error("unterminated CDATA"))?;
+                element.text.push_str(&self.rest()[..end]);
+                self.pos += end + 3;
+            } else if self.rest().starts_with("<!--") || self.rest().starts_with("<?") {
+                self.skip_misc()?;
+            } else {
+                element.children.push(self.element()?);
+            }
+        }
+    }
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or("unterminated entity")? + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "amp" => '&',
+            "lt" => '<',
+            "gt" => '>',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()),
+                };
+                code.and_then(char::from_u32).ok_or(format!("unknown entity &{};", entity))?
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_write() {
+        let text = r#"<?xml version="1.0"?>
+<!-- exported -->
+<workout_file>
+    <name>Over &amp; unders</name>
+    <workout>
+        <SteadyState Duration='300' Power="0.88"/>
+        <textevent message="&#x263A; go"><![CDATA[<raw>]]></textevent>
+    </workout>
+</workout_file>
+"#;
+        let root = parse(text).unwrap();
+        assert_eq!(root.child_text("name"), Some("Over & unders"));
+        let workout = root.find("workout").unwrap();
+        assert_eq!(workout.children[0].get("Duration"), Some("300"));
+        assert_eq!(workout.children[1].get("message"), Some("\u{263A} go"));
+        assert_eq!(workout.children[1].text, "<raw>");
+
+        assert_eq!(parse(&root.to_document()).unwrap(), root);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("<a><b></a>").unwrap_err().message, "expected </b>, found </a>");
+        assert_eq!(parse("<a x=1/>").unwrap_err().message, "expected a quoted attribute value");
+        assert_eq!(parse("<a>&nbsp;</a>").unwrap_err().message, "unknown entity &nbsp;");
+        assert_eq!(parse("<a></a><b/>").unwrap_err().message, "content after the root element");
+    }
+}
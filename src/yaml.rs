@@ -0,0 +1,374 @@
+//! Just enough YAML for workout definition files: block mappings and sequences, plain and
+//! quoted scalars, `|` and `>` block scalars, comments, and documents separated by `---` or
+//! ended by `...`. Anchors, tags and flow collections are rejected rather than misread.
+use crate::notation::ParseError;
+
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum Value {
+    Scalar(String),
+    List(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+/// A value and where it starts, 1-based
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Node {
+    pub value: Value,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Node {
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, self.column, message)
+    }
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    content: &'a str,
+    /// Blank or a comment, only block scalars keep these
+    ignorable: bool,
+}
+
+/// One node per document, documents without content are skipped
+pub(crate) fn parse(text: &str) -> Result<Vec<Node>, ParseError> {
+    let mut documents = vec![Vec::new()];
+    for (i, raw) in text.lines().enumerate() {
+        let content = raw.trim_start_matches(' ');
+        let indent = raw.len() - content.len();
+        let content = content.trim_end();
+        if indent == 0 && (content == "---" || content == "...") {
+            documents.push(Vec::new());
+            continue;
+        }
+        let ignorable = content.is_empty() || content.starts_with('#');
+        if content.starts_with('\t') && !ignorable {
+            return Err(ParseError::new(i + 1, indent + 1, "tabs can't indent YAML, use spaces"));
+        }
+        documents.last_mut().unwrap().push(Line { number: i + 1, indent, content, ignorable });
+    }
+
+    let mut nodes = Vec::new();
+    for lines in documents {
+        let mut parser = Parser { lines, pos: 0 };
+        parser.skip_ignorable();
+        let Some(first) = parser.lines.get(parser.pos) else {
+            continue;
+        };
+        let indent = first.indent;
+        nodes.push(parser.block(indent)?);
+        if let Some(line) = parser.lines.get(parser.pos) {
+            return Err(ParseError::new(line.number, line.indent + 1, "unexpected indentation"));
+        }
+    }
+    if nodes.is_empty() {
+        return Err(ParseError::new(1, 1, "empty file"));
+    }
+    Ok(nodes)
+}
+
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_ignorable(&mut self) {
+        while self.lines.get(self.pos).is_some_and(|line| line.ignorable) {
+            self.pos += 1;
+        }
+    }
+
+    /// The mapping, sequence or scalar starting at the current line, indented by `indent`
+    fn block(&mut self, indent: usize) -> Result<Node, ParseError> {
+        let line = &self.lines[self.pos];
+        if is_item(line.content) {
+            self.sequence(indent)
+        } else if split_key(line.content).is_some() {
+            self.mapping(indent)
+        } else {
+            let (number, column, content) = (line.number, line.indent + 1, line.content);
+            self.pos += 1;
+            self.scalar(content, number, column, indent)
+        }
+    }
+
+    fn sequence(&mut self, indent: usize) -> Result<Node, ParseError> {
+        let (line, column) = (self.lines[self.pos].number, indent + 1);
+        let mut items = Vec::new();
+        while let Some(current) = self.lines.get(self.pos)
+            && current.indent == indent
+            && is_item(current.content)
+        {
+            let (number, rest) = (current.number, current.content[1..].trim_start());
+            if rest.is_empty() {
+                self.pos += 1;
+                self.skip_ignorable();
+                items.push(self.nested(indent, number, indent + 2)?);
+            } else {
+                // `- key: value` starts a mapping indented to where `key` is
+                let item_indent = indent + current.content.len() - rest.len();
+                let current = &mut self.lines[self.pos];
+                current.indent = item_indent;
+                current.content = rest;
+                items.push(self.block(item_indent)?);
+            }
+        }
+        Ok(Node { value: Value::List(items), line, column })
+    }
+
+    fn mapping(&mut self, indent: usize) -> Result<Node, ParseError> {
+        let (line, column) = (self.lines[self.pos].number, indent + 1);
+        let mut entries: Vec<(String, Node)> = Vec::new();
+        while let Some(current) = self.lines.get(self.pos)
+            && current.indent == indent
+            && !is_item(current.content)
+        {
+            let (number, content) = (current.number, current.content);
+            let Some((key, rest)) = split_key(content) else {
+                return Err(ParseError::new(number, indent + 1, "expected `key: value`"));
+            };
+            let key = unquote(key).map_err(|message| ParseError::new(number, indent + 1, message))?;
+            if entries.iter().any(|(existing, _)| *existing == key) {
+                return Err(ParseError::new(number, indent + 1, format!("`{}` given twice", key)));
+            }
+            self.pos += 1;
+            let value_column = indent + content.len() - rest.len() + 1;
+            let value = if rest.is_empty() {
+                self.skip_ignorable();
+                // A sequence may sit at the same indentation as its key
+                match self.lines.get(self.pos) {
+                    Some(next) if next.indent == indent && is_item(next.content) => self.sequence(indent)?,
+                    _ => self.nested(indent, number, value_column)?,
+                }
+            } else {
+                self.scalar(rest, number, value_column, indent)?
+            };
+            entries.push((key, value));
+        }
+        Ok(Node { value: Value::Map(entries), line, column })
+    }
+
+    /// The block under a `key:` or `-` on its own, an empty scalar if nothing is indented
+    /// further than `parent`
+    fn nested(&mut self, parent: usize, line: usize, column: usize) -> Result<Node, ParseError> {
+        match self.lines.get(self.pos) {
+            Some(next) if next.indent > parent => {
+                let indent = next.indent;
+                self.block(indent)
+            }
+            _ => Ok(Node { value: Value::Scalar(String::new()), line, column }),
+        }
+    }
+
+    /// The scalar ending the line before the current one, the lines after it too if it's a
+    /// block scalar
+    fn scalar(&mut self, text: &'a str, line: usize, column: usize, parent: usize) -> Result<Node, ParseError> {
+        let error = |message: &str| ParseError::new(line, column, message);
+        let text = strip_comment(text);
+        if text != "|" && text != ">" {
+            self.skip_ignorable();
+        }
+        let value = match text.chars().next() {
+            Some('|') | Some('>') if text.len() == 1 => self.block_scalar(text == ">", parent),
+            Some('[') | Some('{') => return Err(error("flow collections aren't supported, use one item per line")),
+            Some('&') | Some('*') | Some('!') => return Err(error("anchors, aliases and tags aren't supported")),
+            _ => unquote(text).map_err(|message| error(&message))?,
+        };
+        Ok(Node { value: Value::Scalar(value), line, column })
+    }
+
+    /// The lines indented further than `parent`, comments and blank lines included, joined by
+    /// new lines or, folded, by spaces with blank lines kept as new lines
+    fn block_scalar(&mut self, folded: bool, parent: usize) -> String {
+        let mut lines = Vec::new();
+        let mut indent = None;
+        while let Some(line) = self.lines.get(self.pos)
+            && (line.content.is_empty() || line.indent > parent)
+        {
+            if line.content.is_empty() {
+                lines.push(String::new());
+            } else {
+                let indent = *indent.get_or_insert(line.indent);
+                lines.push(format!("{}{}", " ".repeat(line.indent.saturating_sub(indent)), line.content));
+            }
+            self.pos += 1;
+        }
+        self.skip_ignorable();
+        while lines.last().is_some_and(String::is_empty) {
+            lines.pop();
+        }
+
+        if !folded {
+            return lines.join("\n");
+        }
+        let mut text = String::new();
+        for line in lines {
+            if line.is_empty() {
+                text.push('\n');
+            } else {
+                if !text.is_empty() && !text.ends_with('\n') {
+                    text.push(' ');
+                }
+                text.push_str(&line);
+            }
+        }
+        text
+    }
+}
+
+fn is_item(content: &str) -> bool {
+    content == "-" || content.starts_with("- ")
+}
+
+/// Splits `key: value` at the first colon outside quotes that ends the line or is followed
+/// by a space
+fn split_key(content: &str) -> Option<(&str, &str)> {
+    for (i, c) in outside_quotes(content) {
+        match c {
+            '#' if i > 0 && content[..i].ends_with(' ') => return None,
+            ':' => {
+                let rest = &content[i + 1..];
+                if rest.is_empty() || rest.starts_with(' ') {
+                    return Some((content[..i].trim_end(), strip_comment(rest.trim_start())));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Drops a ` # comment` outside quotes
+fn strip_comment(text: &str) -> &str {
+    match outside_quotes(text).find(|(i, c)| *c == '#' && (*i == 0 || text[..*i].ends_with(' '))) {
+        Some((i, _)) => text[..i].trim_end(),
+        None => text,
+    }
+}
+
+/// The characters after a quoted scalar `text` starts with, or all of them if it isn't
+/// quoted, with their byte offsets
+fn outside_quotes(text: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let mut chars = text.char_indices().peekable();
+    let mut quote = chars.next_if(|(_, c)| *c == '"' || *c == '\'').map(|(_, c)| c);
+    std::iter::from_fn(move || {
+        loop {
+            let (i, c) = chars.next()?;
+            match quote {
+                None => return Some((i, c)),
+                // `\"` in double quotes and `''` in single quotes don't close them
+                Some('"') if c == '\\' => {
+                    chars.next();
+                }
+                Some('\'') if c == '\'' && chars.next_if(|(_, c)| *c == '\'').is_some() => {}
+                Some(open) if c == open => quote = None,
+                Some(_) => {}
+            }
+        }
+    })
+}
+
+fn unquote(text: &str) -> Result<String, String> {
+    if let Some(inner) = text.strip_prefix('\'') {
+        let inner = inner.strip_suffix('\'').ok_or("unterminated quote")?;
+        return Ok(inner.replace("''", "'"));
+    }
+    let Some(inner) = text.strip_prefix('"') else {
+        return Ok(text.to_string());
+    };
+    let inner = inner.strip_suffix('"').ok_or("unterminated quote")?;
+    let mut value = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some(c @ ('"' | '\\' | '/')) => value.push(c),
+            Some(c) => return Err(format!("unknown escape `\\{}`", c)),
+            None => return Err("unterminated quote".to_string()),
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(text: &str) -> Value {
+        Value::Scalar(text.to_string())
+    }
+
+    fn values(node: &Node) -> serde_json::Value {
+        match &node.value {
+            Value::Scalar(text) => text.clone().into(),
+            Value::List(items) => items.iter().map(values).collect(),
+            Value::Map(entries) => entries.iter().map(|(key, node)| (key.clone(), values(node))).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let text = "---\n# Tuesday\nname: 'Masters: Tuesday'\nnotes: |\n  Turns.\n    Tight.\nsets:\n- 400 free wu  # easy\n- reps: 4\n  sets:\n    - swim: \"100 #1\"\n    -\n      rest: 20\nempty:\n";
+        let node = parse(text).unwrap().remove(0);
+        assert_eq!(
+            values(&node),
+            serde_json::json!({
+                "name": "Masters: Tuesday",
+                "notes": "Turns.\n  Tight.",
+                "sets": ["400 free wu", {"reps": "4", "sets": [{"swim": "100 #1"}, {"rest": "20"}]}],
+                "empty": "",
+            })
+        );
+
+        let Value::Map(entries) = &node.value else { panic!() };
+        let Value::List(sets) = &entries[2].1.value else { panic!() };
+        assert_eq!((sets[1].line, sets[1].column), (9, 3));
+        let Value::Map(set) = &sets[1].value else { panic!() };
+        assert_eq!((&set[0].1.value, set[0].1.line, set[0].1.column), (&scalar("4"), 9, 9));
+    }
+
+    #[test]
+    fn test_block_scalars_keep_blank_and_comment_lines() {
+        let text = "notes: |\n  # Main set\n\n  Hold pace.\n# comment\nfolded: >\n  one\n  two\n\n  three\n\nname: A\n";
+        assert_eq!(
+            values(&parse(text).unwrap()[0]),
+            serde_json::json!({"notes": "# Main set\n\nHold pace.", "folded": "one two\nthree", "name": "A"})
+        );
+    }
+
+    #[test]
+    fn test_quotes() {
+        let text = "note: \"a: b\"\n'it''s: here': 'x # y'\n\"say \\\"hi: #1\\\"\": z # comment\nplain: a:b\n";
+        assert_eq!(
+            values(&parse(text).unwrap()[0]),
+            serde_json::json!({"note": "a: b", "it's: here": "x # y", "say \"hi: #1\"": "z", "plain": "a:b"})
+        );
+    }
+
+    #[test]
+    fn test_documents() {
+        let text = "---\nname: A\n...\n---\nname: B\n---\n# nothing\n";
+        let names: Vec<serde_json::Value> = parse(text).unwrap().iter().map(values).collect();
+        assert_eq!(names, vec![serde_json::json!({"name": "A"}), serde_json::json!({"name": "B"})]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |text: &str| parse(text).err().map(|e| e.to_string());
+        assert_eq!(error("name: a\nname: b"), Some("2:1: `name` given twice".to_string()));
+        assert_eq!(error("sets:\n  - a\n   - b"), Some("3:4: unexpected indentation".to_string()));
+        assert_eq!(error("sets: [a, b]"), Some("1:7: flow collections aren't supported, use one item per line".to_string()));
+        assert_eq!(error("name: \"open"), Some("1:7: unterminated quote".to_string()));
+        assert_eq!(error("a: 1\n\tb: 2"), Some("2:1: tabs can't indent YAML, use spaces".to_string()));
+        assert_eq!(error("# nothing\n"), Some("1:1: empty file".to_string()));
+        assert_eq!(error("---\n...\n"), Some("1:1: empty file".to_string()));
+    }
+}
@@ -0,0 +1,631 @@
+//! Zwift workout files (`.zwo`) for indoor bike and run sessions.
+//!
+//! ZWO intensities are fractions of FTP, or of threshold speed for runs, so both directions
+//! resolve them against an [`Athlete`]. Garmin steps have one target and no ramps, ZWO has
+//! no heart rate targets and only repeats on/off pairs; whatever doesn't fit either way is
+//! returned as a [`Loss`].
+use crate::athlete::Athlete;
+use crate::builder::{self, StepsBuilder, WorkoutBuilder};
+use crate::convert::{self, Loss};
+use crate::garmin::sport_type::Sport;
+use crate::garmin::workout::Workout;
+use crate::garmin::workout_steps::end_condition::{Condition, EndCondition};
+use crate::garmin::workout_steps::executable_step_dto::ExecutableStepDTO;
+use crate::garmin::workout_steps::preferred_end_condition_unit::Unit as DistanceUnit;
+use crate::garmin::workout_steps::repeat_group_dto::RepeatGroupDTO;
+use crate::garmin::workout_steps::step_type::Step;
+use crate::garmin::workout_steps::target_type::StepTarget;
+use crate::garmin::workout_steps::WorkoutStep;
+use crate::xml::{self, Element, XmlError};
+use std::cell::Cell;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ZwoError {
+    /// Zwift only has bike and run workouts
+    UnsupportedSport(Sport),
+    Xml(XmlError),
+    /// The root element isn't `workout_file`
+    NotAWorkoutFile(String),
+    MissingAttribute { path: String, attribute: String },
+    InvalidNumber { path: String, attribute: String, value: String },
+    /// The athlete's FTP or threshold pace is zero, so relative intensities mean nothing
+    ZeroThreshold(&'static str),
+}
+
+impl fmt::Display for ZwoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZwoError::UnsupportedSport(sport) => write!(f, "{:?} workouts can't be ZWO files, only cycling and running", sport),
+            ZwoError::Xml(error) => write!(f, "{}", error),
+            ZwoError::NotAWorkoutFile(root) => write!(f, "root element is <{}>, not <workout_file>", root),
+            ZwoError::MissingAttribute { path, attribute } => write!(f, "{}: missing {}", path, attribute),
+            ZwoError::InvalidNumber { path, attribute, value } => {
+                write!(f, "{}: {}=\"{}\" is not a number", path, attribute, value)
+            }
+            ZwoError::ZeroThreshold(threshold) => write!(f, "the athlete's {} is zero", threshold),
+        }
+    }
+}
+
+impl std::error::Error for ZwoError {}
+
+/// Writes a cycling or running workout as a ZWO file.
+///
+/// Repeats that aren't an on/off pair are unrolled, target ranges on steady steps are
+/// narrowed to their middle and heart rate, zone and lap button steps are left out.
+pub fn export(workout: &Workout, athlete: &Athlete) -> Result<(String, Vec<Loss>), ZwoError> {
+    let run = match workout.sport_type().sport() {
+        Sport::Cycling => false,
+        Sport::Running => true,
+        sport => return Err(ZwoError::UnsupportedSport(sport)),
+    };
+    check_threshold(athlete, run)?;
+    // Durations are either all seconds or, for runs, all meters
+    let singles = convert::singles(workout);
+    let distance = run
+        && !singles.is_empty()
+        && singles.iter().all(|single| single.end_condition().condition_type_key == Condition::Distance);
+
+    let mut exporter = Exporter { athlete, run, distance, losses: Vec::new() };
+    let mut steps = Element::new("workout");
+    for (i, segment) in workout.workout_segments().iter().enumerate() {
+        let path = format!("workoutSegments[{}]", i);
+        steps.children.extend(exporter.steps(segment.workout_steps(), &path));
+    }
+
+    let mut file = Element::new("workout_file").child(Element::with_text("name", workout.workout_name()));
+    if let Some(description) = workout.description().filter(|description| !description.is_empty()) {
+        file = file.child(Element::with_text("description", description));
+    }
+    file = file.child(Element::with_text("sportType", if run { "run" } else { "bike" }));
+    if distance {
+        file = file.child(Element::with_text("durationType", "distance"));
+    }
+    let file = file.child(Element::new("tags")).child(steps);
+    Ok((file.to_document(), exporter.losses))
+}
+
+/// Intensity as a range of fractions and an optional cadence range
+type Intensity = (Option<(f64, f64)>, Option<(u16, u16)>);
+
+struct Exporter<'a> {
+    athlete: &'a Athlete,
+    run: bool,
+    distance: bool,
+    losses: Vec<Loss>,
+}
+
+impl Exporter<'_> {
+    fn steps(&mut self, steps: &[WorkoutStep], parent_path: &str) -> Vec<Element> {
+        let mut elements = Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let path = format!("{}.workoutSteps[{}]", parent_path, i);
+            match step {
+                WorkoutStep::Single(single) => elements.extend(self.single(single, &path)),
+                WorkoutStep::Repeat(repeat) => elements.extend(self.repeat(repeat, &path)),
+            }
+        }
+        elements
+    }
+
+    /// Seconds, or meters in a distance workout
+    fn duration(&self, step: &ExecutableStepDTO) -> Option<f32> {
+        match step.end_condition().condition_type_key {
+            Condition::Distance if self.distance => Some(step.end_condition_value()),
+            Condition::Time | Condition::FixedRest | Condition::FixedRepetition if !self.distance => {
+                Some(step.end_condition_value())
+            }
+            _ => None,
+        }
+    }
+
+    fn intensity(&self, step: &ExecutableStepDTO) -> Result<Intensity, String> {
+        let threshold = |secs_per_km: f64| self.athlete.fraction_of_threshold(secs_per_km);
+        match step.target() {
+            None | Some(StepTarget::NoTarget) => Ok((None, None)),
+            Some(StepTarget::Power { min_watts, max_watts }) if !self.run => Ok((
+                Some((self.athlete.fraction_of_ftp(min_watts), self.athlete.fraction_of_ftp(max_watts))),
+                None,
+            )),
+            Some(StepTarget::Pace { slowest_secs_per_km, fastest_secs_per_km }) if self.run => {
+                Ok((Some((threshold(slowest_secs_per_km), threshold(fastest_secs_per_km))), None))
+            }
+            Some(StepTarget::Speed { min_kph, max_kph }) if self.run => {
+                Ok((Some((threshold(3600.0 / min_kph), threshold(3600.0 / max_kph))), None))
+            }
+            Some(StepTarget::Cadence { min, max }) => Ok((None, Some((min, max)))),
+            Some(target) => Err(format!("{} target left out", describe(target))),
+        }
+    }
+
+    fn single(&mut self, step: &ExecutableStepDTO, path: &str) -> Option<Element> {
+        let Some(duration) = self.duration(step) else {
+            let condition = serde_json::to_value(&step.end_condition().condition_type_key).unwrap_or_default();
+            let unit = if self.distance { "distance" } else { "time" };
+            self.losses.push(Loss::new(path, format!("step ending on {} left out, ZWO steps last a {}", condition, unit)));
+            return None;
+        };
+        let (fractions, cadence) = self.intensity(step).unwrap_or_else(|message| {
+            self.losses.push(Loss::new(path, message));
+            (None, None)
+        });
+
+        if let (Step::Warmup | Step::Cooldown, Some((low, high))) = (&step.step_type().step_type_key, fractions)
+            && (high - low).abs() >= 0.0005
+        {
+            let (start, end) = if step.step_type().step_type_key == Step::Cooldown { (high, low) } else { (low, high) };
+            self.losses.push(Loss::new(
+                path,
+                format!("{}-{}% range written as a ramp from {}% to {}%", percent(low), percent(high), percent(start), percent(end)),
+            ));
+        }
+        let mut element = match (&step.step_type().step_type_key, fractions) {
+            (_, None) => Element::new("FreeRide").attribute("Duration", duration.round()),
+            (Step::Warmup, Some((low, high))) => Element::new("Warmup")
+                .attribute("Duration", duration.round())
+                .attribute("PowerLow", fraction(low))
+                .attribute("PowerHigh", fraction(high)),
+            // Cooldowns ramp down from `PowerLow` to `PowerHigh`
+            (Step::Cooldown, Some((low, high))) => Element::new("Cooldown")
+                .attribute("Duration", duration.round())
+                .attribute("PowerLow", fraction(high))
+                .attribute("PowerHigh", fraction(low)),
+            (_, Some(range)) => Element::new("SteadyState")
+                .attribute("Duration", duration.round())
+                .attribute("Power", fraction(self.narrow(range, path))),
+        };
+        match cadence {
+            Some((min, max)) if min == max => element = element.attribute("Cadence", min),
+            Some((min, max)) => element = element.attribute("CadenceLow", min).attribute("CadenceHigh", max),
+            None => {}
+        }
+        Some(with_note(element, step))
+    }
+
+    fn narrow(&mut self, (low, high): (f64, f64), path: &str) -> f64 {
+        if (high - low).abs() < 0.0005 {
+            return low;
+        }
+        let middle = (low + high) / 2.0;
+        self.losses.push(Loss::new(
+            path,
+            format!("{}-{}% range narrowed to {}%", percent(low), percent(high), percent(middle)),
+        ));
+        middle
+    }
+
+    fn repeat(&mut self, repeat: &RepeatGroupDTO, path: &str) -> Vec<Element> {
+        if let Some(intervals) = self.intervals(repeat, path) {
+            return vec![intervals];
+        }
+        let iterations = repeat.number_of_iterations();
+        self.losses.push(Loss::new(
+            path,
+            format!("repeat unrolled into {} copies, ZWO only repeats on/off pairs", iterations),
+        ));
+        let children = self.steps(repeat.workout_steps(), path);
+        (0..iterations).flat_map(|_| children.clone()).collect()
+    }
+
+    /// An on/off pair with an on target becomes `IntervalsT`, an untargeted off step is a rest
+    fn intervals(&mut self, repeat: &RepeatGroupDTO, path: &str) -> Option<Element> {
+        let [WorkoutStep::Single(on), WorkoutStep::Single(off)] = repeat.workout_steps() else {
+            return None;
+        };
+        let (Some(on_duration), Some(off_duration)) = (self.duration(on), self.duration(off)) else {
+            return None;
+        };
+        let (Ok((Some(on_range), None)), Ok((off_range, None))) = (self.intensity(on), self.intensity(off)) else {
+            return None;
+        };
+        if on.step_type().step_type_key == Step::Rest {
+            return None;
+        }
+
+        let mut element = Element::new("IntervalsT")
+            .attribute("Repeat", repeat.number_of_iterations())
+            .attribute("OnDuration", on_duration.round())
+            .attribute("OffDuration", off_duration.round())
+            .attribute("OnPower", fraction(self.narrow(on_range, &format!("{}.workoutSteps[0]", path))));
+        if let Some(off_range) = off_range {
+            let off_power = self.narrow(off_range, &format!("{}.workoutSteps[1]", path));
+            element = element.attribute("OffPower", fraction(off_power));
+        }
+        Some(with_note(element, on))
+    }
+}
+
+fn with_note(element: Element, step: &ExecutableStepDTO) -> Element {
+    match step.description().filter(|description| !description.is_empty()) {
+        Some(description) => {
+            element.child(Element::new("textevent").attribute("timeoffset", 0).attribute("message", description))
+        }
+        None => element,
+    }
+}
+
+fn fraction(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+fn percent(value: f64) -> f64 {
+    (value * 100.0).round()
+}
+
+fn describe(target: StepTarget) -> String {
+    match target {
+        StepTarget::NoTarget => "no".to_string(),
+        StepTarget::HeartRateZone(zone) => format!("heart rate zone {}", zone),
+        StepTarget::HeartRate { min_bpm, max_bpm } => format!("heart rate {}-{} bpm", min_bpm, max_bpm),
+        StepTarget::PowerZone(zone) => format!("power zone {}", zone),
+        StepTarget::Power { min_watts, max_watts } => format!("run power {}-{} W", min_watts, max_watts),
+        StepTarget::Pace { .. } => "bike pace".to_string(),
+        StepTarget::Speed { min_kph, max_kph } => format!("bike speed {:.1}-{:.1} km/h", min_kph, max_kph),
+        StepTarget::Cadence { min, max } => format!("cadence {}-{}", min, max),
+    }
+}
+
+/// Reads a ZWO file into a builder, named and described like the file. Ramps become target
+/// ranges and cadence next to a power target is dropped.
+pub fn import(text: &str, athlete: &Athlete) -> Result<(WorkoutBuilder, Vec<Loss>), ZwoError> {
+    let root = xml::parse(text).map_err(ZwoError::Xml)?;
+    if root.name != "workout_file" {
+        return Err(ZwoError::NotAWorkoutFile(root.name));
+    }
+    let run = root.child_text("sportType").is_some_and(|sport| sport.eq_ignore_ascii_case("run"));
+    let distance = root.child_text("durationType").is_some_and(|unit| unit.eq_ignore_ascii_case("distance"));
+    check_threshold(athlete, run)?;
+
+    let mut builder = builder::workout(if run { Sport::Running } else { Sport::Cycling })
+        .name(root.child_text("name").unwrap_or_default());
+    if let Some(description) = root.child_text("description").filter(|description| !description.is_empty()) {
+        builder = builder.description(description);
+    }
+
+    let mut importer = Importer { athlete, run, distance, losses: Vec::new() };
+    for (i, element) in root.find("workout").map(|workout| workout.children.as_slice()).unwrap_or_default().iter().enumerate() {
+        let path = format!("workout[{}] <{}>", i, element.name);
+        if let Some(step) = importer.element(element, &path)? {
+            builder.steps().push(step);
+        }
+    }
+    Ok((builder, importer.losses))
+}
+
+fn check_threshold(athlete: &Athlete, run: bool) -> Result<(), ZwoError> {
+    let pace = athlete.threshold_pace_secs_per_km;
+    if run && (pace <= 0.0 || pace.is_nan()) {
+        Err(ZwoError::ZeroThreshold("threshold pace"))
+    } else if !run && athlete.ftp_watts == 0 {
+        Err(ZwoError::ZeroThreshold("FTP"))
+    } else {
+        Ok(())
+    }
+}
+
+struct Importer<'a> {
+    athlete: &'a Athlete,
+    run: bool,
+    distance: bool,
+    losses: Vec<Loss>,
+}
+
+impl Importer<'_> {
+    fn element(&mut self, element: &Element, path: &str) -> Result<Option<WorkoutStep>, ZwoError> {
+        let single = match element.name.as_str() {
+            "SteadyState" => {
+                let power = match optional(element, path, "Power")? {
+                    Some(power) => Some((power, power)),
+                    None => self.range(element, path, "PowerLow", "PowerHigh")?,
+                };
+                self.single(Step::Main, element, path, "Duration", power, "Cadence")?
+            }
+            "Warmup" | "Cooldown" | "Ramp" => {
+                let step_type = match element.name.as_str() {
+                    "Warmup" => Step::Warmup,
+                    "Cooldown" => Step::Cooldown,
+                    _ => Step::Main,
+                };
+                let power = self.range(element, path, "PowerLow", "PowerHigh")?;
+                if let Some((low, high)) = power
+                    && (high - low).abs() >= 0.0005
+                {
+                    let (start, end) = if step_type == Step::Cooldown { (high, low) } else { (low, high) };
+                    let message = format!("ramp from {}% to {}% imported as a range", percent(start), percent(end));
+                    self.losses.push(Loss::new(path, message));
+                }
+                self.single(step_type, element, path, "Duration", power, "Cadence")?
+            }
+            "FreeRide" | "FreeRun" => self.single(Step::Main, element, path, "Duration", None, "Cadence")?,
+            "MaxEffort" => {
+                self.losses.push(Loss::new(path, "max effort imported without a target"));
+                self.single(Step::Main, element, path, "Duration", None, "Cadence")?
+            }
+            "IntervalsT" => return self.intervals(element, path).map(Some),
+            _ => {
+                self.losses.push(Loss::new(path, "unsupported element left out"));
+                return Ok(None);
+            }
+        };
+        Ok(Some(WorkoutStep::Single(single)))
+    }
+
+    fn intervals(&mut self, element: &Element, path: &str) -> Result<WorkoutStep, ZwoError> {
+        let iterations = optional(element, path, "Repeat")?.unwrap_or(1.0).clamp(1.0, u8::MAX as f64) as u8;
+        let on_power = match optional(element, path, "OnPower")? {
+            Some(power) => Some((power, power)),
+            None => self.range(element, path, "PowerOnLow", "PowerOnHigh")?,
+        };
+        let off_power = match optional(element, path, "OffPower")? {
+            Some(power) => Some((power, power)),
+            None => self.range(element, path, "PowerOffLow", "PowerOffHigh")?,
+        };
+
+        let on = self.single(Step::Main, element, path, "OnDuration", on_power, "Cadence")?;
+        let off = if off_power.is_none() && element.get("CadenceResting").is_none() {
+            let (end_condition, value) = self.length(element, path, "OffDuration")?;
+            ExecutableStepDTO::rest_step(0, 0, Cell::new(None), None, end_condition, value)
+        } else {
+            self.single(Step::Main, element, path, "OffDuration", off_power, "CadenceResting")?
+        };
+        let children = vec![WorkoutStep::Single(on), WorkoutStep::Single(off)];
+        Ok(WorkoutStep::Repeat(RepeatGroupDTO::new(0, 0, 0, iterations, children)))
+    }
+
+    fn range(&self, element: &Element, path: &str, low: &str, high: &str) -> Result<Option<(f64, f64)>, ZwoError> {
+        Ok(match (optional(element, path, low)?, optional(element, path, high)?) {
+            (Some(low), Some(high)) => Some((low.min(high), low.max(high))),
+            (Some(power), None) | (None, Some(power)) => Some((power, power)),
+            (None, None) => None,
+        })
+    }
+
+    fn length(&self, element: &Element, path: &str, attribute: &str) -> Result<(EndCondition, f32), ZwoError> {
+        let value = optional(element, path, attribute)?.ok_or_else(|| ZwoError::MissingAttribute {
+            path: path.to_string(),
+            attribute: attribute.to_string(),
+        })? as f32;
+        let condition = if self.distance { Condition::Distance } else { Condition::Time };
+        Ok((EndCondition::new(condition), value))
+    }
+
+    fn single(
+        &mut self,
+        step_type: Step,
+        element: &Element,
+        path: &str,
+        duration: &str,
+        power: Option<(f64, f64)>,
+        cadence: &str,
+    ) -> Result<ExecutableStepDTO, ZwoError> {
+        let (end_condition, value) = self.length(element, path, duration)?;
+        let mut single = convert::active_step(step_type, end_condition, value, None);
+        if self.distance {
+            single.set_distance(value, DistanceUnit::Meter);
+        }
+
+        let cadence = match optional(element, path, cadence)? {
+            Some(cadence) => Some((cadence, cadence)),
+            None if cadence == "Cadence" => self.range(element, path, "CadenceLow", "CadenceHigh")?,
+            None => None,
+        };
+        match (power, cadence) {
+            (Some((low, high)), cadence) => {
+                match self.target(low, high) {
+                    Some(target) => single.set_target(target),
+                    None => {
+                        let message = format!("{}-{}% of threshold speed is no pace, left out", percent(low), percent(high));
+                        self.losses.push(Loss::new(path, message));
+                    }
+                }
+                if let Some((min, max)) = cadence {
+                    let message = format!("cadence {}-{} left out, a Garmin step has one target", min, max);
+                    self.losses.push(Loss::new(path, message));
+                }
+            }
+            (None, Some((min, max))) => {
+                single.set_target(StepTarget::Cadence { min: min.round() as u16, max: max.round() as u16 })
+            }
+            (None, None) => {}
+        }
+
+        let note = element.children.iter().find(|child| child.name.eq_ignore_ascii_case("textevent"));
+        if let Some(message) = note.and_then(|note| note.get("message")) {
+            single.set_description(Some(message.to_string()));
+        }
+        Ok(single)
+    }
+
+    /// A run at no speed has no pace, `None`
+    fn target(&self, low: f64, high: f64) -> Option<StepTarget> {
+        if !self.run {
+            return Some(StepTarget::Power { min_watts: self.athlete.watts(low), max_watts: self.athlete.watts(high) });
+        }
+        (low > 0.0 && high > 0.0).then(|| StepTarget::Pace {
+            slowest_secs_per_km: self.athlete.pace(low).round(),
+            fastest_secs_per_km: self.athlete.pace(high).round(),
+        })
+    }
+}
+
+fn optional(element: &Element, path: &str, attribute: &str) -> Result<Option<f64>, ZwoError> {
+    element
+        .get(attribute)
+        .map(|value| {
+            value.trim().parse().map_err(|_| ZwoError::InvalidNumber {
+                path: path.to_string(),
+                attribute: attribute.to_string(),
+                value: value.to_string(),
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::*;
+    use crate::garmin::timestamp::{self, FixedClock};
+
+    fn clock() -> FixedClock {
+        FixedClock(timestamp::parse("2025-04-03T15:49:08.0").unwrap())
+    }
+
+    #[test]
+    fn test_export_and_import_back() {
+        let workout = workout(Sport::Cycling)
+            .name("Over-unders")
+            .warmup(10.mins(), AnyStroke)
+            .target(StepTarget::Power { min_watts: 125, max_watts: 200 })
+            .repeat(5, |r| {
+                r.main(5.mins(), AnyStroke)
+                    .target(StepTarget::Power { min_watts: 275, max_watts: 275 })
+                    .note("stay seated")
+                    .timed_rest(3.mins())
+            })
+            .cooldown(5.mins(), AnyStroke)
+            .target(StepTarget::Power { min_watts: 100, max_watts: 150 })
+            .build(&clock())
+            .unwrap();
+
+        let (zwo, losses) = export(&workout, &Athlete::default()).unwrap();
+        let messages: Vec<String> = losses.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "workoutSegments[0].workoutSteps[0]: 50-80% range written as a ramp from 50% to 80%",
+                "workoutSegments[0].workoutSteps[2]: 40-60% range written as a ramp from 60% to 40%",
+            ]
+        );
+        assert_eq!(
+            zwo,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<workout_file>
+  <name>Over-unders</name>
+  <sportType>bike</sportType>
+  <tags/>
+  <workout>
+    <Warmup Duration="600" PowerLow="0.5" PowerHigh="0.8"/>
+    <IntervalsT Repeat="5" OnDuration="300" OffDuration="180" OnPower="1.1">
+      <textevent timeoffset="0" message="stay seated"/>
+    </IntervalsT>
+    <Cooldown Duration="300" PowerLow="0.6" PowerHigh="0.4"/>
+  </workout>
+</workout_file>
+"#
+        );
+
+        let (builder, losses) = import(&zwo, &Athlete::default()).unwrap();
+        let imported = builder.build(&clock()).unwrap();
+        let losses: Vec<String> = losses.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            losses,
+            vec![
+                "workout[0] <Warmup>: ramp from 50% to 80% imported as a range",
+                "workout[2] <Cooldown>: ramp from 60% to 40% imported as a range",
+            ]
+        );
+        assert_eq!(export(&imported, &Athlete::default()).unwrap().0, zwo);
+        assert_eq!(imported.validate(), vec![]);
+    }
+
+    #[test]
+    fn test_export_reports_losses() {
+        let workout = workout(Sport::Running)
+            .main(lap_button(), AnyStroke)
+            .main(10.mins(), AnyStroke)
+            .target(StepTarget::HeartRateZone(2))
+            .repeat(2, |r| {
+                r.main(2.mins(), AnyStroke)
+                    .target(StepTarget::Pace { slowest_secs_per_km: 260.0, fastest_secs_per_km: 240.0 })
+                    .timed_rest(1.mins())
+                    .main(1.mins(), AnyStroke)
+            })
+            .build(&clock())
+            .unwrap();
+
+        let (zwo, losses) = export(&workout, &Athlete::default()).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "workoutSegments[0].workoutSteps[0]: step ending on \"lap.button\" left out, ZWO steps last a time",
+                "workoutSegments[0].workoutSteps[1]: heart rate zone 2 target left out",
+                "workoutSegments[0].workoutSteps[2]: repeat unrolled into 2 copies, ZWO only repeats on/off pairs",
+                "workoutSegments[0].workoutSteps[2].workoutSteps[0]: 104-113% range narrowed to 108%",
+            ]
+        );
+        assert_eq!(zwo.matches("<SteadyState Duration=\"120\" Power=\"1.082\"/>").count(), 2);
+
+        let swim = swim().main(100.m(), Free).build(&clock()).unwrap();
+        assert_eq!(export(&swim, &Athlete::default()).err(), Some(ZwoError::UnsupportedSport(Sport::Swimming)));
+    }
+
+    #[test]
+    fn test_import_zwift_file() {
+        let zwo = r#"<workout_file>
+    <author>Zwift</author>
+    <name>Run drills</name>
+    <description>Strides &amp; tempo</description>
+    <sportType>run</sportType>
+    <workout>
+        <Warmup Duration="600" PowerLow="0.6" PowerHigh="0.9" pace="0"/>
+        <IntervalsT Repeat="6" OnDuration="20" OffDuration="40" OnPower="1.2" OffPower="0.6" Cadence="180"/>
+        <SteadyState Duration="1200" Power="1.0">
+            <textevent timeoffset="0" message="Hold threshold"/>
+        </SteadyState>
+        <SolidState Duration="60"/>
+        <FreeRide Duration="300"/>
+    </workout>
+</workout_file>"#;
+        let (builder, losses) = import(zwo, &Athlete::default()).unwrap();
+        let messages: Vec<String> = losses.iter().map(Loss::to_string).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "workout[0] <Warmup>: ramp from 60% to 90% imported as a range",
+                "workout[1] <IntervalsT>: cadence 180-180 left out, a Garmin step has one target",
+                "workout[3] <SolidState>: unsupported element left out",
+            ]
+        );
+
+        let workout = builder.build(&clock()).unwrap();
+        assert_eq!(workout.sport_type().sport(), Sport::Running);
+        assert_eq!(workout.description(), Some("Strides & tempo"));
+        let steps = workout.workout_segments()[0].workout_steps();
+        assert_eq!(steps.len(), 4);
+        let WorkoutStep::Single(warmup) = &steps[0] else { panic!() };
+        assert_eq!(warmup.target(), Some(StepTarget::Pace { slowest_secs_per_km: 450.0, fastest_secs_per_km: 300.0 }));
+        let WorkoutStep::Repeat(intervals) = &steps[1] else { panic!() };
+        assert_eq!(intervals.number_of_iterations(), 6);
+        let WorkoutStep::Single(threshold) = &steps[2] else { panic!() };
+        assert_eq!(threshold.description(), Some("Hold threshold"));
+        assert_eq!(threshold.target(), Some(StepTarget::Pace { slowest_secs_per_km: 270.0, fastest_secs_per_km: 270.0 }));
+
+        assert_eq!(
+            import("<workout_file><workout><SteadyState Power=\"1\"/></workout></workout_file>", &Athlete::default())
+                .err()
+                .map(|error| error.to_string()),
+            Some("workout[0] <SteadyState>: missing Duration".to_string())
+        );
+    }
+
+    #[test]
+    fn test_zero_thresholds_and_intensities() {
+        let run = "<workout_file><sportType>run</sportType><workout><SteadyState Duration=\"60\" Power=\"0\"/></workout></workout_file>";
+        let (builder, losses) = import(run, &Athlete::default()).unwrap();
+        assert_eq!(losses[0].to_string(), "workout[0] <SteadyState>: 0-0% of threshold speed is no pace, left out");
+        let imported = builder.build(&clock()).unwrap();
+        let WorkoutStep::Single(step) = &imported.workout_segments()[0].workout_steps()[0] else { panic!() };
+        assert_eq!(step.target(), Some(StepTarget::NoTarget));
+
+        let athlete = Athlete { ftp_watts: 0, threshold_pace_secs_per_km: 0.0, ..Athlete::default() };
+        let error = |result: Result<(WorkoutBuilder, Vec<Loss>), ZwoError>| result.err().map(|error| error.to_string());
+        assert_eq!(error(import(run, &athlete)), Some("the athlete's threshold pace is zero".to_string()));
+        let ride = "<workout_file><workout><SteadyState Duration=\"60\" Power=\"1\"/></workout></workout_file>";
+        assert_eq!(error(import(ride, &athlete)), Some("the athlete's FTP is zero".to_string()));
+        let workout = workout(Sport::Cycling).main(1.mins(), AnyStroke).build(&clock()).unwrap();
+        assert_eq!(export(&workout, &athlete).err(), Some(ZwoError::ZeroThreshold("FTP")));
+    }
+}